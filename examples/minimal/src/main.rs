@@ -1,13 +1,31 @@
-use jubako::Server;
+use jubako::{
+    dock_window::{DockFloatingPanel, DockLayout, DockLayoutFileStorage, DockWindow},
+    Server,
+};
 
 mod test_window;
-use test_window::TestWindowCreator;
+use test_window::test_window_creator;
 
 #[tokio::main]
 async fn main() {
+    let dock_window = DockWindow::new(DockLayout::horizontal(
+        0.5,
+        DockLayout::tabs(["counter-1", "counter-2"]),
+        DockLayout::tabs(["counter-3"]),
+    ))
+    .panel("counter-1", "Counter 1", test_window_creator())
+    .panel("counter-2", "Counter 2", test_window_creator())
+    .panel("counter-3", "Counter 3", test_window_creator())
+    .panel("counter-4", "Counter 4", test_window_creator())
+    .floating(DockFloatingPanel::new("counter-4", 80, 80, 320, 200))
+    .storage(DockLayoutFileStorage::new("layouts"));
+
     let server = Server::new()
-        .route_simple_window("/test-window", TestWindowCreator);
+        .route_simple_window("/test-window", test_window_creator())
+        .route_dock_window("/dock-window", dock_window)
+        .route_shared_window("/shared-window", test_window_creator());
 
     println!("Open http://localhost:8080/test-window/ in your browser.");
+    println!("Open http://localhost:8080/dock-window/ in your browser.");
     server.run(8080).await;
 }
@@ -1,9 +1,9 @@
 use jubako::{
-    simple_window::{SimpleWindow, SimpleWindowContext, SimpleWindowCreator},
-    vnode::{VNode, VNodeEventHandler, VNodeEventHandlers},
+    model_window::{Model, ModelWindowCreator},
+    simple_window::{SimpleWindowContext, SimpleWindowCreator},
+    vnode::{Props, VNode, VNodeEventHandler, VNodeEventHandlers},
     DirtyFlag,
 };
-use std::sync::{atomic::AtomicI32, Arc};
 
 #[derive(Debug, Clone)]
 pub enum TestMessage {
@@ -11,57 +11,48 @@ pub enum TestMessage {
     Decrement,
 }
 
-#[derive(Debug, Clone)]
-pub struct TestWindowCreator;
-impl SimpleWindowCreator for TestWindowCreator {
-    type Message = TestMessage;
-    fn create(
-        &mut self,
-        ctx: SimpleWindowContext<Self::Message>,
-    ) -> Arc<dyn SimpleWindow<Message = Self::Message>> {
-        Arc::new(TestWindow::new(ctx))
-    }
+pub fn test_window_creator() -> impl SimpleWindowCreator<Message = TestMessage> {
+    ModelWindowCreator::new(TestWindow::new)
 }
 
 #[derive(Debug, Clone)]
 pub struct TestWindow {
     _ctx: SimpleWindowContext<TestMessage>,
-    count: Arc<AtomicI32>,
+    count: i32,
 }
 impl TestWindow {
     pub fn new(ctx: SimpleWindowContext<TestMessage>) -> Self {
         Self {
             _ctx: ctx,
-            count: Arc::new(AtomicI32::new(0)),
+            count: 0,
         }
     }
 }
-impl SimpleWindow for TestWindow {
+impl Model for TestWindow {
     type Message = TestMessage;
 
-    fn update(&self, message: Self::Message) -> DirtyFlag {
+    fn update(&mut self, message: Self::Message) -> DirtyFlag {
         match message {
             TestMessage::Increment => {
-                self.count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                self.count += 1;
                 DirtyFlag::ShouldRender
             }
             TestMessage::Decrement => {
-                self.count.fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
+                self.count -= 1;
                 DirtyFlag::ShouldRender
             }
         }
     }
 
     fn view(&self) -> VNode<Self::Message> {
-        let count = self.count.load(std::sync::atomic::Ordering::SeqCst);
-        let count = count.to_string();
+        let count = self.count.to_string();
 
         let count = VNode::Text { text: count };
 
         let increment = VNode::Element {
             tag: "button".into(),
             class: vec![],
-            props: vec![],
+            props: Props::new(),
             event: VNodeEventHandlers {
                 click: VNodeEventHandler::handle(|_| TestMessage::Increment),
                 ..Default::default()
@@ -76,11 +67,14 @@ impl SimpleWindow for TestWindow {
                 "
                 .into(),
             ),
+            key: None,
+            namespace: None,
+            node_ref: None,
         };
         let decrement = VNode::Element {
             tag: "button".into(),
             class: vec![],
-            props: vec![],
+            props: Props::new(),
             event: VNodeEventHandlers {
                 click: VNodeEventHandler::handle(|_| TestMessage::Decrement),
                 ..Default::default()
@@ -95,11 +89,14 @@ impl SimpleWindow for TestWindow {
                 "
                 .into(),
             ),
+            key: None,
+            namespace: None,
+            node_ref: None,
         };
         let buttons = VNode::Element {
             tag: "div".into(),
             class: vec![],
-            props: vec![],
+            props: Props::new(),
             event: Default::default(),
             children: vec![increment, decrement],
             style: Some(
@@ -110,17 +107,20 @@ impl SimpleWindow for TestWindow {
                 "
                 .into(),
             ),
+            key: None,
+            namespace: None,
+            node_ref: None,
         };
 
         VNode::Element {
             tag: "div".into(),
             class: vec![],
-            props: vec![],
+            props: Props::new(),
             event: Default::default(),
             children: vec![VNode::Element {
                 tag: "div".into(),
                 class: vec![],
-                props: vec![],
+                props: Props::new(),
                 event: Default::default(),
                 children: vec![count, buttons],
                 style: Some(
@@ -134,6 +134,9 @@ impl SimpleWindow for TestWindow {
                     "
                     .into(),
                 ),
+                key: None,
+                namespace: None,
+                node_ref: None,
             }],
             style: Some(
                 "
@@ -144,6 +147,9 @@ impl SimpleWindow for TestWindow {
                 "
                 .into(),
             ),
+            key: None,
+            namespace: None,
+            node_ref: None,
         }
     }
 }
@@ -1,2 +0,0 @@
-(function(){const c=document.createElement("link").relList;if(c&&c.supports&&c.supports("modulepreload"))return;for(const r of document.querySelectorAll('link[rel="modulepreload"]'))e(r);new MutationObserver(r=>{for(const s of r)if(s.type==="childList")for(const o of s.addedNodes)o.tagName==="LINK"&&o.rel==="modulepreload"&&e(o)}).observe(document,{childList:!0,subtree:!0});function n(r){const s={};return r.integrity&&(s.integrity=r.integrity),r.referrerpolicy&&(s.referrerPolicy=r.referrerpolicy),r.crossorigin==="use-credentials"?s.credentials="include":r.crossorigin==="anonymous"?s.credentials="omit":s.credentials="same-origin",s}function e(r){if(r.ep)return;r.ep=!0;const s=n(r);fetch(r.href,s)}})();let D=window.location.host,m=window.location.pathname.split("/");for(let t=0;t<m.length-1;t++)m[t]!==""&&(D+="/"+m[t]);const p=new WebSocket(`ws://${D}/ws`);function Y(t){p.readyState===WebSocket.OPEN&&p.send(JSON.stringify(t))}function K(t){p.onopen=t}function A(t){p.onmessage=c=>{const n=JSON.parse(c.data);t(n)}}const a={send:Y,onopen:K,oncommand:A};var T=(t=>(t.Abort="Abort",t.AnimationCancel="AnimationCancel",t.AnimationEnd="AnimationEnd",t.AnimationIteration="AnimationIteration",t.AnimationStart="AnimationStart",t.AuxClick="AuxClick",t.Blur="Blur",t.Cancel="Cancel",t.CanPlay="CanPlay",t.CanPlayThrough="CanPlayThrough",t.Change="Change",t.Click="Click",t.Close="Close",t.ContextMenu="ContextMenu",t.Copy="Copy",t.CueChange="CueChange",t.Cut="Cut",t.DoubleClick="DoubleClick",t.Drag="Drag",t.DragEnd="DragEnd",t.DragEnter="DragEnter",t.DragLeave="DragLeave",t.DragOver="DragOver",t.DragStart="DragStart",t.Drop="Drop",t.DurationChange="DurationChange",t.Emptied="Emptied",t.Ended="Ended",t.Error="Error",t.Focus="Focus",t.FocusIn="FocusIn",t.FocusOut="FocusOut",t.FormData="FormData",t.GotPointerCapture="GotPointerCapture",t.Input="Input",t.Invalid="Invalid",t.KeyDown="KeyDown",t.KeyPress="KeyPress",t.KeyUp="KeyUp",t.Load="Load",t.LoadedData="LoadedData",t.LoadedMetadata="LoadedMetadata",t.LoadStart="LoadStart",t.LostPointerCapture="LostPointerCapture",t.MouseDown="MouseDown",t.MouseEnter="MouseEnter",t.MouseLeave="MouseLeave",t.MouseMove="MouseMove",t.MouseOut="MouseOut",t.MouseOver="MouseOver",t.MouseUp="MouseUp",t.Paste="Paste",t.Pause="Pause",t.Play="Play",t.Playing="Playing",t.PointerCancel="PointerCancel",t.PointerDown="PointerDown",t.PointerEnter="PointerEnter",t.PointerLeave="PointerLeave",t.PointerMove="PointerMove",t.PointerOut="PointerOut",t.PointerOver="PointerOver",t.PointerUp="PointerUp",t.Progress="Progress",t.RateChange="RateChange",t.Reset="Reset",t.Resize="Resize",t.Scroll="Scroll",t.SecurityPolicyViolation="SecurityPolicyViolation",t.Seeked="Seeked",t.Seeking="Seeking",t.Select="Select",t.SelectionChange="SelectionChange",t.SelectStart="SelectStart",t.SlotChange="SlotChange",t.Stalled="Stalled",t.Submit="Submit",t.Suspend="Suspend",t.TimeUpdate="TimeUpdate",t.Toggle="Toggle",t.TouchCancel="TouchCancel",t.TouchEnd="TouchEnd",t.TouchMove="TouchMove",t.TouchStart="TouchStart",t.TransitionCancel="TransitionCancel",t.TransitionEnd="TransitionEnd",t.TransitionRun="TransitionRun",t.TransitionStart="TransitionStart",t.VolumeChange="VolumeChange",t.Waiting="Waiting",t.Wheel="Wheel",t))(T||{});function _(t){if(t.target!==null&&t.target){const c=t.target;return{target:{tag_name:c.tagName,client_height:c.clientHeight,client_width:c.clientWidth,client_left:c.clientLeft,client_top:c.clientTop,scroll_height:c.scrollHeight,scroll_width:c.scrollWidth,scroll_left:c.scrollLeft,scroll_top:c.scrollTop}}}else return{target:void 0}}function L(t){let c;if(t.target!==null&&t.target!==null){const e=t.target;c={tag_name:e.tagName,client_height:e.clientHeight,client_width:e.clientWidth,client_left:e.clientLeft,client_top:e.clientTop,scroll_height:e.scrollHeight,scroll_width:e.scrollWidth,scroll_left:e.scrollLeft,scroll_top:e.scrollTop}}let n;if(t.relatedTarget!==null&&t.relatedTarget!==null){const e=t.relatedTarget;n={tag_name:e.tagName,client_height:e.clientHeight,client_width:e.clientWidth,client_left:e.clientLeft,client_top:e.clientTop,scroll_height:e.scrollHeight,scroll_width:e.scrollWidth,scroll_left:e.scrollLeft,scroll_top:e.scrollTop}}return{alt_key:t.altKey,button:t.button,buttons:t.buttons,client_x:t.clientX,client_y:t.clientY,ctrl_key:t.ctrlKey,meta_key:t.metaKey,movement_x:t.movementX,movement_y:t.movementY,offset_x:t.offsetX,offset_y:t.offsetY,page_x:t.pageX,page_y:t.pageY,related_target:n,screen_x:t.screenX,screen_y:t.screenY,shift_key:t.shiftKey,target:c,x:t.x,y:t.y}}function h(t){let c;if(t.target!==null&&t.target!==null){const e=t.target;c={tag_name:e.tagName,client_height:e.clientHeight,client_width:e.clientWidth,client_left:e.clientLeft,client_top:e.clientTop,scroll_height:e.scrollHeight,scroll_width:e.scrollWidth,scroll_left:e.scrollLeft,scroll_top:e.scrollTop}}let n;if(t.relatedTarget!==null&&t.relatedTarget!==null){const e=t.relatedTarget;n={tag_name:e.tagName,client_height:e.clientHeight,client_width:e.clientWidth,client_left:e.clientLeft,client_top:e.clientTop,scroll_height:e.scrollHeight,scroll_width:e.scrollWidth,scroll_left:e.scrollLeft,scroll_top:e.scrollTop}}return{related_target:n,target:c}}function k(t){let c;if(t.target!==null&&t.target!==null){const e=t.target;c={tag_name:e.tagName,client_height:e.clientHeight,client_width:e.clientWidth,client_left:e.clientLeft,client_top:e.clientTop,scroll_height:e.scrollHeight,scroll_width:e.scrollWidth,scroll_left:e.scrollLeft,scroll_top:e.scrollTop}}let n;if(t.relatedTarget!==null&&t.relatedTarget!==null){const e=t.relatedTarget;n={tag_name:e.tagName,client_height:e.clientHeight,client_width:e.clientWidth,client_left:e.clientLeft,client_top:e.clientTop,scroll_height:e.scrollHeight,scroll_width:e.scrollWidth,scroll_left:e.scrollLeft,scroll_top:e.scrollTop}}return{alt_key:t.altKey,button:t.button,buttons:t.buttons,client_x:t.clientX,client_y:t.clientY,ctrl_key:t.ctrlKey,meta_key:t.metaKey,movement_x:t.movementX,movement_y:t.movementY,offset_x:t.offsetX,offset_y:t.offsetY,page_x:t.pageX,page_y:t.pageY,related_target:n,screen_x:t.screenX,screen_y:t.screenY,shift_key:t.shiftKey,target:c,x:t.x,y:t.y}}function w(t){var n;let c;if(t.target!==null&&t.target!==null){const e=t.target;c={tag_name:e.tagName,client_height:e.clientHeight,client_width:e.clientWidth,client_left:e.clientLeft,client_top:e.clientTop,scroll_height:e.scrollHeight,scroll_width:e.scrollWidth,scroll_left:e.scrollLeft,scroll_top:e.scrollTop}}return{data:(n=t.data)!=null?n:"",input_type:t.inputType,is_composing:t.isComposing,target:c}}function v(t){let c;if(t.target!==null&&t.target!==null){const n=t.target;c={tag_name:n.tagName,client_height:n.clientHeight,client_width:n.clientWidth,client_left:n.clientLeft,client_top:n.clientTop,scroll_height:n.scrollHeight,scroll_width:n.scrollWidth,scroll_left:n.scrollLeft,scroll_top:n.scrollTop}}return{alt_key:t.altKey,char_code:t.charCode,code:t.code,ctrl_key:t.ctrlKey,key:t.key,key_code:t.keyCode,location:t.location,meta_key:t.metaKey,repeat:t.repeat,shift_key:t.shiftKey,target:c}}function C(t){let c;if(t.target!==null&&t.target!==null){const n=t.target;c={tag_name:n.tagName,client_height:n.clientHeight,client_width:n.clientWidth,client_left:n.clientLeft,client_top:n.clientTop,scroll_height:n.scrollHeight,scroll_width:n.scrollWidth,scroll_left:n.scrollLeft,scroll_top:n.scrollTop}}return{length_computable:t.lengthComputable,loaded:t.loaded,target:c,total:t.total}}function x(t){let c;if(t.target!==null&&t.target!==null){const e=t.target;c={tag_name:e.tagName,client_height:e.clientHeight,client_width:e.clientWidth,client_left:e.clientLeft,client_top:e.clientTop,scroll_height:e.scrollHeight,scroll_width:e.scrollWidth,scroll_left:e.scrollLeft,scroll_top:e.scrollTop}}let n;if(t.submitter!==null&&t.submitter!==null){const e=t.submitter;n={tag_name:e.tagName,client_height:e.clientHeight,client_width:e.clientWidth,client_left:e.clientLeft,client_top:e.clientTop,scroll_height:e.scrollHeight,scroll_width:e.scrollWidth,scroll_left:e.scrollLeft,scroll_top:e.scrollTop}}return{submitter:n,target:c}}function P(t){let c;if(t.target!==null&&t.target!==null){const n=t.target;c={tag_name:n.tagName,client_height:n.clientHeight,client_width:n.clientWidth,client_left:n.clientLeft,client_top:n.clientTop,scroll_height:n.scrollHeight,scroll_width:n.scrollWidth,scroll_left:n.scrollLeft,scroll_top:n.scrollTop}}return{alt_key:t.altKey,button:t.button,buttons:t.buttons,client_x:t.clientX,client_y:t.clientY,ctrl_key:t.ctrlKey,delta_mode:t.deltaMode,delta_x:t.deltaX,delta_y:t.deltaY,delta_z:t.deltaZ,meta_key:t.metaKey,movement_x:t.movementX,movement_y:t.movementY,offset_x:t.offsetX,offset_y:t.offsetY,page_x:t.pageX,page_y:t.pageY,screen_x:t.screenX,screen_y:t.screenY,shift_key:t.shiftKey,x:t.x,y:t.y,target:c}}function b(t){let c;if(t.target!==null&&t.target!==null){const n=t.target;c={tag_name:n.tagName,client_height:n.clientHeight,client_width:n.clientWidth,client_left:n.clientLeft,client_top:n.clientTop,scroll_height:n.scrollHeight,scroll_width:n.scrollWidth,scroll_left:n.scrollLeft,scroll_top:n.scrollTop}}return{animation_name:t.animationName,elapsed_time:t.elapsedTime,pseudo_element:t.pseudoElement,target:c}}function W(t){let c;if(t.target!==null&&t.target!==null){const e=t.target;c={tag_name:e.tagName,client_height:e.clientHeight,client_width:e.clientWidth,client_left:e.clientLeft,client_top:e.clientTop,scroll_height:e.scrollHeight,scroll_width:e.scrollWidth,scroll_left:e.scrollLeft,scroll_top:e.scrollTop}}let n;if(t.relatedTarget!==null&&t.relatedTarget!==null){const e=t.relatedTarget;n={tag_name:e.tagName,client_height:e.clientHeight,client_width:e.clientWidth,client_left:e.clientLeft,client_top:e.clientTop,scroll_height:e.scrollHeight,scroll_width:e.scrollWidth,scroll_left:e.scrollLeft,scroll_top:e.scrollTop}}return{alt_key:t.altKey,button:t.button,buttons:t.buttons,client_x:t.clientX,client_y:t.clientY,ctrl_key:t.ctrlKey,height:t.height,is_primary:t.isPrimary,meta_key:t.metaKey,movement_x:t.movementX,movement_y:t.movementY,offset_x:t.offsetX,offset_y:t.offsetY,page_x:t.pageX,page_y:t.pageY,pointer_id:t.pointerId,pointer_type:t.pointerType,pressure:t.pressure,related_target:n,screen_x:t.screenX,screen_y:t.screenY,shift_key:t.shiftKey,target:c,tilt_x:t.tiltX,tilt_y:t.tiltY,twist:t.twist,width:t.width,x:t.x,y:t.y}}function H(t){let c;if(t.target!==null&&t.target!==null){const s=t.target;c={tag_name:s.tagName,client_height:s.clientHeight,client_width:s.clientWidth,client_left:s.clientLeft,client_top:s.clientTop,scroll_height:s.scrollHeight,scroll_width:s.scrollWidth,scroll_left:s.scrollLeft,scroll_top:s.scrollTop}}const n=[];for(let s=0;s<t.changedTouches.length;s++){const o=t.changedTouches[s];let i;if(o.target!==null&&o.target!==null){const l=o.target;i={tag_name:l.tagName,client_height:l.clientHeight,client_width:l.clientWidth,client_left:l.clientLeft,client_top:l.clientTop,scroll_height:l.scrollHeight,scroll_width:l.scrollWidth,scroll_left:l.scrollLeft,scroll_top:l.scrollTop}}n.push({identifier:o.identifier,client_x:o.clientX,client_y:o.clientY,page_x:o.pageX,page_y:o.pageY,radius_x:o.radiusX,radius_y:o.radiusY,rotation_angle:o.rotationAngle,screen_x:o.screenX,screen_y:o.screenY,target:i})}const e=[];for(let s=0;s<t.targetTouches.length;s++){const o=t.targetTouches[s];let i;if(o.target!==null&&o.target!==null){const l=o.target;i={tag_name:l.tagName,client_height:l.clientHeight,client_width:l.clientWidth,client_left:l.clientLeft,client_top:l.clientTop,scroll_height:l.scrollHeight,scroll_width:l.scrollWidth,scroll_left:l.scrollLeft,scroll_top:l.scrollTop}}e.push({identifier:o.identifier,client_x:o.clientX,client_y:o.clientY,page_x:o.pageX,page_y:o.pageY,radius_x:o.radiusX,radius_y:o.radiusY,rotation_angle:o.rotationAngle,screen_x:o.screenX,screen_y:o.screenY,target:i})}const r=[];for(let s=0;s<t.touches.length;s++){const o=t.touches[s];let i;if(o.target!==null&&o.target!==null){const l=o.target;i={tag_name:l.tagName,client_height:l.clientHeight,client_width:l.clientWidth,client_left:l.clientLeft,client_top:l.clientTop,scroll_height:l.scrollHeight,scroll_width:l.scrollWidth,scroll_left:l.scrollLeft,scroll_top:l.scrollTop}}r.push({identifier:o.identifier,client_x:o.clientX,client_y:o.clientY,page_x:o.pageX,page_y:o.pageY,radius_x:o.radiusX,radius_y:o.radiusY,rotation_angle:o.rotationAngle,screen_x:o.screenX,screen_y:o.screenY,target:i})}return{alt_key:t.altKey,changed_touches:n,ctrl_key:t.ctrlKey,meta_key:t.metaKey,shift_key:t.shiftKey,target_touches:e,touches:r,target:c}}function S(t){let c;if(t.target!==null&&t.target!==null){const n=t.target;c={tag_name:n.tagName,client_height:n.clientHeight,client_width:n.clientWidth,client_left:n.clientLeft,client_top:n.clientTop,scroll_height:n.scrollHeight,scroll_width:n.scrollWidth,scroll_left:n.scrollLeft,scroll_top:n.scrollTop}}return{elapsed_time:t.elapsedTime,property_name:t.propertyName,pseudo_element:t.pseudoElement,target:c}}function y(t,c){const n=t,{handle_id:e,handle_events:r,handle_prevent_default_events:s}=c;for(const o of r)if(o==="Cancel"){n.c instanceof Function&&t.removeEventListener("cancel",n.c);const i=l=>{const f=_(l);a.send({handle_id:e,kind:{type:"Cancel",content:f}})};t.addEventListener("cancel",i),n.c=i}else if(o==="FocusIn"){n.fi instanceof Function&&n.removeEventListener("focusin",n.fi);const i=l=>{const f=h(l);a.send({handle_id:e,kind:{type:"FocusIn",content:f}})};t.addEventListener("focusin",i),n.fi=i}else if(o==="FocusOut"){n.fi instanceof Function&&n.removeEventListener("focusout",n.fi);const i=l=>{const f=h(l);a.send({handle_id:e,kind:{type:"FocusOut",content:f}})};t.addEventListener("focusout",i),n.fi=i}else if(o==="Resize"){const i=new ResizeObserver(l=>{for(let f of l){let g=null;if(f.target!==null||f.target!==null){const d=f.target;g={element_name:d.tagName,client_height:d.clientHeight,client_width:d.clientWidth,client_left:d.clientLeft,client_top:d.clientTop,scroll_height:d.scrollHeight,scroll_width:d.scrollWidth,scroll_left:d.scrollLeft,scroll_top:d.scrollTop}}a.send({handle_id:e,kind:{type:"Resize",content:g}})}});i.observe(t),n.ro=i}else n[`on${o.toLowerCase()}`]=i=>{if(i instanceof DragEvent){const l=k(i);a.send({handle_id:e,kind:{type:o,content:l}})}else if(i instanceof FocusEvent){const l=h(i);a.send({handle_id:e,kind:{type:o,content:l}})}else if(i instanceof InputEvent){const l=w(i);a.send({handle_id:e,kind:{type:o,content:l}})}else if(i instanceof MouseEvent){const l=L(i);a.send({handle_id:e,kind:{type:o,content:l}})}else if(i instanceof TouchEvent){const l=H(i);a.send({handle_id:e,kind:{type:o,content:l}})}else if(i instanceof WheelEvent){const l=P(i);a.send({handle_id:e,kind:{type:o,content:l}})}else if(i instanceof SubmitEvent){const l=x(i);a.send({handle_id:e,kind:{type:o,content:l}})}else if(i instanceof PointerEvent){const l=W(i);a.send({handle_id:e,kind:{type:o,content:l}})}else if(i instanceof KeyboardEvent){const l=v(i);a.send({handle_id:e,kind:{type:o,content:l}})}else if(i instanceof ProgressEvent){const l=C(i);a.send({handle_id:e,kind:{type:o,content:l}})}else if(i instanceof AnimationEvent){const l=b(i);a.send({handle_id:e,kind:{type:o,content:l}})}else if(i instanceof TransitionEvent){const l=S(i);a.send({handle_id:e,kind:{type:o,content:l}})}else{const l=_(i);a.send({handle_id:e,kind:{type:o,content:l}})}};for(const o of s)if(o==="Cancel"){n.c instanceof Function&&t.removeEventListener("cancel",n.c);const i=l=>{l.preventDefault();const f=_(l);a.send({handle_id:e,kind:{type:"Cancel",content:f}})};t.addEventListener("cancel",i),n.c=i}else if(o==="FocusIn"){n.fi instanceof Function&&n.removeEventListener("focusin",n.fi);const i=l=>{l.preventDefault();const f=h(l);a.send({handle_id:e,kind:{type:"FocusIn",content:f}})};t.addEventListener("focusin",i),n.fi=i}else if(o==="FocusOut"){n.fi instanceof Function&&n.removeEventListener("focusout",n.fi);const i=l=>{l.preventDefault();const f=h(l);a.send({handle_id:e,kind:{type:"FocusOut",content:f}})};t.addEventListener("focusout",i),n.fi=i}else if(o==="Resize"){const i=new ResizeObserver(l=>{for(let f of l){let g=null;if(f.target!==null||f.target!==null){const d=f.target;g={element_name:d.tagName,client_height:d.clientHeight,client_width:d.clientWidth,client_left:d.clientLeft,client_top:d.clientTop,scroll_height:d.scrollHeight,scroll_width:d.scrollWidth,scroll_left:d.scrollLeft,scroll_top:d.scrollTop}}a.send({handle_id:e,kind:{type:"Resize",content:g}})}});i.observe(t),n.ro=i}else n[`on${o.toLowerCase()}`]=i=>{if(i.preventDefault(),i instanceof DragEvent){const l=k(i);a.send({handle_id:e,kind:{type:o,content:l}})}else if(i instanceof FocusEvent){const l=h(i);a.send({handle_id:e,kind:{type:o,content:l}})}else if(i instanceof InputEvent){const l=w(i);a.send({handle_id:e,kind:{type:o,content:l}})}else if(i instanceof MouseEvent){const l=L(i);a.send({handle_id:e,kind:{type:o,content:l}})}else if(i instanceof TouchEvent){const l=H(i);a.send({handle_id:e,kind:{type:o,content:l}})}else if(i instanceof WheelEvent){const l=P(i);a.send({handle_id:e,kind:{type:o,content:l}})}else if(i instanceof SubmitEvent){const l=x(i);a.send({handle_id:e,kind:{type:o,content:l}})}else if(i instanceof PointerEvent){const l=W(i);a.send({handle_id:e,kind:{type:o,content:l}})}else if(i instanceof KeyboardEvent){const l=v(i);a.send({handle_id:e,kind:{type:o,content:l}})}else if(i instanceof ProgressEvent){const l=C(i);a.send({handle_id:e,kind:{type:o,content:l}})}else if(i instanceof AnimationEvent){const l=b(i);a.send({handle_id:e,kind:{type:o,content:l}})}else if(i instanceof TransitionEvent){const l=S(i);a.send({handle_id:e,kind:{type:o,content:l}})}else{const l=_(i);a.send({handle_id:e,kind:{type:o,content:l}})}};for(const o of Object.keys(T).filter(i=>!r.includes(i)&&!s.includes(i)))o==="Cancel"?n.c instanceof Function&&n.removeEventListener("cancel",n.c):o==="FocusIn"?n.fi instanceof Function&&n.removeEventListener("focusin",n.fi):o==="FocusOn"?n.fo instanceof Function&&n.removeEventListener("focusout",n.fo):o==="Resize"?n.ro instanceof ResizeObserver&&n.ro.disconnect():n[`on${o.toLowerCase()}`]=null}function u(t,c){if(c.type==="UpdateElement"){const{index:n,class_diff:e,props_diff:r,event_diff:s,children:o}=c.content,i=t.childNodes[n];if(i===void 0)return;if(i instanceof HTMLElement){for(const l of e)l.type==="Add"?i.classList.add(l.content):l.type==="Remove"&&i.classList.remove(l.content);for(const l of r)if(l.type==="Add")if(l.content.split("=").length===2){const[f,g]=l.content.split("=");i.setAttribute(f,g)}else i.setAttribute(l.content,"true");else if(l.type==="Remove")if(l.content.split("=").length===2){const[f,g]=l.content.split("=");i.removeAttribute(f)}else i.removeAttribute(l.content);if(s.type==="Update"){const l=s.content;y(i,l)}}for(const l of o)u(i,l)}else if(c.type==="UpdateText"){const{index:n,new_text:e}=c.content,r=t.childNodes[n];if(r===void 0)return;r.textContent=e}else if(c.type==="ReplaceToElement"){const{index:n,new_tag:e,classes:r,props:s,event:o,children:i}=c.content,l=t.childNodes[n];if(l===void 0)return;const f=document.createElement(e);f.classList.add(...r);for(const g of s)if(g.split("=").length===2){const[d,X]=g.split("=");f.setAttribute(d,X)}else f.setAttribute(g,"true");y(f,o);for(const g of i)u(f,g);t.replaceChild(f,l)}else if(c.type==="ReplaceToText"){const{index:n,text:e}=c.content,r=t.childNodes[n];if(r===void 0)return;const s=document.createTextNode(e);t.replaceChild(s,r)}else if(c.type==="InsertElement"){const{index:n,tag:e,classes:r,props:s,event:o,children:i}=c.content,l=document.createElement(e);l.classList.add(...r);for(const f of s)if(f.split("=").length===2){const[g,d]=f.split("=");l.setAttribute(g,d)}else l.setAttribute(f,"true");y(l,o);for(const f of i)u(l,f);t.insertBefore(l,t.childNodes[n])}else if(c.type==="InsertText"){const{index:n,text:e}=c.content,r=document.createTextNode(e);t.insertBefore(r,t.childNodes[n])}else if(c.type==="Remove"){const{index:n}=c.content,e=t.childNodes[n];if(e===void 0)return;t.removeChild(e)}}function F(t){var c;if(t.type==="AddStyle"){const{class_name:n,value:e}=t.content,r=document.head||document.getElementsByTagName("head")[0],s=document.createElement("style");s.id=n,s.appendChild(document.createTextNode(e)),r.appendChild(s)}else if(t.type==="RemoveStyle"){const{class_name:n}=t.content;(c=document.getElementById(n))==null||c.remove()}}const M=async()=>{const t=()=>{a.send("DRAW"),requestAnimationFrame(t)};a.onopen(()=>{t()});const c=document.querySelector("#app"),n=document.querySelector("#portal");a.oncommand(e=>{if(!(c===null||n===null)){for(const r of e.main)u(c,r);for(const r of e.portals)u(n,r);for(const r of e.styles)F(r)}})};document.addEventListener("DOMContentLoaded",M);
-//# sourceMappingURL=index-085e0b06.js.map
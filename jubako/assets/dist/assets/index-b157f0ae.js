@@ -0,0 +1,1454 @@
+/*
+ Generated by typeshare 1.0.0
+*/
+
+const VNodeEventType = {
+	Abort: "Abort",
+	AnimationCancel: "AnimationCancel",
+	AnimationEnd: "AnimationEnd",
+	AnimationIteration: "AnimationIteration",
+	AnimationStart: "AnimationStart",
+	AuxClick: "AuxClick",
+	Blur: "Blur",
+	Cancel: "Cancel",
+	CanPlay: "CanPlay",
+	CanPlayThrough: "CanPlayThrough",
+	Change: "Change",
+	Click: "Click",
+	Close: "Close",
+	ContextMenu: "ContextMenu",
+	Copy: "Copy",
+	CueChange: "CueChange",
+	Cut: "Cut",
+	DoubleClick: "DoubleClick",
+	Drag: "Drag",
+	DragEnd: "DragEnd",
+	DragEnter: "DragEnter",
+	DragLeave: "DragLeave",
+	DragOver: "DragOver",
+	DragStart: "DragStart",
+	Drop: "Drop",
+	DurationChange: "DurationChange",
+	Emptied: "Emptied",
+	Ended: "Ended",
+	Error: "Error",
+	Focus: "Focus",
+	FocusIn: "FocusIn",
+	FocusOut: "FocusOut",
+	FormData: "FormData",
+	GotPointerCapture: "GotPointerCapture",
+	Input: "Input",
+	Invalid: "Invalid",
+	KeyDown: "KeyDown",
+	KeyPress: "KeyPress",
+	KeyUp: "KeyUp",
+	Load: "Load",
+	LoadedData: "LoadedData",
+	LoadedMetadata: "LoadedMetadata",
+	LoadStart: "LoadStart",
+	LostPointerCapture: "LostPointerCapture",
+	MouseDown: "MouseDown",
+	MouseEnter: "MouseEnter",
+	MouseLeave: "MouseLeave",
+	MouseMove: "MouseMove",
+	MouseOut: "MouseOut",
+	MouseOver: "MouseOver",
+	MouseUp: "MouseUp",
+	Paste: "Paste",
+	Pause: "Pause",
+	Play: "Play",
+	Playing: "Playing",
+	PointerCancel: "PointerCancel",
+	PointerDown: "PointerDown",
+	PointerEnter: "PointerEnter",
+	PointerLeave: "PointerLeave",
+	PointerMove: "PointerMove",
+	PointerOut: "PointerOut",
+	PointerOver: "PointerOver",
+	PointerUp: "PointerUp",
+	Progress: "Progress",
+	RateChange: "RateChange",
+	Reset: "Reset",
+	Resize: "Resize",
+	Scroll: "Scroll",
+	SecurityPolicyViolation: "SecurityPolicyViolation",
+	Seeked: "Seeked",
+	Seeking: "Seeking",
+	Select: "Select",
+	SelectionChange: "SelectionChange",
+	SelectStart: "SelectStart",
+	SlotChange: "SlotChange",
+	Stalled: "Stalled",
+	Submit: "Submit",
+	Suspend: "Suspend",
+	TimeUpdate: "TimeUpdate",
+	Toggle: "Toggle",
+	TouchCancel: "TouchCancel",
+	TouchEnd: "TouchEnd",
+	TouchMove: "TouchMove",
+	TouchStart: "TouchStart",
+	TransitionCancel: "TransitionCancel",
+	TransitionEnd: "TransitionEnd",
+	TransitionRun: "TransitionRun",
+	TransitionStart: "TransitionStart",
+	VolumeChange: "VolumeChange",
+	Waiting: "Waiting",
+	Wheel: "Wheel",
+}
+
+const ScrollBehavior = {
+	Auto: "auto",
+	Smooth: "smooth",
+	Instant: "instant",
+}
+
+const ScrollAlignment = {
+	Start: "start",
+	Center: "center",
+	End: "end",
+	Nearest: "nearest",
+}
+
+const Namespace = {
+	Html: "Html",
+	Svg: "Svg",
+	MathMl: "MathMl",
+}
+
+const OfflineEventPolicy = {
+	Discard: "Discard",
+	Queue: "Queue",
+}
+
+// overlay that covers the window while the connection to the server is lost
+
+let overlay = null
+
+function showOverlay(text) {
+  if (overlay === null) {
+    overlay = document.createElement("div")
+    overlay.id = "connection-overlay"
+    document.body.appendChild(overlay)
+  }
+  overlay.textContent = text
+}
+
+function hideOverlay() {
+  overlay?.remove()
+  overlay = null
+}
+
+let path = window.location.host
+let paths = window.location.pathname.split("/");
+for (let i = 0; i < paths.length - 1; i++) {
+  if (paths[i] === "") continue
+  path += "/" + paths[i]
+}
+
+// delay of the reconnection doubles on each failure, up to the max delay
+const RECONNECT_BASE_DELAY = 500
+const RECONNECT_MAX_DELAY = 10000
+
+// close codes of the server, kept in sync with simple_window.rs
+// another browser window resumed the session, e.g. a duplicated tab
+const CLOSE_SUPERSEDED = 4001
+// the session of the pop-out window has ended
+const CLOSE_SESSION_EXPIRED = 4002
+
+const sessionKey = `jubako-session:${path}`
+let websocket
+let reconnectAttempts = 0
+let offlineEvents = OfflineEventPolicy.Discard
+let queuedEvents = []
+// events sent over the current connection
+let sentCount = 0
+
+// frames pushed by the server before the callback is registered
+let pendingCommands = []
+let commandCallback = null
+
+// pop-out windows pass `?session=` in the page url,
+// other windows resume the session that they were connected to
+function url() {
+  const query = new URLSearchParams(window.location.search)
+  const resumeId = sessionStorage.getItem(sessionKey)
+  if (!query.has("session") && resumeId !== null) {
+    query.set("resume", resumeId)
+  }
+  const search = query.toString()
+  return `ws://${path}/ws${search === "" ? "" : "?" + search}`
+}
+
+function connect() {
+  websocket = new WebSocket(url())
+
+  websocket.onopen = () => {
+    reconnectAttempts = 0
+    hideOverlay()
+    sentCount = 0
+    for (const event of queuedEvents) {
+      websocket.send(event)
+      sentCount += 1
+    }
+    queuedEvents = []
+  }
+
+  websocket.onmessage = (evt) => {
+    const cmds = JSON.parse(evt.data)
+    if (commandCallback === null) {
+      pendingCommands.push(cmds)
+    } else {
+      commandCallback(cmds)
+    }
+  }
+
+  websocket.onclose = (evt) => {
+    if (evt.code === CLOSE_SUPERSEDED) {
+      // leave the session to the other window and start a new one
+      sessionStorage.removeItem(sessionKey)
+      reconnectAttempts = 0
+      connect()
+      return
+    }
+    if (evt.code === CLOSE_SESSION_EXPIRED) {
+      showOverlay("This window has been closed.")
+      window.close()
+      return
+    }
+    const delay = Math.min(RECONNECT_BASE_DELAY * 2 ** reconnectAttempts, RECONNECT_MAX_DELAY)
+    reconnectAttempts += 1
+    showOverlay(reconnectAttempts === 1 ? "Disconnected. Reconnecting..." : "Reconnecting...")
+    setTimeout(connect, delay * (0.75 + Math.random() * 0.5))
+  }
+}
+connect()
+
+function setSession(id) {
+  sessionStorage.setItem(sessionKey, id)
+}
+
+function setOfflineEvents(policy) {
+  offlineEvents = policy
+}
+
+// send an event, events sent while disconnected are queued or discarded by the policy
+function send(obj) {
+  const message = JSON.stringify(obj)
+  if (websocket.readyState === WebSocket.OPEN) {
+    websocket.send(message)
+    sentCount += 1
+  } else if (offlineEvents === OfflineEventPolicy.Queue) {
+    queuedEvents.push(message)
+  }
+}
+
+// acknowledge the frame, acknowledgements are meaningless for the next connection
+function sendAck(frame) {
+  if (websocket.readyState === WebSocket.OPEN) {
+    const ack = { frame }
+    websocket.send(JSON.stringify(ack))
+  }
+}
+
+// answer the element query, answers are meaningless for the next connection
+function sendQueryResponse(response) {
+  if (websocket.readyState === WebSocket.OPEN) {
+    websocket.send(JSON.stringify(response))
+  }
+}
+
+function sentEvents() {
+  return sentCount
+}
+
+function oncommand(callback) {
+  commandCallback = callback
+  for (const cmds of pendingCommands) {
+    callback(cmds)
+  }
+  pendingCommands = []
+}
+
+const connection = { send, sendAck, sendQueryResponse, sentEvents, oncommand, setSession, setOfflineEvents }
+
+// while the server has not handled the input of an element, values pushed by the server are stale,
+// so they are held back not to overwrite what the user is typing
+const pendingInputs = new Map()
+// latest values of the DOM properties pushed by the server
+const serverValues = new WeakMap()
+
+// record that the input or change event of the element is sent
+function recordInput(elem) {
+  pendingInputs.set(elem, connection.sentEvents())
+}
+
+// record that the input or change event of the element waits for the rate of its handler,
+// the values pushed by the server are held back until it is sent and handled
+function holdInput(elem) {
+  pendingInputs.set(elem, Infinity)
+}
+
+// set DOM property pushed by the server
+function setProperty(elem, name, value) {
+  let values = serverValues.get(elem)
+  if (values === undefined) {
+    values = new Map()
+    serverValues.set(elem, values)
+  }
+  values.set(name, value)
+  if (!pendingInputs.has(elem)) {
+    writeProperty(elem, name, value)
+  }
+}
+
+function writeProperty(elem, name, value) {
+  const target = elem
+  if (target[name] === value) return
+  // keep the caret of the focused element where it was
+  if (name === "value" && elem === document.activeElement) {
+    let start = null
+    let end = null
+    try {
+      start = target.selectionStart
+      end = target.selectionEnd
+    } catch {
+      // the input type does not support selection
+    }
+    target[name] = value
+    if (start !== null && end !== null) {
+      try {
+        target.setSelectionRange(start, end)
+      } catch {
+        // the input type does not support selection
+      }
+    }
+  } else {
+    target[name] = value
+  }
+}
+
+// the server rendered the frame after handling `events` events of this connection,
+// so the values of the inputs sent until then are settled by the server
+function syncHandledInputs(events) {
+  for (const [elem, sent] of pendingInputs) {
+    if (sent > events) continue
+    pendingInputs.delete(elem)
+    const values = serverValues.get(elem)
+    if (values === undefined) continue
+    for (const [name, value] of values) {
+      writeProperty(elem, name, value)
+    }
+  }
+}
+
+// forget the inputs of the node and its descendants, called when the node is removed from the DOM
+function forgetInputs(node) {
+  for (const elem of pendingInputs.keys()) {
+    if (node.contains(elem)) pendingInputs.delete(elem)
+  }
+}
+
+// forget the inputs of the previous connection
+function resetControlledInputs() {
+  pendingInputs.clear()
+}
+
+// fields of the payloads that are summed up by `Accumulate`
+const DELTA_FIELDS = ["delta_x", "delta_y", "delta_z", "movement_x", "movement_y"]
+
+// sending the payloads of a listener at the rate declared by the handler.
+function rateLimiter(rate, send) {
+  switch (rate.type) {
+    case "Immediate":
+      return { push: send, flush: () => {} }
+    case "Throttle":
+      return throttle(rate.content, send)
+    case "Debounce":
+      return debounce(rate.content, send)
+    case "Coalesce":
+      return perFrame(send, (_, latest) => latest)
+    case "Accumulate":
+      return perFrame(send, accumulate)
+  }
+}
+
+function throttle(interval, send) {
+  let pending
+  let timer
+
+  const tick = () => {
+    if (pending === undefined) {
+      timer = undefined
+      return
+    }
+    send(pending.payload)
+    pending = undefined
+    timer = window.setTimeout(tick, interval)
+  }
+  return {
+    push(payload) {
+      if (timer === undefined) {
+        send(payload)
+        timer = window.setTimeout(tick, interval)
+      } else {
+        pending = { payload }
+      }
+    },
+    flush() {
+      window.clearTimeout(timer)
+      timer = undefined
+      if (pending !== undefined) send(pending.payload)
+      pending = undefined
+    },
+  }
+}
+
+function debounce(delay, send) {
+  let pending
+  let timer
+
+  const fire = () => {
+    timer = undefined
+    if (pending !== undefined) send(pending.payload)
+    pending = undefined
+  }
+  return {
+    push(payload) {
+      pending = { payload }
+      window.clearTimeout(timer)
+      timer = window.setTimeout(fire, delay)
+    },
+    flush() {
+      window.clearTimeout(timer)
+      fire()
+    },
+  }
+}
+
+function perFrame(send, merge) {
+  let pending
+  let frame
+
+  const fire = () => {
+    frame = undefined
+    if (pending !== undefined) send(pending.payload)
+    pending = undefined
+  }
+  return {
+    push(payload) {
+      pending = { payload: pending === undefined ? payload : merge(pending.payload, payload) }
+      if (frame === undefined) frame = window.requestAnimationFrame(fire)
+    },
+    flush() {
+      if (frame !== undefined) window.cancelAnimationFrame(frame)
+      fire()
+    },
+  }
+}
+
+// the latest payload with the deltas of both payloads summed up
+function accumulate(previous, latest) {
+  const merged = { ...latest }
+  for (const field of DELTA_FIELDS) {
+    const p = (previous)[field]
+    const l = (latest)[field]
+    if (typeof p === "number" && typeof l === "number") merged[field] = p + l
+  }
+  return merged
+}
+
+// state of the element, including the user input of form controls
+function elementToPayload(elem) {
+  const e = elem
+  let value = undefined
+  let checked = undefined
+  let selected_options = []
+  let selection_start = undefined
+  let selection_end = undefined
+  if (elem instanceof HTMLInputElement || elem instanceof HTMLTextAreaElement || elem instanceof HTMLSelectElement) {
+    value = elem.value
+  }
+  if (elem instanceof HTMLInputElement && (elem.type === "checkbox" || elem.type === "radio")) {
+    checked = elem.checked
+  }
+  if (elem instanceof HTMLSelectElement) {
+    selected_options = Array.from(elem.selectedOptions).map((option) => option.value)
+  }
+  // selection is not available for some input types such as number
+  try {
+    if (typeof e.selectionStart === "number" && typeof e.selectionEnd === "number") {
+      selection_start = e.selectionStart
+      selection_end = e.selectionEnd
+    }
+  } catch {}
+  return {
+    tag_name: elem.tagName,
+    client_height: elem.clientHeight,
+    client_width: elem.clientWidth,
+    client_left: elem.clientLeft,
+    client_top: elem.clientTop,
+    scroll_height: elem.scrollHeight,
+    scroll_width: elem.scrollWidth,
+    scroll_left: elem.scrollLeft,
+    scroll_top: elem.scrollTop,
+    value,
+    checked,
+    selected_options,
+    selection_start,
+    selection_end,
+  }
+}
+
+function eventToPayload(e) {
+  if (e.target !== null && e.target) {
+    const target = e.target
+    return {
+      target: elementToPayload(target)
+    }
+  } else {
+    return {
+      target: undefined,
+    }
+  }
+}
+
+function mouseEventToPayload(e) {
+  let target = undefined
+  if (e.target !== null && e.target !== null) {
+    const targetElem = e.target
+    target = elementToPayload(targetElem)
+  }
+  let related_target = undefined
+  if (e.relatedTarget !== null && e.relatedTarget !== null) {
+    const relatedTargetElem = e.relatedTarget
+    related_target = elementToPayload(relatedTargetElem)
+  }
+  return {
+    alt_key: e.altKey,
+    button: e.button,
+    buttons: e.buttons,
+    client_x: e.clientX,
+    client_y: e.clientY,
+    ctrl_key: e.ctrlKey,
+    meta_key: e.metaKey,
+    movement_x: e.movementX,
+    movement_y: e.movementY,
+    offset_x: e.offsetX,
+    offset_y: e.offsetY,
+    page_x: e.pageX,
+    page_y: e.pageY,
+    related_target,
+    screen_x: e.screenX,
+    screen_y: e.screenY,
+    shift_key: e.shiftKey,
+    target,
+    x: e.x,
+    y: e.y,
+  }
+}
+
+function focusEventToPayload(e) {
+  let target = undefined
+  if (e.target !== null && e.target !== null) {
+    const targetElem = e.target
+    target = elementToPayload(targetElem)
+  }
+  let related_target = undefined
+  if (e.relatedTarget !== null && e.relatedTarget !== null) {
+    const relatedTargetElem = e.relatedTarget
+    related_target = elementToPayload(relatedTargetElem)
+  }
+  return { related_target, target }
+}
+
+function dragEventToPayload(e) {
+  let target = undefined
+  if (e.target !== null && e.target !== null) {
+    const targetElem = e.target
+    target = elementToPayload(targetElem)
+  }
+  let related_target = undefined
+  if (e.relatedTarget !== null && e.relatedTarget !== null) {
+    const relatedTargetElem = e.relatedTarget
+    related_target = elementToPayload(relatedTargetElem)
+  }
+  return {
+    alt_key: e.altKey,
+    button: e.button,
+    buttons: e.buttons,
+    client_x: e.clientX,
+    client_y: e.clientY,
+    ctrl_key: e.ctrlKey,
+    meta_key: e.metaKey,
+    movement_x: e.movementX,
+    movement_y: e.movementY,
+    offset_x: e.offsetX,
+    offset_y: e.offsetY,
+    page_x: e.pageX,
+    page_y: e.pageY,
+    related_target,
+    screen_x: e.screenX,
+    screen_y: e.screenY,
+    shift_key: e.shiftKey,
+    target,
+    x: e.x,
+    y: e.y,
+  }
+}
+
+function inputEventToPayload(e) {
+  let target = undefined
+  if (e.target !== null && e.target !== null) {
+    const targetElem = e.target
+    target = elementToPayload(targetElem)
+  }
+  return {
+    data: e.data ?? "",
+    input_type: e.inputType,
+    is_composing: e.isComposing,
+    target
+  }
+}
+
+function keyboardEventToPayload(e) {
+  let target = undefined
+  if (e.target !== null && e.target !== null) {
+    const targetElem = e.target
+    target = elementToPayload(targetElem)
+  }
+  return {
+    alt_key: e.altKey,
+    char_code: e.charCode,
+    code: e.code,
+    ctrl_key: e.ctrlKey,
+    key: e.key,
+    key_code: e.keyCode,
+    location: e.location,
+    meta_key: e.metaKey,
+    repeat: e.repeat,
+    shift_key: e.shiftKey,
+    target,
+  }
+}
+
+function progressEventToPayload(e) {
+  let target = undefined
+  if (e.target !== null && e.target !== null) {
+    const targetElem = e.target
+    target = elementToPayload(targetElem)
+  }
+  return {
+    length_computable: e.lengthComputable,
+    loaded: e.loaded,
+    target,
+    total: e.total,
+  }
+}
+
+function submitEventToPayload(e) {
+  let target = undefined
+  if (e.target !== null && e.target !== null) {
+    const targetElem = e.target
+    target = elementToPayload(targetElem)
+  }
+  let submitter = undefined
+  if (e.submitter !== null && e.submitter !== null) {
+    const submitter_elem = e.submitter
+    submitter = elementToPayload(submitter_elem)
+  }
+  let formData = new FormData()
+  if (e.target instanceof HTMLFormElement) {
+    try {
+      // the value of the submitter is included like the web browser submits the form
+      formData = new FormData(e.target, e.submitter)
+    } catch {
+      // the web browser does not support the submitter argument
+      formData = new FormData(e.target)
+    }
+  }
+  return { form_data: formDataToPayload(formData), submitter, target }
+}
+
+function formDataEventToPayload(e) {
+  let target = undefined
+  if (e.target !== null && e.target !== null) {
+    target = elementToPayload(e.target)
+  }
+  return { form_data: formDataToPayload(e.formData), target }
+}
+
+// named fields of the form, only metadata of the files is sent
+function formDataToPayload(formData) {
+  const payload = {}
+  formData.forEach((value, name) => {
+    const values = payload[name] ?? (payload[name] = [])
+    if (typeof value === "string") {
+      values.push({ type: "Text", content: value })
+    } else {
+      values.push({
+        type: "File",
+        content: {
+          name: value.name,
+          size: value.size,
+          mime_type: value.type,
+          last_modified: value.lastModified,
+        }
+      })
+    }
+  })
+  return payload
+}
+
+function wheelEventToPayload(e) {
+  let target = undefined
+  if (e.target !== null && e.target !== null) {
+    const targetElem = e.target
+    target = elementToPayload(targetElem)
+  }
+  return {
+    alt_key: e.altKey,
+    button: e.button,
+    buttons: e.buttons,
+    client_x: e.clientX,
+    client_y: e.clientY,
+    ctrl_key: e.ctrlKey,
+    delta_mode: e.deltaMode,
+    delta_x: e.deltaX,
+    delta_y: e.deltaY,
+    delta_z: e.deltaZ,
+    meta_key: e.metaKey,
+    movement_x: e.movementX,
+    movement_y: e.movementY,
+    offset_x: e.offsetX,
+    offset_y: e.offsetY,
+    page_x: e.pageX,
+    page_y: e.pageY,
+    screen_x: e.screenX,
+    screen_y: e.screenY,
+    shift_key: e.shiftKey,
+    x: e.x,
+    y: e.y,
+    target,
+  }
+}
+
+function animationEventToPayload(e) {
+  let target = undefined
+  if (e.target !== null && e.target !== null) {
+    const targetElem = e.target
+    target = elementToPayload(targetElem)
+  }
+  return {
+    animation_name: e.animationName,
+    elapsed_time: e.elapsedTime,
+    pseudo_element: e.pseudoElement,
+    target,
+  }
+}
+
+function pointerEventToPayload(e) {
+  let target = undefined
+  if (e.target !== null && e.target !== null) {
+    const targetElem = e.target
+    target = elementToPayload(targetElem)
+  }
+
+  let related_target = undefined
+  if (e.relatedTarget !== null && e.relatedTarget !== null) {
+    const relatedTargetElem = e.relatedTarget
+    related_target = elementToPayload(relatedTargetElem)
+  }
+  return {
+    alt_key: e.altKey,
+    button: e.button,
+    buttons: e.buttons,
+    client_x: e.clientX,
+    client_y: e.clientY,
+    ctrl_key: e.ctrlKey,
+    height: e.height,
+    is_primary: e.isPrimary,
+    meta_key: e.metaKey,
+    movement_x: e.movementX,
+    movement_y: e.movementY,
+    offset_x: e.offsetX,
+    offset_y: e.offsetY,
+    page_x: e.pageX,
+    page_y: e.pageY,
+    pointer_id: e.pointerId,
+    pointer_type: e.pointerType,
+    pressure: e.pressure,
+    related_target,
+    screen_x: e.screenX,
+    screen_y: e.screenY,
+    shift_key: e.shiftKey,
+    target,
+    tilt_x: e.tiltX,
+    tilt_y: e.tiltY,
+    twist: e.twist,
+    width: e.width,
+    x: e.x,
+    y: e.y,
+  }
+}
+
+function touchEventToPayload(e) {
+  let target = undefined
+  if (e.target !== null && e.target !== null) {
+    const targetElem = e.target
+    target = elementToPayload(targetElem)
+  }
+  const changed_touches = []
+  for (let i = 0; i < e.changedTouches.length; i++) {
+    const touch = e.changedTouches[i]
+    let touchTarget = undefined
+    if (touch.target !== null && touch.target !== null) {
+      const touchTargetElem = touch.target
+      touchTarget = elementToPayload(touchTargetElem)
+    }
+    changed_touches.push({
+      identifier: touch.identifier,
+      client_x: touch.clientX,
+      client_y: touch.clientY,
+      page_x: touch.pageX,
+      page_y: touch.pageY,
+      radius_x: touch.radiusX,
+      radius_y: touch.radiusY,
+      rotation_angle: touch.rotationAngle,
+      screen_x: touch.screenX,
+      screen_y: touch.screenY,
+      target: touchTarget,
+    })
+  }
+  const target_touches = []
+  for (let i = 0; i < e.targetTouches.length; i++) {
+    const touch = e.targetTouches[i]
+    let touchTarget = undefined
+    if (touch.target !== null && touch.target !== null) {
+      const touchTargetElem = touch.target
+      touchTarget = elementToPayload(touchTargetElem)
+    }
+    target_touches.push({
+      identifier: touch.identifier,
+      client_x: touch.clientX,
+      client_y: touch.clientY,
+      page_x: touch.pageX,
+      page_y: touch.pageY,
+      radius_x: touch.radiusX,
+      radius_y: touch.radiusY,
+      rotation_angle: touch.rotationAngle,
+      screen_x: touch.screenX,
+      screen_y: touch.screenY,
+      target: touchTarget,
+    })
+  }
+  const touches = []
+  for (let i = 0; i < e.touches.length; i++) {
+    const touch = e.touches[i]
+    let touchTarget = undefined
+    if (touch.target !== null && touch.target !== null) {
+      const touchTargetElem = touch.target
+      touchTarget = elementToPayload(touchTargetElem)
+    }
+    touches.push({
+      identifier: touch.identifier,
+      client_x: touch.clientX,
+      client_y: touch.clientY,
+      page_x: touch.pageX,
+      page_y: touch.pageY,
+      radius_x: touch.radiusX,
+      radius_y: touch.radiusY,
+      rotation_angle: touch.rotationAngle,
+      screen_x: touch.screenX,
+      screen_y: touch.screenY,
+      target: touchTarget,
+    })
+  }
+  return {
+    alt_key: e.altKey,
+    changed_touches,
+    ctrl_key: e.ctrlKey,
+    meta_key: e.metaKey,
+    shift_key: e.shiftKey,
+    target_touches,
+    touches,
+    target,
+  }
+}
+
+function transitionEventToPayload(e) {
+  let target = undefined
+  if (e.target !== null && e.target !== null) {
+    const targetElem = e.target
+    target = elementToPayload(targetElem)
+  }
+  return {
+    elapsed_time: e.elapsedTime,
+    property_name: e.propertyName,
+    pseudo_element: e.pseudoElement,
+    target,
+  }
+}
+
+const DEFAULT_OPTIONS = {
+  capture: false,
+  passive: false,
+  once: false,
+  stop_propagation: false,
+  stop_immediate_propagation: false,
+  rate: { type: "Immediate" },
+}
+
+// DOM event names that are not the lowercase of the event type
+const DOM_EVENT_NAMES = {
+  [VNodeEventType.DoubleClick]: "dblclick",
+}
+
+// listeners that jubako added to the elements, removed when the handled events change
+const listeners = new WeakMap()
+// payloads that wait for the rate of the listeners, sent when the listeners are removed
+const limiters = new WeakMap()
+// we want to handle resize event not only when window resized
+// but also when element resized, so we use ResizeObserver.
+const resizeObservers = new WeakMap()
+
+// handling events and send them to jubako server.
+function applyEvent(elem, eventHandle) {
+  const { handle_id, handle_events, handle_prevent_default_events, listener_options } = eventHandle
+
+  removeListeners(elem)
+
+  const listen = (event, preventDefault) => {
+    const options = listener_options[event] ?? DEFAULT_OPTIONS
+    const isInput = event === VNodeEventType.Input || event === VNodeEventType.Change
+    const limiter = rateLimiter(options.rate, (content) => {
+      connection.send({ handle_id, kind: { type: event, content } })
+      if (isInput) recordInput(elem)
+    })
+    let added = limiters.get(elem)
+    if (added === undefined) {
+      added = []
+      limiters.set(elem, added)
+    }
+    added.push(limiter)
+
+    if (event === VNodeEventType.Resize) {
+      const ro = new ResizeObserver((entries) => {
+        for (const entry of entries) {
+          limiter.push({ target: elementToPayload(entry.target) })
+        }
+        if (options.once) ro.disconnect()
+      })
+      ro.observe(elem)
+      resizeObservers.set(elem, ro)
+      return
+    }
+
+    const listener = (e) => {
+      // passive listeners can't prevent the default action
+      if (preventDefault && !options.passive && matches(e, options.prevent_default_if)) {
+        e.preventDefault()
+      }
+      if (options.stop_immediate_propagation) {
+        e.stopImmediatePropagation()
+      } else if (options.stop_propagation) {
+        e.stopPropagation()
+      }
+      if (isInput) holdInput(elem)
+      limiter.push(eventToPayloadOf(e))
+    }
+    const type = DOM_EVENT_NAMES[event] ?? event.toLowerCase()
+    elem.addEventListener(type, listener, {
+      capture: options.capture,
+      passive: options.passive,
+      once: options.once,
+    })
+
+    let addedListeners = listeners.get(elem)
+    if (addedListeners === undefined) {
+      addedListeners = []
+      listeners.set(elem, addedListeners)
+    }
+    addedListeners.push({ type, listener, capture: options.capture })
+  }
+
+  for (const event of handle_events) {
+    listen(event, false)
+  }
+  for (const event of handle_prevent_default_events) {
+    listen(event, true)
+  }
+}
+
+function removeListeners(elem) {
+  for (const { type, listener, capture } of listeners.get(elem) ?? []) {
+    elem.removeEventListener(type, listener, { capture })
+  }
+  listeners.delete(elem)
+  for (const limiter of limiters.get(elem) ?? []) {
+    limiter.flush()
+  }
+  limiters.delete(elem)
+  resizeObservers.get(elem)?.disconnect()
+  resizeObservers.delete(elem)
+}
+
+// whether the event matches the condition, no condition matches every event
+function matches(e, condition) {
+  if (condition == null) return true
+  switch (condition.type) {
+    case "Key":
+      return e instanceof KeyboardEvent && condition.content.includes(e.key)
+    case "Code":
+      return e instanceof KeyboardEvent && condition.content.includes(e.code)
+    case "Button":
+      return e instanceof MouseEvent && condition.content.includes(e.button)
+    case "CtrlKey":
+      return (e).ctrlKey === true
+    case "ShiftKey":
+      return (e).shiftKey === true
+    case "AltKey":
+      return (e).altKey === true
+    case "MetaKey":
+      return (e).metaKey === true
+    case "All":
+      return condition.content.every((c) => matches(e, c))
+    case "Any":
+      return condition.content.some((c) => matches(e, c))
+    case "Not":
+      return !matches(e, condition.content)
+  }
+}
+
+// payload of the event, subclasses are checked before their base classes
+function eventToPayloadOf(e) {
+  if (e instanceof DragEvent) {
+    return dragEventToPayload(e)
+  } else if (e instanceof WheelEvent) {
+    return wheelEventToPayload(e)
+  } else if (e instanceof PointerEvent) {
+    return pointerEventToPayload(e)
+  } else if (e instanceof MouseEvent) {
+    return mouseEventToPayload(e)
+  } else if (e instanceof FocusEvent) {
+    return focusEventToPayload(e)
+  } else if (e instanceof InputEvent) {
+    return inputEventToPayload(e)
+  } else if (e instanceof TouchEvent) {
+    return touchEventToPayload(e)
+  } else if (e instanceof SubmitEvent) {
+    return submitEventToPayload(e)
+  } else if (e instanceof FormDataEvent) {
+    return formDataEventToPayload(e)
+  } else if (e instanceof KeyboardEvent) {
+    return keyboardEventToPayload(e)
+  } else if (e instanceof ProgressEvent) {
+    return progressEventToPayload(e)
+  } else if (e instanceof AnimationEvent) {
+    return animationEventToPayload(e)
+  } else if (e instanceof TransitionEvent) {
+    return transitionEventToPayload(e)
+  } else {
+    return eventToPayload(e)
+  }
+}
+
+// rendered element of the node ref
+function findRef(node_ref) {
+  return document.querySelector(`[data-jubako-ref="${CSS.escape(node_ref)}"]`)
+}
+
+// answer the query about the element of the node ref, after the frame is applied
+function answerElementQuery(query_id, node_ref, query) {
+  const element = findRef(node_ref)
+  const result = element === null ? undefined : queryElement(element, query)
+  connection.sendQueryResponse({ query_id, result })
+}
+
+function queryElement(element, query) {
+  if (query.type === "BoundingRect") {
+    const { x, y, width, height, top, right, bottom, left } = element.getBoundingClientRect()
+    return { type: "BoundingRect", content: { x, y, width, height, top, right, bottom, left } }
+  } else if (query.type === "ComputedStyle") {
+    const style = window.getComputedStyle(element)
+    const values = {}
+    for (const name of query.content) {
+      values[name] = style.getPropertyValue(name)
+    }
+    return { type: "ComputedStyle", content: values }
+  } else {
+    return {
+      type: "ScrollMetrics",
+      content: {
+        scroll_top: element.scrollTop,
+        scroll_left: element.scrollLeft,
+        scroll_width: element.scrollWidth,
+        scroll_height: element.scrollHeight,
+        client_width: element.clientWidth,
+        client_height: element.clientHeight,
+      }
+    }
+  }
+}
+
+const NAMESPACE_URIS = {
+  [Namespace.Html]: "http://www.w3.org/1999/xhtml",
+  [Namespace.Svg]: "http://www.w3.org/2000/svg",
+  [Namespace.MathMl]: "http://www.w3.org/1998/Math/MathML",
+}
+
+// namespaces of the prefixed attributes such as `xlink:href`
+const ATTRIBUTE_NAMESPACE_URIS = {
+  xlink: "http://www.w3.org/1999/xlink",
+  xml: "http://www.w3.org/XML/1998/namespace",
+  xmlns: "http://www.w3.org/2000/xmlns/",
+}
+
+function createElement(tag, namespace) {
+  if (namespace === Namespace.Html) {
+    return document.createElement(tag)
+  }
+  return document.createElementNS(NAMESPACE_URIS[namespace], tag)
+}
+
+// container of the html sanitized by the server, which does not affect the layout
+function createRawHtml(html) {
+  const container = document.createElement("div")
+  container.style.display = "contents"
+  container.innerHTML = html
+  return container
+}
+
+// namespace of the prefixed attribute, `xmlns` itself is in the xmlns namespace
+function attributeNamespace(name) {
+  if (name === "xmlns") return ATTRIBUTE_NAMESPACE_URIS.xmlns
+  const separator = name.indexOf(":")
+  if (separator === -1) return null
+  return ATTRIBUTE_NAMESPACE_URIS[name.slice(0, separator)] ?? null
+}
+
+function setAttribute(element, name, value) {
+  const namespace = attributeNamespace(name)
+  if (namespace === null) {
+    element.setAttribute(name, value)
+  } else {
+    element.setAttributeNS(namespace, name, value)
+  }
+}
+
+function removeAttribute(element, name) {
+  const namespace = attributeNamespace(name)
+  if (namespace === null) {
+    element.removeAttribute(name)
+  } else {
+    element.removeAttributeNS(namespace, name.slice(name.indexOf(":") + 1))
+  }
+}
+
+// apply VNode difference command to DOM
+function applyCommand(root, cmd) {
+  if (cmd.type === "UpdateElement") {
+    const { index, class_diff, props_diff, event_diff, children } = cmd.content
+    const element = root.childNodes[index]
+    if (element === undefined) return
+
+    if (element instanceof Element) {
+      // update class
+      for (const diff of class_diff) {
+        if (diff.type === "Add") {
+          element.classList.add(diff.content)
+        } else if (diff.type === "Remove") {
+          element.classList.remove(diff.content)
+        }
+      }
+
+      // update props
+      for (const diff of props_diff) {
+        const { name, value } = diff.content
+        if (diff.type === "Set") {
+          setProp(element, name, value)
+        } else if (diff.type === "Remove") {
+          removeProp(element, name, value)
+        }
+      }
+
+      // update events
+      if (event_diff.type === "Update") {
+        const event = event_diff.content
+        applyEvent(element, event)
+      }
+    }
+
+    for (const child_cmd of children) {
+      applyCommand(element, child_cmd)
+    }
+  } else if (cmd.type === "UpdateText") {
+    const { index, new_text } = cmd.content
+    const text = root.childNodes[index]
+    if (text === undefined) return
+    text.textContent = new_text
+  } else if (cmd.type === "ReplaceToElement") {
+    const { index, new_tag, namespace, classes, props, event, children } = cmd.content
+    const element = root.childNodes[index]
+    if (element === undefined) return
+
+    // create new element to replace
+    const new_element = createElement(new_tag, namespace)
+
+    // add classes
+    new_element.classList.add(...classes)
+
+    // add props
+    setProps(new_element, props)
+
+    // add events
+    applyEvent(new_element, event)
+
+    for (const child_cmd of children) {
+      applyCommand(new_element, child_cmd)
+    }
+
+    // replace element with new element
+    forgetInputs(element)
+    root.replaceChild(new_element, element)
+  } else if (cmd.type === "ReplaceToText") {
+    const { index, text } = cmd.content
+    const element = root.childNodes[index]
+    if (element === undefined) return
+
+    // create new text to replace
+    const new_text = document.createTextNode(text)
+
+    // replace element with new text
+    forgetInputs(element)
+    root.replaceChild(new_text, element)
+  } else if (cmd.type === "InsertElement") {
+    const { index, tag, namespace, classes, props, event, children } = cmd.content
+
+    // create new element
+    const new_element = createElement(tag, namespace)
+
+    // add classes
+    new_element.classList.add(...classes)
+
+    // add props
+    setProps(new_element, props)
+
+    // add events
+    applyEvent(new_element, event)
+
+    for (const child_cmd of children) {
+      applyCommand(new_element, child_cmd)
+    }
+
+    // insert element
+    root.insertBefore(new_element, root.childNodes[index])
+  } else if (cmd.type === "InsertText") {
+    const { index, text } = cmd.content
+
+    // create new text
+    const new_text = document.createTextNode(text)
+
+    // insert text
+    root.insertBefore(new_text, root.childNodes[index])
+  } else if (cmd.type === "ReplaceToRawHtml") {
+    const { index, html } = cmd.content
+    const element = root.childNodes[index]
+    if (element === undefined) return
+
+    // replace element with the container of the html
+    forgetInputs(element)
+    root.replaceChild(createRawHtml(html), element)
+  } else if (cmd.type === "InsertRawHtml") {
+    const { index, html } = cmd.content
+
+    // insert the container of the html
+    root.insertBefore(createRawHtml(html), root.childNodes[index])
+  } else if (cmd.type === "Remove") {
+    const { index } = cmd.content
+    const element = root.childNodes[index]
+    if (element === undefined) return
+    forgetInputs(element)
+    root.removeChild(element)
+  } else if (cmd.type === "Move") {
+    const { from, to } = cmd.content
+    const element = root.childNodes[from]
+    if (element === undefined) return
+
+    // removing the element blurs the focused element in it, so the focus and the text
+    // selection are restored after the move. the scroll positions in the element are not kept
+    const focused = document.activeElement
+    const restoresFocus = focused instanceof HTMLElement && element.contains(focused)
+    const selection = restoresFocus ? textSelection(focused) : undefined
+    root.removeChild(element)
+    root.insertBefore(element, root.childNodes[to])
+    if (restoresFocus && document.activeElement !== focused) {
+      focused.focus({ preventScroll: true })
+      if (selection !== undefined) {
+        const [start, end, direction] = selection
+        const input = focused
+        input.setSelectionRange(start, end, direction)
+      }
+    }
+  }
+}
+
+// text selection of the input or textarea, undefined for other elements
+function textSelection(
+  element,
+) {
+  if (!(element instanceof HTMLInputElement || element instanceof HTMLTextAreaElement)) {
+    return undefined
+  }
+  try {
+    const { selectionStart, selectionEnd, selectionDirection } = element
+    if (selectionStart === null || selectionEnd === null) return undefined
+    return [selectionStart, selectionEnd, selectionDirection ?? "none"]
+  } catch {
+    // inputs such as number do not support the selection
+    return undefined
+  }
+}
+
+// set attribute or DOM property
+function setProp(element, name, prop) {
+  if (prop.type === "Attribute") {
+    setAttribute(element, name, prop.content)
+  } else if (prop.type === "BooleanAttribute") {
+    if (prop.content) {
+      setAttribute(element, name, "")
+    } else {
+      removeAttribute(element, name)
+    }
+  } else if (prop.type === "Property") {
+    setProperty(element, name, prop.content.content)
+  }
+}
+
+function setProps(element, props) {
+  for (const [name, prop] of Object.entries(props)) {
+    setProp(element, name, prop)
+  }
+}
+
+// remove attribute, or reset DOM property to the empty value of its type
+function removeProp(element, name, prop) {
+  if (prop.type === "Attribute" || prop.type === "BooleanAttribute") {
+    removeAttribute(element, name)
+  } else if (prop.type === "Property") {
+    const value = prop.content
+    if (value.type === "String") {
+      setProperty(element, name, "")
+    } else if (value.type === "Bool") {
+      setProperty(element, name, false)
+    } else if (value.type === "Number") {
+      setProperty(element, name, 0)
+    }
+  }
+}
+
+// apply style difference command to DOM
+function applyStyleCommand(cmd) {
+  if (cmd.type === "AddStyle") {
+    const { class_name, value } = cmd.content
+    const head = document.head || document.getElementsByTagName("head")[0]
+    const style = document.createElement("style")
+
+    style.id = class_name
+    style.dataset.jubako = ""
+    style.appendChild(document.createTextNode(value))
+
+    head.appendChild(style)
+  } else if (cmd.type === "RemoveStyle") {
+    const { class_name } = cmd.content
+    document.getElementById(class_name)?.remove()
+  }
+}
+
+// apply DOM operation to the element of the node ref, operations on missing elements are ignored
+function applyDomCommand(cmd) {
+  const element = findRef(cmd.content.node_ref)
+  if (element === null) return
+
+  if (cmd.type === "Focus") {
+    element.focus?.()
+  } else if (cmd.type === "Blur") {
+    element.blur?.()
+  } else if (cmd.type === "ScrollIntoView") {
+    const { behavior, block, inline } = cmd.content.options
+    element.scrollIntoView({ behavior, block, inline })
+  } else if (cmd.type === "SetSelectionRange") {
+    const { start, end } = cmd.content
+    try {
+      element.setSelectionRange?.(start, end)
+    } catch {
+      // the input type does not support selection
+    }
+  } else if (cmd.type === "Select") {
+    element.select?.()
+  } else if (cmd.type === "Click") {
+    element.click?.()
+  }
+}
+
+// apply browser command such as opening a pop-out window
+function applyBrowserCommand(cmd) {
+  if (cmd.type === "OpenWindow") {
+    const { url } = cmd.content
+    // noopener, so that the pop-out does not copy the session storage of this window
+    window.open(url, "_blank", "popup,noopener,width=800,height=600")
+  } else if (cmd.type === "SetSession") {
+    const { id } = cmd.content
+    connection.setSession(id)
+  } else if (cmd.type === "ConfigureConnection") {
+    const { offline_events } = cmd.content
+    connection.setOfflineEvents(offline_events)
+  } else if (cmd.type === "QueryElement") {
+    const { query_id, node_ref, query } = cmd.content
+    answerElementQuery(query_id, node_ref, query)
+  }
+}
+
+// clear the rendered DOM to render the whole tree again
+function resetRendered(appRoot, portalRoot) {
+  while (appRoot.firstChild !== null) appRoot.removeChild(appRoot.firstChild)
+  while (portalRoot.firstChild !== null) portalRoot.removeChild(portalRoot.firstChild)
+  document.head.querySelectorAll("style[data-jubako]").forEach((style) => style.remove())
+  resetControlledInputs()
+}
+
+const main = async () => {
+  const appRoot = document.querySelector('#app')
+  const portalRoot = document.querySelector('#portal')
+
+  connection.oncommand((cmds) => {
+    if (appRoot === null || portalRoot === null) return
+
+    if (cmds.reset) {
+      resetRendered(appRoot, portalRoot)
+    }
+
+    for (const cmd of cmds.main) {
+      applyCommand(appRoot, cmd)
+    }
+    for (const cmd of cmds.portals) {
+      applyCommand(portalRoot, cmd)
+    }
+    for (const cmd of cmds.styles) {
+      applyStyleCommand(cmd)
+    }
+    for (const cmd of cmds.dom) {
+      applyDomCommand(cmd)
+    }
+    for (const cmd of cmds.browser) {
+      applyBrowserCommand(cmd)
+    }
+    // restore the values of the inputs that the server has handled
+    syncHandledInputs(cmds.events)
+
+    // let the server push the next frame
+    connection.sendAck(cmds.frame)
+  })
+}
+
+document.addEventListener('DOMContentLoaded', main)
+//# sourceMappingURL=index-b157f0ae.js.map
@@ -1,5 +1,62 @@
-import { DifferenceCommand, StyleDifferenceCommand } from "./rustTypes"
+import { BrowserCommand, DifferenceCommand, DomCommand, Namespace, Prop, Props, StyleDifferenceCommand } from "./rustTypes"
 import { applyEvent } from "./applyEvent"
+import connection from "./connection"
+import { forgetInputs, resetControlledInputs, setProperty } from "./controlledInput"
+import { answerElementQuery, findRef } from "./queryElement"
+
+const NAMESPACE_URIS: Record<Namespace, string> = {
+  [Namespace.Html]: "http://www.w3.org/1999/xhtml",
+  [Namespace.Svg]: "http://www.w3.org/2000/svg",
+  [Namespace.MathMl]: "http://www.w3.org/1998/Math/MathML",
+}
+
+// namespaces of the prefixed attributes such as `xlink:href`
+const ATTRIBUTE_NAMESPACE_URIS: Record<string, string> = {
+  xlink: "http://www.w3.org/1999/xlink",
+  xml: "http://www.w3.org/XML/1998/namespace",
+  xmlns: "http://www.w3.org/2000/xmlns/",
+}
+
+function createElement(tag: string, namespace: Namespace): Element {
+  if (namespace === Namespace.Html) {
+    return document.createElement(tag)
+  }
+  return document.createElementNS(NAMESPACE_URIS[namespace], tag)
+}
+
+// container of the html sanitized by the server, which does not affect the layout
+function createRawHtml(html: string): Element {
+  const container = document.createElement("div")
+  container.style.display = "contents"
+  container.innerHTML = html
+  return container
+}
+
+// namespace of the prefixed attribute, `xmlns` itself is in the xmlns namespace
+function attributeNamespace(name: string): string | null {
+  if (name === "xmlns") return ATTRIBUTE_NAMESPACE_URIS.xmlns
+  const separator = name.indexOf(":")
+  if (separator === -1) return null
+  return ATTRIBUTE_NAMESPACE_URIS[name.slice(0, separator)] ?? null
+}
+
+function setAttribute(element: Element, name: string, value: string) {
+  const namespace = attributeNamespace(name)
+  if (namespace === null) {
+    element.setAttribute(name, value)
+  } else {
+    element.setAttributeNS(namespace, name, value)
+  }
+}
+
+function removeAttribute(element: Element, name: string) {
+  const namespace = attributeNamespace(name)
+  if (namespace === null) {
+    element.removeAttribute(name)
+  } else {
+    element.removeAttributeNS(namespace, name.slice(name.indexOf(":") + 1))
+  }
+}
 
 // apply VNode difference command to DOM
 export function applyCommand(root: Node, cmd: DifferenceCommand) {
@@ -8,7 +65,7 @@ export function applyCommand(root: Node, cmd: DifferenceCommand) {
     const element = root.childNodes[index]
     if (element === undefined) return
 
-    if (element instanceof HTMLElement) {
+    if (element instanceof Element) {
       // update class
       for (const diff of class_diff) {
         if (diff.type === "Add") {
@@ -20,20 +77,11 @@ export function applyCommand(root: Node, cmd: DifferenceCommand) {
 
       // update props
       for (const diff of props_diff) {
-        if (diff.type === "Add") {
-          if (diff.content.split("=").length === 2) {
-            const [key, value] = diff.content.split("=")
-            element.setAttribute(key, value)
-          } else {
-            element.setAttribute(diff.content, "true")
-          }
+        const { name, value } = diff.content
+        if (diff.type === "Set") {
+          setProp(element, name, value)
         } else if (diff.type === "Remove") {
-          if (diff.content.split("=").length === 2) {
-            const [key, _value] = diff.content.split("=")
-            element.removeAttribute(key)
-          } else {
-            element.removeAttribute(diff.content)
-          }
+          removeProp(element, name, value)
         }
       }
 
@@ -53,25 +101,18 @@ export function applyCommand(root: Node, cmd: DifferenceCommand) {
     if (text === undefined) return
     text.textContent = new_text
   } else if (cmd.type === "ReplaceToElement") {
-    const { index, new_tag, classes, props, event, children } = cmd.content
+    const { index, new_tag, namespace, classes, props, event, children } = cmd.content
     const element = root.childNodes[index]
     if (element === undefined) return
 
     // create new element to replace
-    const new_element = document.createElement(new_tag)
+    const new_element = createElement(new_tag, namespace)
 
     // add classes
     new_element.classList.add(...classes)
 
     // add props
-    for (const prop of props) {
-      if (prop.split("=").length === 2) {
-        const [key, value] = prop.split("=")
-        new_element.setAttribute(key, value)
-      } else {
-        new_element.setAttribute(prop, "true")
-      }
-    }
+    setProps(new_element, props)
 
     // add events
     applyEvent(new_element, event)
@@ -81,6 +122,7 @@ export function applyCommand(root: Node, cmd: DifferenceCommand) {
     }
 
     // replace element with new element
+    forgetInputs(element)
     root.replaceChild(new_element, element)
   } else if (cmd.type === "ReplaceToText") {
     const { index, text } = cmd.content
@@ -91,25 +133,19 @@ export function applyCommand(root: Node, cmd: DifferenceCommand) {
     const new_text = document.createTextNode(text)
 
     // replace element with new text
+    forgetInputs(element)
     root.replaceChild(new_text, element)
   } else if (cmd.type === "InsertElement") {
-    const { index, tag, classes, props, event, children } = cmd.content
+    const { index, tag, namespace, classes, props, event, children } = cmd.content
 
     // create new element
-    const new_element = document.createElement(tag)
+    const new_element = createElement(tag, namespace)
 
     // add classes
     new_element.classList.add(...classes)
 
     // add props
-    for (const prop of props) {
-      if (prop.split("=").length === 2) {
-        const [key, value] = prop.split("=")
-        new_element.setAttribute(key, value)
-      } else {
-        new_element.setAttribute(prop, "true")
-      }
-    }
+    setProps(new_element, props)
 
     // add events
     applyEvent(new_element, event)
@@ -128,11 +164,99 @@ export function applyCommand(root: Node, cmd: DifferenceCommand) {
 
     // insert text
     root.insertBefore(new_text, root.childNodes[index])
+  } else if (cmd.type === "ReplaceToRawHtml") {
+    const { index, html } = cmd.content
+    const element = root.childNodes[index]
+    if (element === undefined) return
+
+    // replace element with the container of the html
+    forgetInputs(element)
+    root.replaceChild(createRawHtml(html), element)
+  } else if (cmd.type === "InsertRawHtml") {
+    const { index, html } = cmd.content
+
+    // insert the container of the html
+    root.insertBefore(createRawHtml(html), root.childNodes[index])
   } else if (cmd.type === "Remove") {
     const { index } = cmd.content
     const element = root.childNodes[index]
     if (element === undefined) return
+    forgetInputs(element)
+    root.removeChild(element)
+  } else if (cmd.type === "Move") {
+    const { from, to } = cmd.content
+    const element = root.childNodes[from]
+    if (element === undefined) return
+
+    // removing the element blurs the focused element in it, so the focus and the text
+    // selection are restored after the move. the scroll positions in the element are not kept
+    const focused = document.activeElement
+    const restoresFocus = focused instanceof HTMLElement && element.contains(focused)
+    const selection = restoresFocus ? textSelection(focused) : undefined
     root.removeChild(element)
+    root.insertBefore(element, root.childNodes[to])
+    if (restoresFocus && document.activeElement !== focused) {
+      focused.focus({ preventScroll: true })
+      if (selection !== undefined) {
+        const [start, end, direction] = selection
+        const input = focused as HTMLInputElement
+        input.setSelectionRange(start, end, direction)
+      }
+    }
+  }
+}
+
+// text selection of the input or textarea, undefined for other elements
+function textSelection(
+  element: HTMLElement,
+): [number, number, "forward" | "backward" | "none"] | undefined {
+  if (!(element instanceof HTMLInputElement || element instanceof HTMLTextAreaElement)) {
+    return undefined
+  }
+  try {
+    const { selectionStart, selectionEnd, selectionDirection } = element
+    if (selectionStart === null || selectionEnd === null) return undefined
+    return [selectionStart, selectionEnd, selectionDirection ?? "none"]
+  } catch {
+    // inputs such as number do not support the selection
+    return undefined
+  }
+}
+
+// set attribute or DOM property
+function setProp(element: Element, name: string, prop: Prop) {
+  if (prop.type === "Attribute") {
+    setAttribute(element, name, prop.content)
+  } else if (prop.type === "BooleanAttribute") {
+    if (prop.content) {
+      setAttribute(element, name, "")
+    } else {
+      removeAttribute(element, name)
+    }
+  } else if (prop.type === "Property") {
+    setProperty(element, name, prop.content.content)
+  }
+}
+
+function setProps(element: Element, props: Props) {
+  for (const [name, prop] of Object.entries(props)) {
+    setProp(element, name, prop)
+  }
+}
+
+// remove attribute, or reset DOM property to the empty value of its type
+function removeProp(element: Element, name: string, prop: Prop) {
+  if (prop.type === "Attribute" || prop.type === "BooleanAttribute") {
+    removeAttribute(element, name)
+  } else if (prop.type === "Property") {
+    const value = prop.content
+    if (value.type === "String") {
+      setProperty(element, name, "")
+    } else if (value.type === "Bool") {
+      setProperty(element, name, false)
+    } else if (value.type === "Number") {
+      setProperty(element, name, 0)
+    }
   }
 }
 
@@ -144,6 +268,7 @@ export function applyStyleCommand(cmd: StyleDifferenceCommand) {
     const style = document.createElement("style")
 
     style.id = class_name
+    style.dataset.jubako = ""
     style.appendChild(document.createTextNode(value))
 
     head.appendChild(style)
@@ -152,3 +277,55 @@ export function applyStyleCommand(cmd: StyleDifferenceCommand) {
     document.getElementById(class_name)?.remove()
   }
 }
+
+// apply DOM operation to the element of the node ref, operations on missing elements are ignored
+export function applyDomCommand(cmd: DomCommand) {
+  const element = findRef(cmd.content.node_ref) as any
+  if (element === null) return
+
+  if (cmd.type === "Focus") {
+    element.focus?.()
+  } else if (cmd.type === "Blur") {
+    element.blur?.()
+  } else if (cmd.type === "ScrollIntoView") {
+    const { behavior, block, inline } = cmd.content.options
+    element.scrollIntoView({ behavior, block, inline })
+  } else if (cmd.type === "SetSelectionRange") {
+    const { start, end } = cmd.content
+    try {
+      element.setSelectionRange?.(start, end)
+    } catch {
+      // the input type does not support selection
+    }
+  } else if (cmd.type === "Select") {
+    element.select?.()
+  } else if (cmd.type === "Click") {
+    element.click?.()
+  }
+}
+
+// apply browser command such as opening a pop-out window
+export function applyBrowserCommand(cmd: BrowserCommand) {
+  if (cmd.type === "OpenWindow") {
+    const { url } = cmd.content
+    // noopener, so that the pop-out does not copy the session storage of this window
+    window.open(url, "_blank", "popup,noopener,width=800,height=600")
+  } else if (cmd.type === "SetSession") {
+    const { id } = cmd.content
+    connection.setSession(id)
+  } else if (cmd.type === "ConfigureConnection") {
+    const { offline_events } = cmd.content
+    connection.setOfflineEvents(offline_events)
+  } else if (cmd.type === "QueryElement") {
+    const { query_id, node_ref, query } = cmd.content
+    answerElementQuery(query_id, node_ref, query)
+  }
+}
+
+// clear the rendered DOM to render the whole tree again
+export function resetRendered(appRoot: Node, portalRoot: Node) {
+  while (appRoot.firstChild !== null) appRoot.removeChild(appRoot.firstChild)
+  while (portalRoot.firstChild !== null) portalRoot.removeChild(portalRoot.firstChild)
+  document.head.querySelectorAll("style[data-jubako]").forEach((style) => style.remove())
+  resetControlledInputs()
+}
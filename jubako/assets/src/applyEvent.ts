@@ -1,6 +1,9 @@
-import { HandleEvent, VNodeEventType } from "./rustTypes"
+import { EventCondition, HandleEvent, ListenerOptions, VNodeEventType } from "./rustTypes"
 import connection from "./connection"
+import { holdInput, recordInput } from "./controlledInput"
+import { RateLimiter, rateLimiter } from "./eventRate"
 import {
+  elementToPayload,
   eventToPayload,
   dragEventToPayload,
   focusEventToPayload,
@@ -9,6 +12,7 @@ import {
   touchEventToPayload,
   wheelEventToPayload,
   submitEventToPayload,
+  formDataEventToPayload,
   pointerEventToPayload,
   keyboardEventToPayload,
   progressEventToPayload,
@@ -16,346 +20,171 @@ import {
   transitionEventToPayload
 } from "./eventToPayload"
 
+const DEFAULT_OPTIONS: ListenerOptions = {
+  capture: false,
+  passive: false,
+  once: false,
+  stop_propagation: false,
+  stop_immediate_propagation: false,
+  rate: { type: "Immediate" },
+}
+
+// DOM event names that are not the lowercase of the event type
+const DOM_EVENT_NAMES: Partial<Record<VNodeEventType, string>> = {
+  [VNodeEventType.DoubleClick]: "dblclick",
+}
+
+interface Listener {
+  type: string
+  listener: (e: Event) => void
+  capture: boolean
+}
+
+// listeners that jubako added to the elements, removed when the handled events change
+const listeners = new WeakMap<Element, Listener[]>()
+// payloads that wait for the rate of the listeners, sent when the listeners are removed
+const limiters = new WeakMap<Element, RateLimiter<any>[]>()
+// we want to handle resize event not only when window resized
+// but also when element resized, so we use ResizeObserver.
+const resizeObservers = new WeakMap<Element, ResizeObserver>()
+
 // handling events and send them to jubako server.
-export function applyEvent(elem: HTMLElement, eventHandle: HandleEvent) {
-  const e = elem as any
-  const { handle_id, handle_events, handle_prevent_default_events } = eventHandle
+export function applyEvent(elem: Element, eventHandle: HandleEvent) {
+  const { handle_id, handle_events, handle_prevent_default_events, listener_options } = eventHandle
 
-  // handle events.
-  for (const event of handle_events) {
-    // there is no `oncancel`, `focusin`, `focusout` event in HTMLElement
-    // so that we need to use `addEventListener` instead of `oncancel` etc.
-    // we want to handle resize event not only when window resized
-    // but also when element resized, so we use ResizeObserver.
-    if (event === "Cancel") {
-      if (e.c instanceof Function) {
-        elem.removeEventListener('cancel', e.c)
-      }
-      const oncancel = (e: Event) => {
-        const payload = eventToPayload(e)
-        connection.send({
-          handle_id,
-          kind: { type: "Cancel", content: payload }
-        })
-      }
-      elem.addEventListener('cancel', oncancel)
-      e.c = oncancel
-    } else if (event === "FocusIn") {
-      if (e.fi instanceof Function) {
-        e.removeEventListener('focusin', e.fi)
-      }
-      const onfocusin = (e: FocusEvent) => {
-        const payload = focusEventToPayload(e)
-        connection.send({
-          handle_id,
-          kind: { type: "FocusIn", content: payload }
-        })
-      }
-      elem.addEventListener("focusin", onfocusin)
-      e.fi = onfocusin
-    } else if (event === "FocusOut") {
-      if (e.fi instanceof Function) {
-        e.removeEventListener('focusout', e.fi)
-      }
-      const onfocusin = (e: FocusEvent) => {
-        const payload = focusEventToPayload(e)
-        connection.send({
-          handle_id,
-          kind: { type: "FocusOut", content: payload }
-        })
-      }
-      elem.addEventListener("focusout", onfocusin)
-      e.fi = onfocusin
-    } else if (event === "Resize") {
+  removeListeners(elem)
+
+  const listen = (event: VNodeEventType, preventDefault: boolean) => {
+    const options = listener_options[event] ?? DEFAULT_OPTIONS
+    const isInput = event === VNodeEventType.Input || event === VNodeEventType.Change
+    const limiter = rateLimiter(options.rate, (content: any) => {
+      connection.send({ handle_id, kind: { type: event, content } })
+      if (isInput) recordInput(elem)
+    })
+    let added = limiters.get(elem)
+    if (added === undefined) {
+      added = []
+      limiters.set(elem, added)
+    }
+    added.push(limiter)
+
+    if (event === VNodeEventType.Resize) {
       const ro = new ResizeObserver((entries) => {
-        for (let entry of entries) {
-          let target: any = null
-          if (entry.target !== null || entry.target as Element !== null) {
-            const targetElem = entry.target as Element
-            target = {
-              element_name: targetElem.tagName,
-              client_height: targetElem.clientHeight,
-              client_width: targetElem.clientWidth,
-              client_left: targetElem.clientLeft,
-              client_top: targetElem.clientTop,
-              scroll_height: targetElem.scrollHeight,
-              scroll_width: targetElem.scrollWidth,
-              scroll_left: targetElem.scrollLeft,
-              scroll_top: targetElem.scrollTop,
-            }
-          }
-        connection.send({
-          handle_id,
-          kind: { type: "Resize", content: target }
-        })
+        for (const entry of entries) {
+          limiter.push({ target: elementToPayload(entry.target) })
         }
+        if (options.once) ro.disconnect()
       })
       ro.observe(elem)
-      e["ro"] = ro
-    } else {
-      e[`on${event.toLowerCase()}`] = (e: Event) => {
-        if (e instanceof DragEvent) {
-          const payload = dragEventToPayload(e)
-          connection.send({
-            handle_id,
-            kind: { type: event, content: payload }
-          })
-        } else if (e instanceof FocusEvent) {
-          const payload = focusEventToPayload(e)
-          connection.send({
-            handle_id,
-            kind: { type: event, content: payload }
-          })
-        } else if (e instanceof InputEvent) {
-          const payload = inputEventToPayload(e)
-          connection.send({
-            handle_id,
-            kind: { type: event, content: payload }
-          })
-        } else if (e instanceof MouseEvent) {
-          const payload = mouseEventToPayload(e)
-          connection.send({
-            handle_id,
-            kind: { type: event, content: payload }
-          })
-        } else if (e instanceof TouchEvent) {
-          const payload = touchEventToPayload(e)
-          connection.send({
-            handle_id,
-            kind: { type: event, content: payload }
-          })
-        } else if (e instanceof WheelEvent) {
-          const payload = wheelEventToPayload(e)
-          connection.send({
-            handle_id,
-            kind: { type: event, content: payload }
-          })
-        } else if (e instanceof SubmitEvent) {
-          const payload = submitEventToPayload(e)
-          connection.send({
-            handle_id,
-            kind: { type: event, content: payload }
-          })
-        } else if (e instanceof PointerEvent) {
-          const payload = pointerEventToPayload(e)
-          connection.send({
-            handle_id,
-            kind: { type: event, content: payload }
-          })
-        } else if (e instanceof KeyboardEvent) {
-          const payload = keyboardEventToPayload(e)
-          connection.send({
-            handle_id,
-            kind: { type: event, content: payload }
-          })
-        } else if (e instanceof ProgressEvent) {
-          const payload = progressEventToPayload(e)
-          connection.send({
-            handle_id,
-            kind: { type: event, content: payload }
-          })
-        } else if (e instanceof AnimationEvent) {
-          const payload = animationEventToPayload(e)
-          connection.send({
-            handle_id,
-            kind: { type: event, content: payload }
-          })
-        } else if (e instanceof TransitionEvent) {
-          const payload = transitionEventToPayload(e)
-          connection.send({
-            handle_id,
-            kind: { type: event, content: payload }
-          })
-        } else {
-          const payload = eventToPayload(e)
-          connection.send({
-            handle_id,
-            kind: { type: event, content: payload }
-          })
-        }
-      }
+      resizeObservers.set(elem, ro)
+      return
     }
-  }
 
-  // handle prevent default events
-  for (const event of handle_prevent_default_events) {
-    // same as above, we use `addEventListener` instead of `oncancel` etc.
-    // and same as above, we use ResizeObserver to detect resize.
-    if (event === "Cancel") {
-      if (e.c instanceof Function) {
-        elem.removeEventListener('cancel', e.c)
-      }
-      const oncancel = (e: Event) => {
-        e.preventDefault()
-        const payload = eventToPayload(e)
-        connection.send({
-          handle_id,
-          kind: { type: "Cancel", content: payload }
-        })
-      }
-      elem.addEventListener('cancel', oncancel)
-      e.c = oncancel
-    } else if (event === "FocusIn") {
-      if (e.fi instanceof Function) {
-        e.removeEventListener('focusin', e.fi)
-      }
-      const onfocusin = (e: FocusEvent) => {
-        e.preventDefault()
-        const payload = focusEventToPayload(e)
-        connection.send({
-          handle_id,
-          kind: { type: "FocusIn", content: payload }
-        })
-      }
-      elem.addEventListener("focusin", onfocusin)
-      e.fi = onfocusin
-    } else if (event === "FocusOut") {
-      if (e.fi instanceof Function) {
-        e.removeEventListener('focusout', e.fi)
-      }
-      const onfocusin = (e: FocusEvent) => {
+    const listener = (e: Event) => {
+      // passive listeners can't prevent the default action
+      if (preventDefault && !options.passive && matches(e, options.prevent_default_if)) {
         e.preventDefault()
-        const payload = focusEventToPayload(e)
-        connection.send({
-          handle_id,
-          kind: { type: "FocusOut", content: payload }
-        })
       }
-      elem.addEventListener("focusout", onfocusin)
-      e.fi = onfocusin
-    } else if (event === "Resize") {
-      const ro = new ResizeObserver((entries) => {
-        for (let entry of entries) {
-          let target: any = null
-          if (entry.target !== null || entry.target as Element !== null) {
-            const targetElem = entry.target as Element
-            target = {
-              element_name: targetElem.tagName,
-              client_height: targetElem.clientHeight,
-              client_width: targetElem.clientWidth,
-              client_left: targetElem.clientLeft,
-              client_top: targetElem.clientTop,
-              scroll_height: targetElem.scrollHeight,
-              scroll_width: targetElem.scrollWidth,
-              scroll_left: targetElem.scrollLeft,
-              scroll_top: targetElem.scrollTop,
-            }
-          }
-          connection.send({
-            handle_id,
-            kind: { type: "Resize", content: target }
-          })
-        }
-      })
-      ro.observe(elem)
-      e["ro"] = ro
-    } else {
-      e[`on${event.toLowerCase()}`] = (e: Event) => {
-        e.preventDefault()
-        if (e instanceof DragEvent) {
-          const payload = dragEventToPayload(e)
-          connection.send({
-            handle_id,
-            kind: { type: event, content: payload }
-          })
-        } else if (e instanceof FocusEvent) {
-          const payload = focusEventToPayload(e)
-          connection.send({
-            handle_id,
-            kind: { type: event, content: payload }
-          })
-        } else if (e instanceof InputEvent) {
-          const payload = inputEventToPayload(e)
-          connection.send({
-            handle_id,
-            kind: { type: event, content: payload }
-          })
-        } else if (e instanceof MouseEvent) {
-          const payload = mouseEventToPayload(e)
-          connection.send({
-            handle_id,
-            kind: { type: event, content: payload }
-          })
-        } else if (e instanceof TouchEvent) {
-          const payload = touchEventToPayload(e)
-          connection.send({
-            handle_id,
-            kind: { type: event, content: payload }
-          })
-        } else if (e instanceof WheelEvent) {
-          const payload = wheelEventToPayload(e)
-          connection.send({
-            handle_id,
-            kind: { type: event, content: payload }
-          })
-        } else if (e instanceof SubmitEvent) {
-          const payload = submitEventToPayload(e)
-          connection.send({
-            handle_id,
-            kind: { type: event, content: payload }
-          })
-        } else if (e instanceof PointerEvent) {
-          const payload = pointerEventToPayload(e)
-          connection.send({
-            handle_id,
-            kind: { type: event, content: payload }
-          })
-        } else if (e instanceof KeyboardEvent) {
-          const payload = keyboardEventToPayload(e)
-          connection.send({
-            handle_id,
-            kind: { type: event, content: payload }
-          })
-        } else if (e instanceof ProgressEvent) {
-          const payload = progressEventToPayload(e)
-          connection.send({
-            handle_id,
-            kind: { type: event, content: payload }
-          })
-        } else if (e instanceof AnimationEvent) {
-          const payload = animationEventToPayload(e)
-          connection.send({
-            handle_id,
-            kind: { type: event, content: payload }
-          })
-        } else if (e instanceof TransitionEvent) {
-          const payload = transitionEventToPayload(e)
-          connection.send({
-            handle_id,
-            kind: { type: event, content: payload }
-          })
-        } else {
-          const payload = eventToPayload(e)
-          connection.send({
-            handle_id,
-            kind: { type: event, content: payload }
-          })
-        }
+      if (options.stop_immediate_propagation) {
+        e.stopImmediatePropagation()
+      } else if (options.stop_propagation) {
+        e.stopPropagation()
       }
+      if (isInput) holdInput(elem)
+      limiter.push(eventToPayloadOf(e))
     }
-  }
+    const type = DOM_EVENT_NAMES[event] ?? event.toLowerCase()
+    elem.addEventListener(type, listener, {
+      capture: options.capture,
+      passive: options.passive,
+      once: options.once,
+    })
 
-  for (const event of Object.keys(VNodeEventType).filter(
-    (key) => !handle_events.includes(key as VNodeEventType) && !handle_prevent_default_events.includes(key as VNodeEventType)
-  )) {
-    if (event === "Cancel") {
-      if (e.c instanceof Function) {
-        e.removeEventListener('cancel', e.c)
-      }
-    } else if (event === "FocusIn") {
-      if (e.fi instanceof Function) {
-        e.removeEventListener('focusin', e.fi)
-      }
-    } else if (event === "FocusOn") {
-      if (e.fo instanceof Function) {
-        e.removeEventListener('focusout', e.fo)
-      }
-    } else if (event === "Resize") {
-      if (e.ro instanceof ResizeObserver) {
-        e.ro.disconnect()
-      }
-    } else {
-      e[`on${event.toLowerCase()}`] = null
+    let addedListeners = listeners.get(elem)
+    if (addedListeners === undefined) {
+      addedListeners = []
+      listeners.set(elem, addedListeners)
     }
+    addedListeners.push({ type, listener, capture: options.capture })
+  }
+
+  for (const event of handle_events) {
+    listen(event, false)
+  }
+  for (const event of handle_prevent_default_events) {
+    listen(event, true)
+  }
+}
+
+function removeListeners(elem: Element) {
+  for (const { type, listener, capture } of listeners.get(elem) ?? []) {
+    elem.removeEventListener(type, listener, { capture })
+  }
+  listeners.delete(elem)
+  for (const limiter of limiters.get(elem) ?? []) {
+    limiter.flush()
+  }
+  limiters.delete(elem)
+  resizeObservers.get(elem)?.disconnect()
+  resizeObservers.delete(elem)
+}
+
+// whether the event matches the condition, no condition matches every event
+function matches(e: Event, condition: EventCondition | null | undefined): boolean {
+  if (condition == null) return true
+  switch (condition.type) {
+    case "Key":
+      return e instanceof KeyboardEvent && condition.content.includes(e.key)
+    case "Code":
+      return e instanceof KeyboardEvent && condition.content.includes(e.code)
+    case "Button":
+      return e instanceof MouseEvent && condition.content.includes(e.button)
+    case "CtrlKey":
+      return (e as any).ctrlKey === true
+    case "ShiftKey":
+      return (e as any).shiftKey === true
+    case "AltKey":
+      return (e as any).altKey === true
+    case "MetaKey":
+      return (e as any).metaKey === true
+    case "All":
+      return condition.content.every((c) => matches(e, c))
+    case "Any":
+      return condition.content.some((c) => matches(e, c))
+    case "Not":
+      return !matches(e, condition.content)
+  }
+}
+
+// payload of the event, subclasses are checked before their base classes
+function eventToPayloadOf(e: Event) {
+  if (e instanceof DragEvent) {
+    return dragEventToPayload(e)
+  } else if (e instanceof WheelEvent) {
+    return wheelEventToPayload(e)
+  } else if (e instanceof PointerEvent) {
+    return pointerEventToPayload(e)
+  } else if (e instanceof MouseEvent) {
+    return mouseEventToPayload(e)
+  } else if (e instanceof FocusEvent) {
+    return focusEventToPayload(e)
+  } else if (e instanceof InputEvent) {
+    return inputEventToPayload(e)
+  } else if (e instanceof TouchEvent) {
+    return touchEventToPayload(e)
+  } else if (e instanceof SubmitEvent) {
+    return submitEventToPayload(e)
+  } else if (e instanceof FormDataEvent) {
+    return formDataEventToPayload(e)
+  } else if (e instanceof KeyboardEvent) {
+    return keyboardEventToPayload(e)
+  } else if (e instanceof ProgressEvent) {
+    return progressEventToPayload(e)
+  } else if (e instanceof AnimationEvent) {
+    return animationEventToPayload(e)
+  } else if (e instanceof TransitionEvent) {
+    return transitionEventToPayload(e)
+  } else {
+    return eventToPayload(e)
   }
 }
@@ -1,4 +1,5 @@
-import { DifferenceCommands } from "./rustTypes"
+import { DifferenceCommands, ElementQueryResponse, FrameAck, OfflineEventPolicy } from "./rustTypes"
+import { hideOverlay, showOverlay } from "./connectionOverlay"
 
 let path = window.location.host
 let paths = window.location.pathname.split("/");
@@ -6,23 +7,129 @@ for (let i = 0; i < paths.length - 1; i++) {
   if (paths[i] === "") continue
   path += "/" + paths[i]
 }
-const websocket = new WebSocket(`ws://${path}/ws`)
 
+// delay of the reconnection doubles on each failure, up to the max delay
+const RECONNECT_BASE_DELAY = 500
+const RECONNECT_MAX_DELAY = 10000
+
+// close codes of the server, kept in sync with simple_window.rs
+// another browser window resumed the session, e.g. a duplicated tab
+const CLOSE_SUPERSEDED = 4001
+// the session of the pop-out window has ended
+const CLOSE_SESSION_EXPIRED = 4002
+
+const sessionKey = `jubako-session:${path}`
+let websocket: WebSocket
+let reconnectAttempts = 0
+let offlineEvents = OfflineEventPolicy.Discard
+let queuedEvents: string[] = []
+// events sent over the current connection
+let sentCount = 0
+
+// frames pushed by the server before the callback is registered
+let pendingCommands: DifferenceCommands[] = []
+let commandCallback: ((cmds: DifferenceCommands) => void) | null = null
+
+// pop-out windows pass `?session=` in the page url,
+// other windows resume the session that they were connected to
+function url() {
+  const query = new URLSearchParams(window.location.search)
+  const resumeId = sessionStorage.getItem(sessionKey)
+  if (!query.has("session") && resumeId !== null) {
+    query.set("resume", resumeId)
+  }
+  const search = query.toString()
+  return `ws://${path}/ws${search === "" ? "" : "?" + search}`
+}
+
+function connect() {
+  websocket = new WebSocket(url())
+
+  websocket.onopen = () => {
+    reconnectAttempts = 0
+    hideOverlay()
+    sentCount = 0
+    for (const event of queuedEvents) {
+      websocket.send(event)
+      sentCount += 1
+    }
+    queuedEvents = []
+  }
+
+  websocket.onmessage = (evt) => {
+    const cmds = JSON.parse(evt.data) as DifferenceCommands
+    if (commandCallback === null) {
+      pendingCommands.push(cmds)
+    } else {
+      commandCallback(cmds)
+    }
+  }
+
+  websocket.onclose = (evt) => {
+    if (evt.code === CLOSE_SUPERSEDED) {
+      // leave the session to the other window and start a new one
+      sessionStorage.removeItem(sessionKey)
+      reconnectAttempts = 0
+      connect()
+      return
+    }
+    if (evt.code === CLOSE_SESSION_EXPIRED) {
+      showOverlay("This window has been closed.")
+      window.close()
+      return
+    }
+    const delay = Math.min(RECONNECT_BASE_DELAY * 2 ** reconnectAttempts, RECONNECT_MAX_DELAY)
+    reconnectAttempts += 1
+    showOverlay(reconnectAttempts === 1 ? "Disconnected. Reconnecting..." : "Reconnecting...")
+    setTimeout(connect, delay * (0.75 + Math.random() * 0.5))
+  }
+}
+connect()
+
+function setSession(id: string) {
+  sessionStorage.setItem(sessionKey, id)
+}
+
+function setOfflineEvents(policy: OfflineEventPolicy) {
+  offlineEvents = policy
+}
+
+// send an event, events sent while disconnected are queued or discarded by the policy
 function send(obj: any) {
+  const message = JSON.stringify(obj)
   if (websocket.readyState === WebSocket.OPEN) {
-    websocket.send(JSON.stringify(obj))
+    websocket.send(message)
+    sentCount += 1
+  } else if (offlineEvents === OfflineEventPolicy.Queue) {
+    queuedEvents.push(message)
   }
 }
 
-function onopen(callback: () => void) {
-  websocket.onopen = callback
+// acknowledge the frame, acknowledgements are meaningless for the next connection
+function sendAck(frame: number) {
+  if (websocket.readyState === WebSocket.OPEN) {
+    const ack: FrameAck = { frame }
+    websocket.send(JSON.stringify(ack))
+  }
+}
+
+// answer the element query, answers are meaningless for the next connection
+function sendQueryResponse(response: ElementQueryResponse) {
+  if (websocket.readyState === WebSocket.OPEN) {
+    websocket.send(JSON.stringify(response))
+  }
+}
+
+function sentEvents() {
+  return sentCount
 }
 
 function oncommand(callback: (cmds: DifferenceCommands) => void) {
-  websocket.onmessage = (evt) => {
-    const cmds = JSON.parse(evt.data) as DifferenceCommands
+  commandCallback = callback
+  for (const cmds of pendingCommands) {
     callback(cmds)
   }
+  pendingCommands = []
 }
 
-export default { send, onopen, oncommand }
+export default { send, sendAck, sendQueryResponse, sentEvents, oncommand, setSession, setOfflineEvents }
@@ -0,0 +1,17 @@
+// overlay that covers the window while the connection to the server is lost
+
+let overlay: HTMLDivElement | null = null
+
+export function showOverlay(text: string) {
+  if (overlay === null) {
+    overlay = document.createElement("div")
+    overlay.id = "connection-overlay"
+    document.body.appendChild(overlay)
+  }
+  overlay.textContent = text
+}
+
+export function hideOverlay() {
+  overlay?.remove()
+  overlay = null
+}
@@ -0,0 +1,83 @@
+import connection from "./connection"
+
+// while the server has not handled the input of an element, values pushed by the server are stale,
+// so they are held back not to overwrite what the user is typing
+const pendingInputs = new Map<Element, number>()
+// latest values of the DOM properties pushed by the server
+const serverValues = new WeakMap<Element, Map<string, unknown>>()
+
+// record that the input or change event of the element is sent
+export function recordInput(elem: Element) {
+  pendingInputs.set(elem, connection.sentEvents())
+}
+
+// record that the input or change event of the element waits for the rate of its handler,
+// the values pushed by the server are held back until it is sent and handled
+export function holdInput(elem: Element) {
+  pendingInputs.set(elem, Infinity)
+}
+
+// set DOM property pushed by the server
+export function setProperty(elem: Element, name: string, value: unknown) {
+  let values = serverValues.get(elem)
+  if (values === undefined) {
+    values = new Map()
+    serverValues.set(elem, values)
+  }
+  values.set(name, value)
+  if (!pendingInputs.has(elem)) {
+    writeProperty(elem, name, value)
+  }
+}
+
+function writeProperty(elem: Element, name: string, value: unknown) {
+  const target = elem as any
+  if (target[name] === value) return
+  // keep the caret of the focused element where it was
+  if (name === "value" && elem === document.activeElement) {
+    let start: number | null = null
+    let end: number | null = null
+    try {
+      start = target.selectionStart
+      end = target.selectionEnd
+    } catch {
+      // the input type does not support selection
+    }
+    target[name] = value
+    if (start !== null && end !== null) {
+      try {
+        target.setSelectionRange(start, end)
+      } catch {
+        // the input type does not support selection
+      }
+    }
+  } else {
+    target[name] = value
+  }
+}
+
+// the server rendered the frame after handling `events` events of this connection,
+// so the values of the inputs sent until then are settled by the server
+export function syncHandledInputs(events: number) {
+  for (const [elem, sent] of pendingInputs) {
+    if (sent > events) continue
+    pendingInputs.delete(elem)
+    const values = serverValues.get(elem)
+    if (values === undefined) continue
+    for (const [name, value] of values) {
+      writeProperty(elem, name, value)
+    }
+  }
+}
+
+// forget the inputs of the node and its descendants, called when the node is removed from the DOM
+export function forgetInputs(node: Node) {
+  for (const elem of pendingInputs.keys()) {
+    if (node.contains(elem)) pendingInputs.delete(elem)
+  }
+}
+
+// forget the inputs of the previous connection
+export function resetControlledInputs() {
+  pendingInputs.clear()
+}
@@ -0,0 +1,112 @@
+import { EventRate } from "./rustTypes"
+
+// fields of the payloads that are summed up by `Accumulate`
+const DELTA_FIELDS = ["delta_x", "delta_y", "delta_z", "movement_x", "movement_y"]
+
+export interface RateLimiter<P> {
+  // called for every event, the payload is sent according to the rate
+  push(payload: P): void
+  // send the pending payload now, e.g. when the listener is removed
+  flush(): void
+}
+
+// sending the payloads of a listener at the rate declared by the handler.
+export function rateLimiter<P>(rate: EventRate, send: (payload: P) => void): RateLimiter<P> {
+  switch (rate.type) {
+    case "Immediate":
+      return { push: send, flush: () => {} }
+    case "Throttle":
+      return throttle(rate.content, send)
+    case "Debounce":
+      return debounce(rate.content, send)
+    case "Coalesce":
+      return perFrame(send, (_, latest) => latest)
+    case "Accumulate":
+      return perFrame(send, accumulate)
+  }
+}
+
+function throttle<P>(interval: number, send: (payload: P) => void): RateLimiter<P> {
+  let pending: { payload: P } | undefined
+  let timer: number | undefined
+
+  const tick = () => {
+    if (pending === undefined) {
+      timer = undefined
+      return
+    }
+    send(pending.payload)
+    pending = undefined
+    timer = window.setTimeout(tick, interval)
+  }
+  return {
+    push(payload) {
+      if (timer === undefined) {
+        send(payload)
+        timer = window.setTimeout(tick, interval)
+      } else {
+        pending = { payload }
+      }
+    },
+    flush() {
+      window.clearTimeout(timer)
+      timer = undefined
+      if (pending !== undefined) send(pending.payload)
+      pending = undefined
+    },
+  }
+}
+
+function debounce<P>(delay: number, send: (payload: P) => void): RateLimiter<P> {
+  let pending: { payload: P } | undefined
+  let timer: number | undefined
+
+  const fire = () => {
+    timer = undefined
+    if (pending !== undefined) send(pending.payload)
+    pending = undefined
+  }
+  return {
+    push(payload) {
+      pending = { payload }
+      window.clearTimeout(timer)
+      timer = window.setTimeout(fire, delay)
+    },
+    flush() {
+      window.clearTimeout(timer)
+      fire()
+    },
+  }
+}
+
+function perFrame<P>(send: (payload: P) => void, merge: (previous: P, latest: P) => P): RateLimiter<P> {
+  let pending: { payload: P } | undefined
+  let frame: number | undefined
+
+  const fire = () => {
+    frame = undefined
+    if (pending !== undefined) send(pending.payload)
+    pending = undefined
+  }
+  return {
+    push(payload) {
+      pending = { payload: pending === undefined ? payload : merge(pending.payload, payload) }
+      if (frame === undefined) frame = window.requestAnimationFrame(fire)
+    },
+    flush() {
+      if (frame !== undefined) window.cancelAnimationFrame(frame)
+      fire()
+    },
+  }
+}
+
+// the latest payload with the deltas of both payloads summed up
+function accumulate<P>(previous: P, latest: P): P {
+  const merged: any = { ...latest }
+  for (const field of DELTA_FIELDS) {
+    const p = (previous as any)[field]
+    const l = (latest as any)[field]
+    if (typeof p === "number" && typeof l === "number") merged[field] = p + l
+  }
+  return merged
+}
@@ -8,6 +8,8 @@ import {
   KeyboardEvent as KeyboardEventPayload,
   ProgressEvent as ProgressEventPayload,
   SubmitEvent as SubmitEventPayload,
+  FormDataEvent as FormDataEventPayload,
+  FormData as FormDataPayload,
   WheelEvent as WheelEventPayload,
   AnimationEvent as AnimationEventPayload,
   TouchEvent as TouchEventPayload,
@@ -15,21 +17,53 @@ import {
   TransitionEvent as TransitionEventPayload,
 } from "./rustTypes"
 
+// state of the element, including the user input of form controls
+export function elementToPayload(elem: Element): ElementPayload {
+  const e = elem as any
+  let value: string | undefined = undefined
+  let checked: boolean | undefined = undefined
+  let selected_options: string[] = []
+  let selection_start: number | undefined = undefined
+  let selection_end: number | undefined = undefined
+  if (elem instanceof HTMLInputElement || elem instanceof HTMLTextAreaElement || elem instanceof HTMLSelectElement) {
+    value = elem.value
+  }
+  if (elem instanceof HTMLInputElement && (elem.type === "checkbox" || elem.type === "radio")) {
+    checked = elem.checked
+  }
+  if (elem instanceof HTMLSelectElement) {
+    selected_options = Array.from(elem.selectedOptions).map((option) => option.value)
+  }
+  // selection is not available for some input types such as number
+  try {
+    if (typeof e.selectionStart === "number" && typeof e.selectionEnd === "number") {
+      selection_start = e.selectionStart
+      selection_end = e.selectionEnd
+    }
+  } catch {}
+  return {
+    tag_name: elem.tagName,
+    client_height: elem.clientHeight,
+    client_width: elem.clientWidth,
+    client_left: elem.clientLeft,
+    client_top: elem.clientTop,
+    scroll_height: elem.scrollHeight,
+    scroll_width: elem.scrollWidth,
+    scroll_left: elem.scrollLeft,
+    scroll_top: elem.scrollTop,
+    value,
+    checked,
+    selected_options,
+    selection_start,
+    selection_end,
+  }
+}
+
 export function eventToPayload(e: Event): EventPayload {
   if (e.target !== null && e.target as Element) {
     const target = e.target as Element
     return {
-      target: {
-        tag_name: target.tagName,
-        client_height: target.clientHeight,
-        client_width: target.clientWidth,
-        client_left: target.clientLeft,
-        client_top: target.clientTop,
-        scroll_height: target.scrollHeight,
-        scroll_width: target.scrollWidth,
-        scroll_left: target.scrollLeft,
-        scroll_top: target.scrollTop,
-      }
+      target: elementToPayload(target)
     }
   } else {
     return {
@@ -42,32 +76,12 @@ export function mouseEventToPayload(e: MouseEvent): MouseEventPayload {
   let target: ElementPayload | undefined = undefined
   if (e.target !== null && e.target as Element !== null) {
     const targetElem = e.target as Element
-    target = {
-        tag_name: targetElem.tagName,
-        client_height: targetElem.clientHeight,
-        client_width: targetElem.clientWidth,
-        client_left: targetElem.clientLeft,
-        client_top: targetElem.clientTop,
-        scroll_height: targetElem.scrollHeight,
-        scroll_width: targetElem.scrollWidth,
-        scroll_left: targetElem.scrollLeft,
-        scroll_top: targetElem.scrollTop,
-      }
+    target = elementToPayload(targetElem)
   }
   let related_target: ElementPayload | undefined = undefined
   if (e.relatedTarget !== null && e.relatedTarget as Element !== null) {
     const relatedTargetElem = e.relatedTarget as Element
-    related_target = {
-      tag_name: relatedTargetElem.tagName,
-      client_height: relatedTargetElem.clientHeight,
-      client_width: relatedTargetElem.clientWidth,
-      client_left: relatedTargetElem.clientLeft,
-      client_top: relatedTargetElem.clientTop,
-      scroll_height: relatedTargetElem.scrollHeight,
-      scroll_width: relatedTargetElem.scrollWidth,
-      scroll_left: relatedTargetElem.scrollLeft,
-      scroll_top: relatedTargetElem.scrollTop,
-    }
+    related_target = elementToPayload(relatedTargetElem)
   }
   return {
     alt_key: e.altKey,
@@ -97,32 +111,12 @@ export function focusEventToPayload(e: FocusEvent): FocusEventPayload {
   let target: ElementPayload | undefined = undefined
   if (e.target !== null && e.target as Element !== null) {
     const targetElem = e.target as Element
-    target = {
-        tag_name: targetElem.tagName,
-        client_height: targetElem.clientHeight,
-        client_width: targetElem.clientWidth,
-        client_left: targetElem.clientLeft,
-        client_top: targetElem.clientTop,
-        scroll_height: targetElem.scrollHeight,
-        scroll_width: targetElem.scrollWidth,
-        scroll_left: targetElem.scrollLeft,
-        scroll_top: targetElem.scrollTop,
-      }
+    target = elementToPayload(targetElem)
   }
   let related_target: ElementPayload | undefined = undefined
   if (e.relatedTarget !== null && e.relatedTarget as Element !== null) {
     const relatedTargetElem = e.relatedTarget as Element
-    related_target = {
-      tag_name: relatedTargetElem.tagName,
-      client_height: relatedTargetElem.clientHeight,
-      client_width: relatedTargetElem.clientWidth,
-      client_left: relatedTargetElem.clientLeft,
-      client_top: relatedTargetElem.clientTop,
-      scroll_height: relatedTargetElem.scrollHeight,
-      scroll_width: relatedTargetElem.scrollWidth,
-      scroll_left: relatedTargetElem.scrollLeft,
-      scroll_top: relatedTargetElem.scrollTop,
-    }
+    related_target = elementToPayload(relatedTargetElem)
   }
   return { related_target, target }
 }
@@ -131,32 +125,12 @@ export function dragEventToPayload(e: DragEvent): DragEventPayload {
   let target: ElementPayload | undefined = undefined
   if (e.target !== null && e.target as Element !== null) {
     const targetElem = e.target as Element
-    target = {
-        tag_name: targetElem.tagName,
-        client_height: targetElem.clientHeight,
-        client_width: targetElem.clientWidth,
-        client_left: targetElem.clientLeft,
-        client_top: targetElem.clientTop,
-        scroll_height: targetElem.scrollHeight,
-        scroll_width: targetElem.scrollWidth,
-        scroll_left: targetElem.scrollLeft,
-        scroll_top: targetElem.scrollTop,
-      }
+    target = elementToPayload(targetElem)
   }
   let related_target: ElementPayload | undefined = undefined
   if (e.relatedTarget !== null && e.relatedTarget as Element !== null) {
     const relatedTargetElem = e.relatedTarget as Element
-    related_target = {
-      tag_name: relatedTargetElem.tagName,
-      client_height: relatedTargetElem.clientHeight,
-      client_width: relatedTargetElem.clientWidth,
-      client_left: relatedTargetElem.clientLeft,
-      client_top: relatedTargetElem.clientTop,
-      scroll_height: relatedTargetElem.scrollHeight,
-      scroll_width: relatedTargetElem.scrollWidth,
-      scroll_left: relatedTargetElem.scrollLeft,
-      scroll_top: relatedTargetElem.scrollTop,
-    }
+    related_target = elementToPayload(relatedTargetElem)
   }
   return {
     alt_key: e.altKey,
@@ -186,17 +160,7 @@ export function inputEventToPayload(e: InputEvent): InputEventPayload {
   let target: ElementPayload | undefined = undefined
   if (e.target !== null && e.target as Element !== null) {
     const targetElem = e.target as Element
-    target = {
-        tag_name: targetElem.tagName,
-        client_height: targetElem.clientHeight,
-        client_width: targetElem.clientWidth,
-        client_left: targetElem.clientLeft,
-        client_top: targetElem.clientTop,
-        scroll_height: targetElem.scrollHeight,
-        scroll_width: targetElem.scrollWidth,
-        scroll_left: targetElem.scrollLeft,
-        scroll_top: targetElem.scrollTop,
-      }
+    target = elementToPayload(targetElem)
   }
   return {
     data: e.data ?? "",
@@ -210,17 +174,7 @@ export function keyboardEventToPayload(e: KeyboardEvent): KeyboardEventPayload {
   let target: ElementPayload | undefined = undefined
   if (e.target !== null && e.target as Element !== null) {
     const targetElem = e.target as Element
-    target = {
-        tag_name: targetElem.tagName,
-        client_height: targetElem.clientHeight,
-        client_width: targetElem.clientWidth,
-        client_left: targetElem.clientLeft,
-        client_top: targetElem.clientTop,
-        scroll_height: targetElem.scrollHeight,
-        scroll_width: targetElem.scrollWidth,
-        scroll_left: targetElem.scrollLeft,
-        scroll_top: targetElem.scrollTop,
-      }
+    target = elementToPayload(targetElem)
   }
   return {
     alt_key: e.altKey,
@@ -241,17 +195,7 @@ export function progressEventToPayload(e: ProgressEvent): ProgressEventPayload {
   let target: ElementPayload | undefined = undefined
   if (e.target !== null && e.target as Element !== null) {
     const targetElem = e.target as Element
-    target = {
-        tag_name: targetElem.tagName,
-        client_height: targetElem.clientHeight,
-        client_width: targetElem.clientWidth,
-        client_left: targetElem.clientLeft,
-        client_top: targetElem.clientTop,
-        scroll_height: targetElem.scrollHeight,
-        scroll_width: targetElem.scrollWidth,
-        scroll_left: targetElem.scrollLeft,
-        scroll_top: targetElem.scrollTop,
-      }
+    target = elementToPayload(targetElem)
   }
   return {
     length_computable: e.lengthComputable,
@@ -265,51 +209,61 @@ export function submitEventToPayload(e: SubmitEvent): SubmitEventPayload {
   let target: ElementPayload | undefined = undefined
   if (e.target !== null && e.target as Element !== null) {
     const targetElem = e.target as Element
-    target = {
-        tag_name: targetElem.tagName,
-        client_height: targetElem.clientHeight,
-        client_width: targetElem.clientWidth,
-        client_left: targetElem.clientLeft,
-        client_top: targetElem.clientTop,
-        scroll_height: targetElem.scrollHeight,
-        scroll_width: targetElem.scrollWidth,
-        scroll_left: targetElem.scrollLeft,
-        scroll_top: targetElem.scrollTop,
-      }
+    target = elementToPayload(targetElem)
   }
   let submitter: ElementPayload | undefined = undefined
   if (e.submitter !== null && e.submitter as Element !== null) {
     const submitter_elem = e.submitter as Element
-    submitter = {
-      tag_name: submitter_elem.tagName,
-      client_height: submitter_elem.clientHeight,
-      client_width: submitter_elem.clientWidth,
-      client_left: submitter_elem.clientLeft,
-      client_top: submitter_elem.clientTop,
-      scroll_height: submitter_elem.scrollHeight,
-      scroll_width: submitter_elem.scrollWidth,
-      scroll_left: submitter_elem.scrollLeft,
-      scroll_top: submitter_elem.scrollTop,
+    submitter = elementToPayload(submitter_elem)
+  }
+  let formData = new FormData()
+  if (e.target instanceof HTMLFormElement) {
+    try {
+      // the value of the submitter is included like the web browser submits the form
+      formData = new FormData(e.target, e.submitter)
+    } catch {
+      // the web browser does not support the submitter argument
+      formData = new FormData(e.target)
     }
   }
-  return { submitter, target }
+  return { form_data: formDataToPayload(formData), submitter, target }
+}
+
+export function formDataEventToPayload(e: FormDataEvent): FormDataEventPayload {
+  let target: ElementPayload | undefined = undefined
+  if (e.target !== null && e.target as Element !== null) {
+    target = elementToPayload(e.target as Element)
+  }
+  return { form_data: formDataToPayload(e.formData), target }
+}
+
+// named fields of the form, only metadata of the files is sent
+function formDataToPayload(formData: FormData): FormDataPayload {
+  const payload: FormDataPayload = {}
+  formData.forEach((value, name) => {
+    const values = payload[name] ?? (payload[name] = [])
+    if (typeof value === "string") {
+      values.push({ type: "Text", content: value })
+    } else {
+      values.push({
+        type: "File",
+        content: {
+          name: value.name,
+          size: value.size,
+          mime_type: value.type,
+          last_modified: value.lastModified,
+        }
+      })
+    }
+  })
+  return payload
 }
 
 export function wheelEventToPayload(e: WheelEvent): WheelEventPayload {
   let target: ElementPayload | undefined = undefined
   if (e.target !== null && e.target as Element !== null) {
     const targetElem = e.target as Element
-    target = {
-        tag_name: targetElem.tagName,
-        client_height: targetElem.clientHeight,
-        client_width: targetElem.clientWidth,
-        client_left: targetElem.clientLeft,
-        client_top: targetElem.clientTop,
-        scroll_height: targetElem.scrollHeight,
-        scroll_width: targetElem.scrollWidth,
-        scroll_left: targetElem.scrollLeft,
-        scroll_top: targetElem.scrollTop,
-      }
+    target = elementToPayload(targetElem)
   }
   return {
     alt_key: e.altKey,
@@ -342,17 +296,7 @@ export function animationEventToPayload(e: AnimationEvent): AnimationEventPayloa
   let target: ElementPayload | undefined = undefined
   if (e.target !== null && e.target as Element !== null) {
     const targetElem = e.target as Element
-    target = {
-        tag_name: targetElem.tagName,
-        client_height: targetElem.clientHeight,
-        client_width: targetElem.clientWidth,
-        client_left: targetElem.clientLeft,
-        client_top: targetElem.clientTop,
-        scroll_height: targetElem.scrollHeight,
-        scroll_width: targetElem.scrollWidth,
-        scroll_left: targetElem.scrollLeft,
-        scroll_top: targetElem.scrollTop,
-      }
+    target = elementToPayload(targetElem)
   }
   return {
     animation_name: e.animationName,
@@ -366,33 +310,13 @@ export function pointerEventToPayload(e: PointerEvent): PointerEventPayload {
   let target: ElementPayload | undefined = undefined
   if (e.target !== null && e.target as Element !== null) {
     const targetElem = e.target as Element
-    target = {
-        tag_name: targetElem.tagName,
-        client_height: targetElem.clientHeight,
-        client_width: targetElem.clientWidth,
-        client_left: targetElem.clientLeft,
-        client_top: targetElem.clientTop,
-        scroll_height: targetElem.scrollHeight,
-        scroll_width: targetElem.scrollWidth,
-        scroll_left: targetElem.scrollLeft,
-        scroll_top: targetElem.scrollTop,
-      }
+    target = elementToPayload(targetElem)
   }
 
   let related_target: ElementPayload | undefined = undefined
   if (e.relatedTarget !== null && e.relatedTarget as Element !== null) {
     const relatedTargetElem = e.relatedTarget as Element
-    related_target = {
-      tag_name: relatedTargetElem.tagName,
-      client_height: relatedTargetElem.clientHeight,
-      client_width: relatedTargetElem.clientWidth,
-      client_left: relatedTargetElem.clientLeft,
-      client_top: relatedTargetElem.clientTop,
-      scroll_height: relatedTargetElem.scrollHeight,
-      scroll_width: relatedTargetElem.scrollWidth,
-      scroll_left: relatedTargetElem.scrollLeft,
-      scroll_top: relatedTargetElem.scrollTop,
-    }
+    related_target = elementToPayload(relatedTargetElem)
   }
   return {
     alt_key: e.altKey,
@@ -431,17 +355,7 @@ export function touchEventToPayload(e: TouchEvent): TouchEventPayload {
   let target: ElementPayload | undefined = undefined
   if (e.target !== null && e.target as Element !== null) {
     const targetElem = e.target as Element
-    target = {
-        tag_name: targetElem.tagName,
-        client_height: targetElem.clientHeight,
-        client_width: targetElem.clientWidth,
-        client_left: targetElem.clientLeft,
-        client_top: targetElem.clientTop,
-        scroll_height: targetElem.scrollHeight,
-        scroll_width: targetElem.scrollWidth,
-        scroll_left: targetElem.scrollLeft,
-        scroll_top: targetElem.scrollTop,
-      }
+    target = elementToPayload(targetElem)
   }
   const changed_touches = []
   for (let i = 0; i < e.changedTouches.length; i++) {
@@ -449,17 +363,7 @@ export function touchEventToPayload(e: TouchEvent): TouchEventPayload {
     let touchTarget: ElementPayload | undefined = undefined
     if (touch.target !== null && touch.target as Element !== null) {
       const touchTargetElem = touch.target as Element
-      touchTarget = {
-        tag_name: touchTargetElem.tagName,
-        client_height: touchTargetElem.clientHeight,
-        client_width: touchTargetElem.clientWidth,
-        client_left: touchTargetElem.clientLeft,
-        client_top: touchTargetElem.clientTop,
-        scroll_height: touchTargetElem.scrollHeight,
-        scroll_width: touchTargetElem.scrollWidth,
-        scroll_left: touchTargetElem.scrollLeft,
-        scroll_top: touchTargetElem.scrollTop,
-      }
+      touchTarget = elementToPayload(touchTargetElem)
     }
     changed_touches.push({
       identifier: touch.identifier,
@@ -481,17 +385,7 @@ export function touchEventToPayload(e: TouchEvent): TouchEventPayload {
     let touchTarget: ElementPayload | undefined = undefined
     if (touch.target !== null && touch.target as Element !== null) {
       const touchTargetElem = touch.target as Element
-      touchTarget = {
-        tag_name: touchTargetElem.tagName,
-        client_height: touchTargetElem.clientHeight,
-        client_width: touchTargetElem.clientWidth,
-        client_left: touchTargetElem.clientLeft,
-        client_top: touchTargetElem.clientTop,
-        scroll_height: touchTargetElem.scrollHeight,
-        scroll_width: touchTargetElem.scrollWidth,
-        scroll_left: touchTargetElem.scrollLeft,
-        scroll_top: touchTargetElem.scrollTop,
-      }
+      touchTarget = elementToPayload(touchTargetElem)
     }
     target_touches.push({
       identifier: touch.identifier,
@@ -513,17 +407,7 @@ export function touchEventToPayload(e: TouchEvent): TouchEventPayload {
     let touchTarget: ElementPayload | undefined = undefined
     if (touch.target !== null && touch.target as Element !== null) {
       const touchTargetElem = touch.target as Element
-      touchTarget = {
-        tag_name: touchTargetElem.tagName,
-        client_height: touchTargetElem.clientHeight,
-        client_width: touchTargetElem.clientWidth,
-        client_left: touchTargetElem.clientLeft,
-        client_top: touchTargetElem.clientTop,
-        scroll_height: touchTargetElem.scrollHeight,
-        scroll_width: touchTargetElem.scrollWidth,
-        scroll_left: touchTargetElem.scrollLeft,
-        scroll_top: touchTargetElem.scrollTop,
-      }
+      touchTarget = elementToPayload(touchTargetElem)
     }
     touches.push({
       identifier: touch.identifier,
@@ -555,17 +439,7 @@ export function transitionEventToPayload(e: TransitionEvent): TransitionEventPay
   let target: ElementPayload | undefined = undefined
   if (e.target !== null && e.target as Element !== null) {
     const targetElem = e.target as Element
-    target = {
-        tag_name: targetElem.tagName,
-        client_height: targetElem.clientHeight,
-        client_width: targetElem.clientWidth,
-        client_left: targetElem.clientLeft,
-        client_top: targetElem.clientTop,
-        scroll_height: targetElem.scrollHeight,
-        scroll_width: targetElem.scrollWidth,
-        scroll_left: targetElem.scrollLeft,
-        scroll_top: targetElem.scrollTop,
-      }
+    target = elementToPayload(targetElem)
   }
   return {
     elapsed_time: e.elapsedTime,
@@ -1,23 +1,19 @@
 import { DifferenceCommands } from "./rustTypes";
 import connection from "./connection";
-import { applyCommand, applyStyleCommand } from "./applyCommand";
+import { applyBrowserCommand, applyCommand, applyDomCommand, applyStyleCommand, resetRendered } from "./applyCommand";
+import { syncHandledInputs } from "./controlledInput";
 
 const main = async () => {
-  const update = () => {
-    connection.send("DRAW")
-    requestAnimationFrame(update)
-  }
-
-  connection.onopen(() => {
-    update()
-  })
-
   const appRoot = document.querySelector<HTMLDivElement>('#app')
   const portalRoot = document.querySelector<HTMLDivElement>('#portal')
 
   connection.oncommand((cmds: DifferenceCommands) => {
     if (appRoot === null || portalRoot === null) return
 
+    if (cmds.reset) {
+      resetRendered(appRoot, portalRoot)
+    }
+
     for (const cmd of cmds.main) {
       applyCommand(appRoot, cmd)
     }
@@ -27,6 +23,17 @@ const main = async () => {
     for (const cmd of cmds.styles) {
       applyStyleCommand(cmd)
     }
+    for (const cmd of cmds.dom) {
+      applyDomCommand(cmd)
+    }
+    for (const cmd of cmds.browser) {
+      applyBrowserCommand(cmd)
+    }
+    // restore the values of the inputs that the server has handled
+    syncHandledInputs(cmds.events)
+
+    // let the server push the next frame
+    connection.sendAck(cmds.frame)
   })
 }
 
@@ -0,0 +1,40 @@
+import { ElementQuery, ElementQueryResult } from "./rustTypes"
+import connection from "./connection"
+
+// rendered element of the node ref
+export function findRef(node_ref: string): Element | null {
+  return document.querySelector(`[data-jubako-ref="${CSS.escape(node_ref)}"]`)
+}
+
+// answer the query about the element of the node ref, after the frame is applied
+export function answerElementQuery(query_id: string, node_ref: string, query: ElementQuery) {
+  const element = findRef(node_ref)
+  const result = element === null ? undefined : queryElement(element, query)
+  connection.sendQueryResponse({ query_id, result })
+}
+
+function queryElement(element: Element, query: ElementQuery): ElementQueryResult {
+  if (query.type === "BoundingRect") {
+    const { x, y, width, height, top, right, bottom, left } = element.getBoundingClientRect()
+    return { type: "BoundingRect", content: { x, y, width, height, top, right, bottom, left } }
+  } else if (query.type === "ComputedStyle") {
+    const style = window.getComputedStyle(element)
+    const values: Record<string, string> = {}
+    for (const name of query.content) {
+      values[name] = style.getPropertyValue(name)
+    }
+    return { type: "ComputedStyle", content: values }
+  } else {
+    return {
+      type: "ScrollMetrics",
+      content: {
+        scroll_top: element.scrollTop,
+        scroll_left: element.scrollLeft,
+        scroll_width: element.scrollWidth,
+        scroll_height: element.scrollHeight,
+        client_width: element.clientWidth,
+        client_height: element.clientHeight,
+      }
+    }
+  }
+}
@@ -4,16 +4,71 @@
 
 export type EventHandleId = string;
 
+export type Props = Record<string, Prop>;
+
+export type FormData = Record<string, FormValue[]>;
+
+export interface ListenerOptions {
+	capture: boolean;
+	passive: boolean;
+	once: boolean;
+	stop_propagation: boolean;
+	stop_immediate_propagation: boolean;
+	rate: EventRate;
+	prevent_default_if?: EventCondition;
+}
+
 export interface HandleEvent {
 	handle_id: EventHandleId;
 	handle_events: VNodeEventType[];
 	handle_prevent_default_events: VNodeEventType[];
+	listener_options: Record<VNodeEventType, ListenerOptions>;
 }
 
 export interface DifferenceCommands {
+	frame: number;
+	reset: boolean;
+	events: number;
 	main: DifferenceCommand[];
 	portals: DifferenceCommand[];
 	styles: StyleDifferenceCommand[];
+	dom: DomCommand[];
+	browser: BrowserCommand[];
+}
+
+export interface FrameAck {
+	frame: number;
+}
+
+export interface ElementRect {
+	x: number;
+	y: number;
+	width: number;
+	height: number;
+	top: number;
+	right: number;
+	bottom: number;
+	left: number;
+}
+
+export interface ScrollMetrics {
+	scroll_top: number;
+	scroll_left: number;
+	scroll_width: number;
+	scroll_height: number;
+	client_width: number;
+	client_height: number;
+}
+
+export interface ScrollIntoViewOptions {
+	behavior: ScrollBehavior;
+	block: ScrollAlignment;
+	inline: ScrollAlignment;
+}
+
+export interface ElementQueryResponse {
+	query_id: string;
+	result?: ElementQueryResult;
 }
 
 export interface Element {
@@ -26,6 +81,11 @@ export interface Element {
 	scroll_width: number;
 	scroll_left: number;
 	scroll_top: number;
+	value?: string;
+	checked?: boolean;
+	selected_options: string[];
+	selection_start?: number;
+	selection_end?: number;
 }
 
 export interface Event {
@@ -112,10 +172,23 @@ export interface ProgressEvent {
 }
 
 export interface SubmitEvent {
+	form_data: FormData;
 	submitter?: Element;
 	target?: Element;
 }
 
+export interface FormDataEvent {
+	form_data: FormData;
+	target?: Element;
+}
+
+export interface FileMetadata {
+	name: string;
+	size: number;
+	mime_type: string;
+	last_modified: number;
+}
+
 export interface WheelEvent {
 	alt_key: boolean;
 	button: number;
@@ -218,9 +291,78 @@ export interface VNodeEvent {
 	kind: VNodeEventKind;
 }
 
+export type BrowserCommand =
+	| { type: "OpenWindow", content: {
+	url: string;
+}}
+	| { type: "SetSession", content: {
+	id: string;
+}}
+	| { type: "ConfigureConnection", content: {
+	offline_events: OfflineEventPolicy;
+}}
+	| { type: "QueryElement", content: {
+	query_id: string;
+	node_ref: string;
+	query: ElementQuery;
+}};
+
+export type ElementQuery =
+	| { type: "BoundingRect", content?: undefined }
+	| { type: "ComputedStyle", content: string[] }
+	| { type: "ScrollMetrics", content?: undefined };
+
+export type ElementQueryResult =
+	| { type: "BoundingRect", content: ElementRect }
+	| { type: "ComputedStyle", content: Record<string, string> }
+	| { type: "ScrollMetrics", content: ScrollMetrics };
+
+export type DomCommand =
+	| { type: "Focus", content: {
+	node_ref: string;
+}}
+	| { type: "Blur", content: {
+	node_ref: string;
+}}
+	| { type: "ScrollIntoView", content: {
+	node_ref: string;
+	options: ScrollIntoViewOptions;
+}}
+	| { type: "SetSelectionRange", content: {
+	node_ref: string;
+	start: number;
+	end: number;
+}}
+	| { type: "Select", content: {
+	node_ref: string;
+}}
+	| { type: "Click", content: {
+	node_ref: string;
+}};
+
+export type Prop =
+	| { type: "Attribute", content: string }
+	| { type: "BooleanAttribute", content: boolean }
+	| { type: "Property", content: PropertyValue };
+
+export type PropertyValue =
+	| { type: "String", content: string }
+	| { type: "Bool", content: boolean }
+	| { type: "Number", content: number };
+
+export type FormValue =
+	| { type: "Text", content: string }
+	| { type: "File", content: FileMetadata };
+
 export type DifferenceProps =
-	| { type: "Add", content: string }
-	| { type: "Remove", content: string };
+	| { type: "Set", content: {
+	name: string;
+	value: Prop;
+}}
+	| { type: "Remove", content: {
+	name: string;
+	value: Prop;
+}};
 
 export type DifferenceClass =
 	| { type: "Add", content: string }
@@ -245,29 +387,43 @@ export type DifferenceCommand =
 	| { type: "ReplaceToElement", content: {
 	index: number;
 	new_tag: string;
+	namespace: Namespace;
 	classes: string[];
-	props: string[];
+	props: Props;
 	event: HandleEvent;
 	children: DifferenceCommand[];
 }}
 	| { type: "ReplaceToText", content: {
 	index: number;
 	text: string;
+}}
+	| { type: "ReplaceToRawHtml", content: {
+	index: number;
+	html: string;
 }}
 	| { type: "InsertElement", content: {
 	index: number;
 	tag: string;
+	namespace: Namespace;
 	classes: string[];
-	props: string[];
+	props: Props;
 	event: HandleEvent;
 	children: DifferenceCommand[];
 }}
 	| { type: "InsertText", content: {
 	index: number;
 	text: string;
+}}
+	| { type: "InsertRawHtml", content: {
+	index: number;
+	html: string;
 }}
 	| { type: "Remove", content: {
 	index: number;
+}}
+	| { type: "Move", content: {
+	from: number;
+	to: number;
 }};
 
 export type StyleDifferenceCommand =
@@ -313,7 +469,7 @@ export type VNodeEventKind =
 	| { type: "Focus", content: FocusEvent }
 	| { type: "FocusIn", content: FocusEvent }
 	| { type: "FocusOut", content: FocusEvent }
-	| { type: "FormData", content: Event }
+	| { type: "FormData", content: FormDataEvent }
 	| { type: "GotPointerCapture", content: PointerEvent }
 	| { type: "Input", content: InputEvent }
 	| { type: "Invalid", content: Event }
@@ -468,3 +624,46 @@ export enum VNodeEventType {
 	Waiting = "Waiting",
 	Wheel = "Wheel",
 }
+
+export enum ScrollBehavior {
+	Auto = "auto",
+	Smooth = "smooth",
+	Instant = "instant",
+}
+
+export enum ScrollAlignment {
+	Start = "start",
+	Center = "center",
+	End = "end",
+	Nearest = "nearest",
+}
+
+export enum Namespace {
+	Html = "Html",
+	Svg = "Svg",
+	MathMl = "MathMl",
+}
+
+export enum OfflineEventPolicy {
+	Discard = "Discard",
+	Queue = "Queue",
+}
+
+export type EventRate =
+	| { type: "Immediate", content?: undefined }
+	| { type: "Throttle", content: number }
+	| { type: "Debounce", content: number }
+	| { type: "Coalesce", content?: undefined }
+	| { type: "Accumulate", content?: undefined };
+
+export type EventCondition =
+	| { type: "Key", content: string[] }
+	| { type: "Code", content: string[] }
+	| { type: "Button", content: number[] }
+	| { type: "CtrlKey", content?: undefined }
+	| { type: "ShiftKey", content?: undefined }
+	| { type: "AltKey", content?: undefined }
+	| { type: "MetaKey", content?: undefined }
+	| { type: "All", content: EventCondition[] }
+	| { type: "Any", content: EventCondition[] }
+	| { type: "Not", content: EventCondition };
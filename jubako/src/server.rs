@@ -2,27 +2,91 @@
 //!
 //! # Example
 
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
 use axum::{
     body::{boxed, Full},
     extract::{
         ws::{WebSocket, WebSocketUpgrade},
-        Path,
+        Path, Query,
     },
     http::{header, StatusCode},
     response::{IntoResponse, Response},
     routing::{get, Router},
 };
+use parking_lot::Mutex;
 use rust_embed::RustEmbed;
+use uuid::Uuid;
 
-use crate::simple_window;
-use crate::Message;
+use crate::{dock_window, simple_window};
+use crate::{Message, OfflineEventPolicy, SessionConfig};
 
 async fn handle_static(Path(path): Path<String>) -> impl IntoResponse {
     StaticFile(path)
 }
 
-async fn handle_simple_window_socket(socket: WebSocket, runner: simple_window::SimpleWindowRunner) {
-    runner.run(socket).await;
+type SessionRegistry = Arc<Mutex<HashMap<Uuid, simple_window::SimpleWindowRunner>>>;
+
+// window of a shared route and the number of its connections
+#[derive(Default)]
+struct SharedWindow {
+    runner: Option<simple_window::SimpleWindowRunner>,
+    connections: usize,
+    // generation of the grace period, incremented by each disconnection
+    disconnections: u64,
+}
+
+async fn handle_shared_window_socket<T: Message>(
+    socket: WebSocket,
+    window_creator: impl simple_window::SimpleWindowCreator<Message = T>,
+    shared: Arc<Mutex<SharedWindow>>,
+    config: SessionConfig,
+) {
+    let runner = {
+        let mut shared = shared.lock();
+        shared.connections += 1;
+        shared
+            .runner
+            .get_or_insert_with(|| simple_window::SimpleWindowRunner::new(window_creator))
+            .clone()
+    };
+    runner.run_shared(socket, config.clone()).await;
+
+    let disconnection = {
+        let mut shared = shared.lock();
+        shared.connections -= 1;
+        shared.disconnections += 1;
+        (shared.connections == 0).then_some(shared.disconnections)
+    };
+    let Some(disconnection) = disconnection else {
+        return;
+    };
+    // end the window when no client reconnects within the grace period,
+    // unless a client connected and disconnected again, which starts a new grace period
+    tokio::time::sleep(config.grace_period).await;
+    let ended = {
+        let mut shared = shared.lock();
+        let is_same = shared.runner.as_ref().map(|r| r.session_id()) == Some(runner.session_id());
+        (shared.connections == 0 && shared.disconnections == disconnection && is_same)
+            .then(|| shared.runner.take())
+            .flatten()
+    };
+    if let Some(runner) = ended {
+        runner.end();
+    }
+}
+
+async fn handle_simple_window_socket(
+    socket: WebSocket,
+    runner: simple_window::SimpleWindowRunner,
+    sessions: SessionRegistry,
+    config: SessionConfig,
+) {
+    let session_id = runner.session_id();
+    sessions.lock().insert(session_id, runner.clone());
+    if runner.run(socket, config).await {
+        sessions.lock().remove(&session_id);
+    }
 }
 
 async fn not_found() -> impl IntoResponse {
@@ -34,11 +98,49 @@ async fn not_found() -> impl IntoResponse {
 
 pub struct Server {
     app: Router,
+    // shared with the routes, so that settings apply regardless of the order of the calls
+    config: Arc<Mutex<SessionConfig>>,
+}
+impl Default for Server {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 impl Server {
     /// Create a new jubako server.
     pub fn new() -> Self {
-        Self { app: Router::new() }
+        Self {
+            app: Router::new(),
+            config: Default::default(),
+        }
+    }
+
+    /// Set the maximum number of frames per second pushed to each browser window.
+    ///
+    /// Renders requested within a frame are coalesced into one frame. The default is 60.
+    pub fn max_frame_rate(self, frame_rate: u32) -> Self {
+        if frame_rate == 0 {
+            panic!("frame rate must not be zero");
+        }
+        self.config.lock().frame_interval = Duration::from_secs(1) / frame_rate;
+        self
+    }
+
+    /// Set how long a session is kept alive after its browser window disconnected.
+    ///
+    /// A browser that reconnects within the period resumes the session with its state intact,
+    /// otherwise a new window is created. The default is 30 seconds.
+    pub fn session_grace_period(self, grace_period: Duration) -> Self {
+        self.config.lock().grace_period = grace_period;
+        self
+    }
+
+    /// Set what the browser does with events that occur while it is disconnected.
+    ///
+    /// The default is [`OfflineEventPolicy::Discard`].
+    pub fn offline_events(self, policy: OfflineEventPolicy) -> Self {
+        self.config.lock().offline_events = policy;
+        self
     }
 
     /// Add route simple window.
@@ -46,15 +148,61 @@ impl Server {
         self,
         path: &str,
         window_creator: impl simple_window::SimpleWindowCreator<Message = T>,
+    ) -> Self {
+        self.route_window(path, move || {
+            simple_window::SimpleWindowRunner::new(window_creator.clone())
+        })
+    }
+
+    /// Add route dock window.
+    pub fn route_dock_window(self, path: &str, dock_window: dock_window::DockWindow) -> Self {
+        let key = path.trim_end_matches('/').to_string();
+        self.route_window(path, move || dock_window.create_runner(&key))
+    }
+
+    /// Add route window that is shared by all clients.
+    ///
+    /// The window is created when the first client connects, and every client shows the same
+    /// state of the window. Messages dispatched by any client are handled by the shared window.
+    /// Browser commands and DOM operations are sent to every client.
+    ///
+    /// The window is disconnected when no client is connected for the session grace period,
+    /// and a new window is created for the next client.
+    pub fn route_shared_window<T: Message>(
+        self,
+        path: &str,
+        window_creator: impl simple_window::SimpleWindowCreator<Message = T>,
     ) -> Self {
         let path = String::from(path).trim_end_matches('/').to_string();
-        if path == "" {
+        let app = Self::route_assets(self.app, &path);
+
+        // handle websocket
+        let shared = Arc::new(Mutex::new(SharedWindow::default()));
+        let config = self.config.clone();
+        let app = app.route(
+            &format!("{path}/ws"),
+            get(|ws: WebSocketUpgrade| async move {
+                let config = config.lock().clone();
+                ws.on_upgrade(move |websocket| {
+                    handle_shared_window_socket(websocket, window_creator, shared, config)
+                })
+            }),
+        );
+
+        Self {
+            app,
+            config: self.config,
+        }
+    }
+
+    // handle index.html and static files
+    fn route_assets(app: Router, path: &str) -> Router {
+        if path.is_empty() {
             panic!("path must not be empty");
         }
 
         // handle index.html
-        let app = self
-            .app
+        let app = app
             .route(
                 &format!("{path}/"),
                 get(|| async { StaticFile("index.html") }),
@@ -65,18 +213,67 @@ impl Server {
             );
 
         // handle static files
-        let app = app.route(&format!("{path}/*path"), get(handle_static));
+        app.route(&format!("{path}/*path"), get(handle_static))
+    }
+
+    fn route_window(
+        self,
+        path: &str,
+        create_runner: impl Fn() -> simple_window::SimpleWindowRunner + Clone + Send + Sync + 'static,
+    ) -> Self {
+        let path = String::from(path).trim_end_matches('/').to_string();
+        let app = Self::route_assets(self.app, &path);
 
         // handle websocket
+        // `?session=<id>` attaches a pop-out window to the running session,
+        // `?resume=<id>` resumes the session that lost its connection.
+        let sessions = SessionRegistry::default();
+        let config = self.config.clone();
         let app = app.route(
             &format!("{path}/ws"),
-            get(|ws: WebSocketUpgrade| async move {
-                let runner = simple_window::SimpleWindowRunner::new(window_creator);
-                ws.on_upgrade(|websocket| handle_simple_window_socket(websocket, runner))
-            }),
+            get(
+                |ws: WebSocketUpgrade, Query(query): Query<HashMap<String, String>>| async move {
+                    let config = config.lock().clone();
+                    let find_session = |key: &str| {
+                        query
+                            .get(key)
+                            .and_then(|id| Uuid::parse_str(id).ok())
+                            .and_then(|id| sessions.lock().get(&id).cloned())
+                    };
+                    let pop_out = find_session("session");
+                    let resumed = find_session("resume").filter(|r| r.is_awaiting_resume());
+                    match pop_out {
+                        Some(runner) => {
+                            let panel = query.get("panel").cloned();
+                            ws.on_upgrade(move |websocket| async move {
+                                runner.run_pop_out(websocket, panel, config).await
+                            })
+                        }
+                        // the pop-out window must not show a new window of its own
+                        None if query.contains_key("session") => ws.on_upgrade(|websocket| {
+                            simple_window::close_websocket(
+                                websocket,
+                                simple_window::CLOSE_SESSION_EXPIRED,
+                                "session expired",
+                            )
+                        }),
+                        None => {
+                            // create a new window if the session to resume is already ended
+                            // or still connected to another browser
+                            let runner = resumed.unwrap_or_else(&create_runner);
+                            ws.on_upgrade(|websocket| {
+                                handle_simple_window_socket(websocket, runner, sessions, config)
+                            })
+                        }
+                    }
+                },
+            ),
         );
 
-        Self { app }
+        Self {
+            app,
+            config: self.config,
+        }
     }
 
     /// Start run the server.
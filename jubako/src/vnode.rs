@@ -1,13 +1,25 @@
 //! Server side Virtual Dom Node and related types.
 
 mod command;
-mod event;
+mod component;
+pub mod event;
 mod event_list;
+mod node_ref;
 mod processor;
+mod props;
+mod raw_html;
+#[allow(clippy::module_inception)]
 mod vnode;
 
-pub(crate) use command::DifferenceCommands;
+pub(crate) use command::{BrowserCommand, DifferenceCommands, DomCommand};
+pub use component::{Component, ComponentInstance};
 pub(crate) use event::VNodeEvent;
+pub(crate) use node_ref::{ElementQuery, ElementQueryResponse, ElementQueryResult};
+pub use node_ref::{
+    ElementRect, NodeRef, ScrollAlignment, ScrollBehavior, ScrollIntoViewOptions, ScrollMetrics,
+};
 pub(crate) use processor::Processor;
+pub use props::{Prop, PropertyValue, Props};
+pub use raw_html::{HtmlSanitizer, RawHtml};
 
 pub use vnode::*;
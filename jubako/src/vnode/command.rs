@@ -1,15 +1,23 @@
 //! Commands sent to the web browser to update the DOM.
 
+use std::collections::HashMap;
 use typeshare::typeshare;
 
-use crate::vnode::{EventHandleId, VNodeEventType};
+use crate::{
+    vnode::{
+        ElementQuery, EventHandleId, ListenerOptions, Namespace, Prop, Props,
+        ScrollIntoViewOptions, VNodeEventType,
+    },
+    OfflineEventPolicy,
+};
 
 #[typeshare]
 #[derive(serde::Serialize, Clone, Debug)]
 #[serde(tag = "type", content = "content")]
 pub(crate) enum DifferenceProps {
-    Add(String),
-    Remove(String),
+    Set { name: String, value: Prop },
+    // the removed prop tells how to remove it
+    Remove { name: String, value: Prop },
 }
 
 #[typeshare]
@@ -34,6 +42,8 @@ pub(crate) struct HandleEvent {
     pub(crate) handle_id: EventHandleId,
     pub(crate) handle_events: Vec<VNodeEventType>,
     pub(crate) handle_prevent_default_events: Vec<VNodeEventType>,
+    // events that are not listened with the default options
+    pub(crate) listener_options: HashMap<VNodeEventType, ListenerOptions>,
 }
 
 #[typeshare]
@@ -57,8 +67,9 @@ pub(crate) enum DifferenceCommand {
     ReplaceToElement {
         index: u32,
         new_tag: String,
+        namespace: Namespace,
         classes: Vec<String>,
-        props: Vec<String>,
+        props: Props,
         event: HandleEvent,
         children: Vec<DifferenceCommand>,
     },
@@ -67,12 +78,18 @@ pub(crate) enum DifferenceCommand {
         index: u32,
         text: String,
     },
+    // replace the node with raw html
+    ReplaceToRawHtml {
+        index: u32,
+        html: String,
+    },
     // Insert element
     InsertElement {
         index: u32,
         tag: String,
+        namespace: Namespace,
         classes: Vec<String>,
-        props: Vec<String>,
+        props: Props,
         event: HandleEvent,
         children: Vec<DifferenceCommand>,
     },
@@ -81,10 +98,20 @@ pub(crate) enum DifferenceCommand {
         index: u32,
         text: String,
     },
+    // Insert raw html
+    InsertRawHtml {
+        index: u32,
+        html: String,
+    },
     // Remove element
     Remove {
         index: u32,
     },
+    // Move element, `to` is the index after removing the element from `from`
+    Move {
+        from: u32,
+        to: u32,
+    },
 }
 
 #[typeshare]
@@ -97,8 +124,68 @@ pub(crate) enum StyleDifferenceCommand {
 
 #[typeshare]
 #[derive(serde::Serialize, Clone, Debug)]
+#[serde(tag = "type", content = "content")]
+pub(crate) enum BrowserCommand {
+    // open a new browser window
+    OpenWindow {
+        url: String,
+    },
+    // remember the session to resume it after reconnecting
+    SetSession {
+        id: String,
+    },
+    // settings of the connection that are sent with the first frame
+    ConfigureConnection {
+        offline_events: OfflineEventPolicy,
+    },
+    // ask the element of the node ref, the browser responds with `ElementQueryResponse`
+    QueryElement {
+        query_id: String,
+        node_ref: String,
+        query: ElementQuery,
+    },
+}
+
+// imperative operations on the element of a node ref, applied after the difference commands
+#[typeshare]
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(tag = "type", content = "content")]
+pub(crate) enum DomCommand {
+    Focus {
+        node_ref: String,
+    },
+    Blur {
+        node_ref: String,
+    },
+    ScrollIntoView {
+        node_ref: String,
+        options: ScrollIntoViewOptions,
+    },
+    SetSelectionRange {
+        node_ref: String,
+        start: u32,
+        end: u32,
+    },
+    Select {
+        node_ref: String,
+    },
+    Click {
+        node_ref: String,
+    },
+}
+
+#[typeshare]
+#[derive(serde::Serialize, Clone, Debug, Default)]
 pub(crate) struct DifferenceCommands {
+    // sequence number of the frame, that the browser acknowledges after applying the commands
+    pub(crate) frame: u64,
+    // the browser must clear the rendered DOM before applying the commands
+    pub(crate) reset: bool,
+    // number of events of the connection whose messages were handled before the frame was rendered
+    pub(crate) events: u64,
     pub(crate) main: Vec<DifferenceCommand>,
     pub(crate) portals: Vec<DifferenceCommand>,
     pub(crate) styles: Vec<StyleDifferenceCommand>,
+    pub(crate) dom: Vec<DomCommand>,
+    pub(crate) browser: Vec<BrowserCommand>,
 }
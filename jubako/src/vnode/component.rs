@@ -0,0 +1,84 @@
+//! Reusable part of a view that has its own state and messages.
+
+use parking_lot::Mutex;
+use std::{fmt::Debug, sync::Arc};
+
+use crate::vnode::{MessageHandledVNode, MessageSender, NestedNode, NestedVNode, VNode};
+use crate::{DirtyFlag, Message};
+
+/// Part of a view that has its own state, messages, update and view.
+///
+/// The parent keeps a [`ComponentInstance`] in its state and embeds the view of the instance
+/// with [`ComponentInstance::view`].
+pub trait Component: 'static + Send {
+    type Message: Message;
+    fn update(&mut self, message: Self::Message) -> DirtyFlag;
+    fn view(&self) -> VNode<Self::Message>;
+}
+
+/// Instance of a component that is shared by the parent and the views of the instance.
+pub struct ComponentInstance<C: Component> {
+    component: Arc<Mutex<C>>,
+}
+impl<C: Component> ComponentInstance<C> {
+    pub fn new(component: C) -> Self {
+        Self {
+            component: Arc::new(Mutex::new(component)),
+        }
+    }
+
+    /// Access the state of the component.
+    pub fn with<R>(&self, f: impl FnOnce(&mut C) -> R) -> R {
+        f(&mut self.component.lock())
+    }
+
+    /// View of the component to embed in the parent view.
+    ///
+    /// Messages of the view are handled by the component, and then converted by `map` and
+    /// sent to the parent. Messages that `map` returns `None` for are not sent to the parent.
+    pub fn view<T: Message>(
+        &self,
+        map: impl Fn(C::Message) -> Option<T> + 'static + Sync + Send,
+    ) -> VNode<T> {
+        let vnode = self.component.lock().view();
+        VNode::Nested(NestedVNode::new(ComponentVNode {
+            vnode,
+            component: self.component.clone(),
+            map: Arc::new(map),
+        }))
+    }
+}
+impl<C: Component> Clone for ComponentInstance<C> {
+    fn clone(&self) -> Self {
+        Self {
+            component: self.component.clone(),
+        }
+    }
+}
+impl<C: Component> Debug for ComponentInstance<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ComponentInstance").finish()
+    }
+}
+
+struct ComponentVNode<C: Component, T: Message> {
+    vnode: VNode<C::Message>,
+    component: Arc<Mutex<C>>,
+    map: Arc<dyn Fn(C::Message) -> Option<T> + Sync + Send>,
+}
+impl<C: Component, T: Message> NestedNode<T> for ComponentVNode<C, T> {
+    fn handle_message(self: Box<Self>, sender: MessageSender<T>) -> MessageHandledVNode {
+        let component = self.component;
+        let map = self.map;
+        let parent = sender.clone();
+        let sender = sender.with_handler(move |message: C::Message| {
+            if component.lock().update(message.clone()) == DirtyFlag::ShouldRender {
+                parent.request_render();
+            }
+            if let Some(message) = map(message) {
+                parent.send(message);
+            }
+        });
+        MessageHandledVNode::handle_message(self.vnode, sender)
+    }
+}
@@ -1,5 +1,6 @@
 //! Events sent from the web browser to the jubako server.
 
+use std::collections::BTreeMap;
 use typeshare::typeshare;
 
 use crate::{event_list_macro, vnode::EventHandleId};
@@ -16,6 +17,15 @@ pub struct Element {
     pub scroll_width: u32,
     pub scroll_left: u32,
     pub scroll_top: u32,
+    /// `value` of input, textarea and select elements.
+    pub value: Option<String>,
+    /// `checked` of checkbox and radio inputs.
+    pub checked: Option<bool>,
+    /// Values of the selected options of select elements.
+    pub selected_options: Vec<String>,
+    /// Selection range of text inputs and textareas.
+    pub selection_start: Option<u32>,
+    pub selection_end: Option<u32>,
 }
 
 #[typeshare]
@@ -118,10 +128,88 @@ pub struct ProgressEvent {
 #[typeshare]
 #[derive(serde::Deserialize, PartialEq, Clone, Debug, Default)]
 pub struct SubmitEvent {
+    /// Named fields of the submitted form, including the value of the submitter.
+    pub form_data: FormData,
     pub submitter: Option<Element>,
     pub target: Option<Element>,
 }
 
+#[typeshare]
+#[derive(serde::Deserialize, PartialEq, Clone, Debug, Default)]
+pub struct FormDataEvent {
+    pub form_data: FormData,
+    pub target: Option<Element>,
+}
+
+/// Named fields of a form, in the order of the `FormData` of the web browser.
+///
+/// A name has several values for multi-selects, checkboxes sharing the name and multi-file inputs.
+#[typeshare]
+#[derive(serde::Deserialize, PartialEq, Clone, Debug, Default)]
+pub struct FormData(BTreeMap<String, Vec<FormValue>>);
+impl FormData {
+    /// The first text value of the field.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.get_all(name).iter().find_map(|value| match value {
+            FormValue::Text(text) => Some(text.as_str()),
+            FormValue::File(_) => None,
+        })
+    }
+
+    /// All values of the field, empty if the form has no field of the name.
+    pub fn get_all(&self, name: &str) -> &[FormValue] {
+        self.0.get(name).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Files selected in the file input.
+    pub fn files(&self, name: &str) -> impl Iterator<Item = &FileMetadata> {
+        self.get_all(name).iter().filter_map(|value| match value {
+            FormValue::File(file) => Some(file),
+            FormValue::Text(_) => None,
+        })
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.0.contains_key(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[FormValue])> {
+        self.0
+            .iter()
+            .map(|(name, values)| (name.as_str(), values.as_slice()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Value of a form field.
+#[typeshare]
+#[derive(serde::Deserialize, PartialEq, Clone, Debug)]
+#[serde(tag = "type", content = "content")]
+pub enum FormValue {
+    Text(String),
+    /// File selected in a file input, the content is not sent to the server.
+    File(FileMetadata),
+}
+
+#[typeshare]
+#[derive(serde::Deserialize, PartialEq, Clone, Debug, Default)]
+pub struct FileMetadata {
+    pub name: String,
+    /// Size in bytes.
+    pub size: u64,
+    /// MIME type, empty if the web browser can't guess it.
+    pub mime_type: String,
+    /// Milliseconds since the UNIX epoch.
+    pub last_modified: u64,
+}
+
 #[typeshare]
 #[derive(serde::Deserialize, PartialEq, Clone, Debug, Default)]
 pub struct WheelEvent {
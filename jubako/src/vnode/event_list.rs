@@ -1,5 +1,6 @@
 // VNode Events list handled by jubako
-#[macro_export(crate)]
+#[macro_export]
+#[doc(hidden)]
 macro_rules! event_list_macro {
     ($event_type:ident) => {
         $event_type! {
@@ -36,7 +37,7 @@ macro_rules! event_list_macro {
             focus, Focus => FocusEvent,
             focus_in, FocusIn => FocusEvent,
             focus_out, FocusOut => FocusEvent,
-            form_data, FormData => Event,
+            form_data, FormData => FormDataEvent,
             got_pointer_capture, GotPointerCapture => PointerEvent,
             input, Input => InputEvent,
             invalid, Invalid => Event,
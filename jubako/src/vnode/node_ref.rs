@@ -0,0 +1,146 @@
+//! References to rendered elements, the queries of their geometry and the operations on them.
+
+use std::collections::BTreeMap;
+use typeshare::typeshare;
+use uuid::Uuid;
+
+/// Reference to the element that it is attached to by the `node_ref` of
+/// [`VNode::Element`](crate::vnode::VNode::Element).
+///
+/// The element is queried through the window context, e.g.
+/// [`SimpleWindowContext::bounding_rect`](crate::simple_window::SimpleWindowContext::bounding_rect).
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct NodeRef {
+    id: String,
+}
+impl NodeRef {
+    /// Unique reference.
+    pub fn new() -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+        }
+    }
+
+    pub(crate) fn id(&self) -> &str {
+        &self.id
+    }
+}
+impl Default for NodeRef {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Rectangle of an element relative to the viewport, the result of `getBoundingClientRect`.
+#[typeshare]
+#[derive(serde::Deserialize, PartialEq, Clone, Debug, Default)]
+pub struct ElementRect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    pub top: f64,
+    pub right: f64,
+    pub bottom: f64,
+    pub left: f64,
+}
+
+#[typeshare]
+#[derive(serde::Deserialize, PartialEq, Clone, Debug, Default)]
+pub struct ScrollMetrics {
+    pub scroll_top: f64,
+    pub scroll_left: f64,
+    pub scroll_width: f64,
+    pub scroll_height: f64,
+    pub client_width: f64,
+    pub client_height: f64,
+}
+
+/// Options of `scrollIntoView`, which default to those of the web browser.
+#[typeshare]
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScrollIntoViewOptions {
+    pub behavior: ScrollBehavior,
+    /// Vertical alignment of the element.
+    pub block: ScrollAlignment,
+    /// Horizontal alignment of the element.
+    pub inline: ScrollAlignment,
+}
+impl ScrollIntoViewOptions {
+    pub fn new() -> Self {
+        Self {
+            behavior: ScrollBehavior::Auto,
+            block: ScrollAlignment::Start,
+            inline: ScrollAlignment::Nearest,
+        }
+    }
+
+    pub fn behavior(mut self, behavior: ScrollBehavior) -> Self {
+        self.behavior = behavior;
+        self
+    }
+
+    pub fn block(mut self, block: ScrollAlignment) -> Self {
+        self.block = block;
+        self
+    }
+
+    pub fn inline(mut self, inline: ScrollAlignment) -> Self {
+        self.inline = inline;
+        self
+    }
+}
+impl Default for ScrollIntoViewOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[typeshare]
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ScrollBehavior {
+    #[default]
+    Auto,
+    Smooth,
+    Instant,
+}
+
+#[typeshare]
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ScrollAlignment {
+    Start,
+    Center,
+    End,
+    Nearest,
+}
+
+// what the browser is asked about the referenced element
+#[typeshare]
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(tag = "type", content = "content")]
+pub(crate) enum ElementQuery {
+    BoundingRect,
+    // values of the CSS properties
+    ComputedStyle(Vec<String>),
+    ScrollMetrics,
+}
+
+#[typeshare]
+#[derive(serde::Deserialize, Clone, Debug)]
+#[serde(tag = "type", content = "content")]
+pub(crate) enum ElementQueryResult {
+    BoundingRect(ElementRect),
+    ComputedStyle(BTreeMap<String, String>),
+    ScrollMetrics(ScrollMetrics),
+}
+
+// answer of the browser to `BrowserCommand::QueryElement`,
+// the result is None if the element is not rendered
+#[typeshare]
+#[derive(serde::Deserialize, Clone, Debug)]
+pub(crate) struct ElementQueryResponse {
+    pub(crate) query_id: String,
+    pub(crate) result: Option<ElementQueryResult>,
+}
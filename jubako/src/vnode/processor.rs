@@ -8,7 +8,7 @@ use uuid::Uuid;
 use crate::vnode::{
     command::*,
     event::{VNodeEvent, VNodeEventKind},
-    vnode::{MessageHandledVNode, PortalExpandedVNodes, RenderedVNodes, StyleExtractedVNode},
+    vnode::{MessageHandledVNode, PortalExpandedVNodes, RenderedVNodes, StyleExtractor},
     EventHandleId,
 };
 
@@ -16,6 +16,8 @@ pub(crate) struct Processor {
     current_vnode: RenderedVNodes,
     styles: HashMap<String, Uuid>,
     events: HashMap<EventHandleId, Box<dyn Fn(VNodeEventKind) + Sync + Send>>,
+    // handlers of the tree rendered before `reset`
+    stale_events: HashMap<EventHandleId, Box<dyn Fn(VNodeEventKind) + Sync + Send>>,
 }
 impl Processor {
     pub(crate) fn new() -> Self {
@@ -23,23 +25,46 @@ impl Processor {
             current_vnode: RenderedVNodes::new(),
             styles: HashMap::new(),
             events: HashMap::new(),
+            stale_events: HashMap::new(),
         }
     }
 
+    // forget the rendered tree so that the next commands render the whole tree,
+    // handlers of the forgotten tree keep handling events until `forget_stale_events`
+    pub(crate) fn reset(&mut self) {
+        self.current_vnode = RenderedVNodes::new();
+        self.styles.clear();
+        self.stale_events = std::mem::take(&mut self.events);
+    }
+
+    pub(crate) fn forget_stale_events(&mut self) {
+        self.stale_events.clear();
+    }
+
     // generate next vnode difference commands
     pub(crate) fn next(&mut self, vnode: MessageHandledVNode) -> DifferenceCommands {
-        let (vnode, styles) = StyleExtractedVNode::extract(vnode, &mut self.styles);
+        // lazy vnodes are rendered while diffing, so styles are generated after the diff
+        let mut styles = StyleExtractor::new(&mut self.styles);
+        let vnode = styles.extract(vnode);
         let vnode = PortalExpandedVNodes::expand(vnode);
-        let (main, portals) = self.current_vnode.diff(vnode, &mut self.events);
+        let (main, portals) = self
+            .current_vnode
+            .diff(vnode, &mut self.events, &mut styles);
+        let styles = styles.finish();
         DifferenceCommands {
             main,
             portals,
             styles,
+            ..Default::default()
         }
     }
 
     pub(crate) fn handle_event(&self, evt: VNodeEvent) {
-        if let Some(handler) = self.events.get(&evt.handle_id) {
+        let handler = self
+            .events
+            .get(&evt.handle_id)
+            .or_else(|| self.stale_events.get(&evt.handle_id));
+        if let Some(handler) = handler {
             handler(evt.kind);
         }
     }
@@ -0,0 +1,119 @@
+//! Attributes and DOM properties of elements.
+
+use std::collections::BTreeMap;
+use typeshare::typeshare;
+
+/// Attribute or DOM property of an element.
+#[typeshare]
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", content = "content")]
+pub enum Prop {
+    /// Attribute set with `setAttribute`.
+    Attribute(String),
+    /// Attribute that is present only while it is `true`, e.g. `disabled` or `hidden`.
+    BooleanAttribute(bool),
+    /// DOM property set on the element, e.g. `value`, `checked` or `selectedIndex`.
+    Property(PropertyValue),
+}
+
+/// Value of a DOM property.
+#[typeshare]
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", content = "content")]
+pub enum PropertyValue {
+    String(String),
+    Bool(bool),
+    Number(f64),
+}
+impl From<String> for PropertyValue {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+impl From<&str> for PropertyValue {
+    fn from(value: &str) -> Self {
+        Self::String(value.into())
+    }
+}
+impl From<bool> for PropertyValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+impl From<f64> for PropertyValue {
+    fn from(value: f64) -> Self {
+        Self::Number(value)
+    }
+}
+impl From<i32> for PropertyValue {
+    fn from(value: i32) -> Self {
+        Self::Number(value.into())
+    }
+}
+impl From<u32> for PropertyValue {
+    fn from(value: u32) -> Self {
+        Self::Number(value.into())
+    }
+}
+
+/// Attributes and DOM properties of an element, keyed by name.
+#[typeshare]
+#[derive(serde::Serialize, Clone, Debug, Default, PartialEq)]
+pub struct Props(BTreeMap<String, Prop>);
+impl Props {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the attribute.
+    pub fn attribute(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.insert(name, Prop::Attribute(value.into()));
+        self
+    }
+
+    /// Set the boolean attribute, which is removed while `value` is `false`.
+    pub fn boolean_attribute(mut self, name: impl Into<String>, value: bool) -> Self {
+        self.insert(name, Prop::BooleanAttribute(value));
+        self
+    }
+
+    /// Set the DOM property.
+    pub fn property(mut self, name: impl Into<String>, value: impl Into<PropertyValue>) -> Self {
+        self.insert(name, Prop::Property(value.into()));
+        self
+    }
+
+    /// Set the prop, and return the previous prop of the name.
+    pub fn insert(&mut self, name: impl Into<String>, prop: Prop) -> Option<Prop> {
+        self.0.insert(name.into(), prop)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Prop> {
+        self.0.remove(name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Prop> {
+        self.0.get(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Prop)> {
+        self.0.iter().map(|(name, prop)| (name.as_str(), prop))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+impl<K: Into<String>> FromIterator<(K, Prop)> for Props {
+    fn from_iter<I: IntoIterator<Item = (K, Prop)>>(iter: I) -> Self {
+        Self(
+            iter.into_iter()
+                .map(|(name, prop)| (name.into(), prop))
+                .collect(),
+        )
+    }
+}
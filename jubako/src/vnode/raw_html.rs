@@ -0,0 +1,164 @@
+//! HTML that is rendered as it is, after it is sanitized on the server.
+
+use ammonia::Builder;
+
+/// Sanitized HTML, rendered by [`VNode::RawHtml`](crate::vnode::VNode::RawHtml).
+///
+/// It is created only by [`HtmlSanitizer::sanitize`], so that unsanitized HTML is never rendered.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RawHtml {
+    html: String,
+}
+impl RawHtml {
+    pub fn as_str(&self) -> &str {
+        &self.html
+    }
+
+    pub(crate) fn into_string(self) -> String {
+        self.html
+    }
+}
+
+/// Allowlist of the tags, attributes and URL schemes that are kept in [`RawHtml`].
+///
+/// Everything else is removed, including scripts, event handler attributes and `javascript:` URLs.
+/// The content of `script` and `style` elements is always removed, and they can't be allowed.
+/// Attributes that run scripts, such as `onclick` and `srcdoc`, and the `javascript:`,
+/// `vbscript:` and `data:` URL schemes can't be allowed either, while the `style` attribute
+/// is kept as it is once it is allowed.
+pub struct HtmlSanitizer {
+    builder: Builder<'static>,
+}
+impl HtmlSanitizer {
+    /// Sanitizer that allows the common formatting tags and attributes, such as `p`, `a`, `table`
+    /// and `img`.
+    pub fn new() -> Self {
+        Self {
+            builder: Builder::default(),
+        }
+    }
+
+    /// Sanitizer that allows no tags, attributes and URL schemes.
+    pub fn empty() -> Self {
+        Self {
+            builder: Builder::empty(),
+        }
+    }
+
+    /// Allow the tags.
+    pub fn allow_tags(mut self, tags: &[&'static str]) -> Self {
+        let clean_content_tags = self.builder.clone_clean_content_tags();
+        self.builder.add_tags(
+            tags.iter()
+                .copied()
+                .filter(|tag| !clean_content_tags.contains(tag)),
+        );
+        self
+    }
+
+    /// Remove the tags, keeping their content.
+    pub fn deny_tags(mut self, tags: &[&'static str]) -> Self {
+        self.builder.rm_tags(tags.iter().copied());
+        self
+    }
+
+    /// Allow the attributes on every allowed tag, except the attributes that run scripts.
+    pub fn allow_attributes(mut self, attributes: &[&'static str]) -> Self {
+        self.builder.add_generic_attributes(
+            attributes
+                .iter()
+                .copied()
+                .filter(|attribute| !is_script_attribute(attribute)),
+        );
+        self
+    }
+
+    /// Allow the attributes on the tag, except the attributes that run scripts.
+    pub fn allow_tag_attributes(mut self, tag: &'static str, attributes: &[&'static str]) -> Self {
+        self.builder.add_tag_attributes(
+            tag,
+            attributes
+                .iter()
+                .copied()
+                .filter(|attribute| !is_script_attribute(attribute)),
+        );
+        self
+    }
+
+    /// Allow the URL schemes in the attributes that take URLs, e.g. `href` and `src`,
+    /// except the schemes that run scripts.
+    pub fn allow_url_schemes(mut self, schemes: &[&'static str]) -> Self {
+        self.builder.add_url_schemes(
+            schemes
+                .iter()
+                .copied()
+                .filter(|scheme| !is_script_url_scheme(scheme)),
+        );
+        self
+    }
+
+    pub fn sanitize(&self, html: &str) -> RawHtml {
+        RawHtml {
+            html: self.builder.clean(html).to_string(),
+        }
+    }
+}
+impl Default for HtmlSanitizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// event handler attributes such as `onclick` and `onerror` run scripts,
+// and `srcdoc` runs the scripts in the document of the iframe
+fn is_script_attribute(attribute: &str) -> bool {
+    attribute
+        .get(..2)
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case("on"))
+        || attribute.eq_ignore_ascii_case("srcdoc")
+}
+
+// URLs of these schemes run scripts, `data:` URLs can contain html documents and svg images
+fn is_script_url_scheme(scheme: &str) -> bool {
+    ["javascript", "vbscript", "data"]
+        .iter()
+        .any(|script_scheme| scheme.eq_ignore_ascii_case(script_scheme))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn script_attributes_are_not_allowed() {
+        let sanitizer = HtmlSanitizer::new()
+            .allow_tags(&["iframe"])
+            .allow_attributes(&["ONCLICK", "title"])
+            .allow_tag_attributes("iframe", &["srcdoc", "onload"]);
+        let html = sanitizer.sanitize(
+            r#"<p onclick="alert(1)" title="a">b</p><iframe srcdoc="<script>alert(1)</script>" onload="alert(1)"></iframe>"#,
+        );
+        assert_eq!(html.as_str(), r#"<p title="a">b</p><iframe></iframe>"#);
+    }
+
+    #[test]
+    fn script_url_schemes_are_not_allowed() {
+        let sanitizer =
+            HtmlSanitizer::new().allow_url_schemes(&["JavaScript", "vbscript", "data", "ftp"]);
+        let html = sanitizer.sanitize(concat!(
+            r#"<a href="javascript:alert(1)">a</a>"#,
+            r#"<a href="vbscript:msgbox(1)">b</a>"#,
+            r#"<a href="data:text/html,<script>alert(1)</script>">c</a>"#,
+            r#"<a href="ftp://example.com/">d</a>"#,
+        ));
+        assert_eq!(
+            html.as_str(),
+            concat!(
+                r#"<a rel="noopener noreferrer">a</a>"#,
+                r#"<a rel="noopener noreferrer">b</a>"#,
+                r#"<a rel="noopener noreferrer">c</a>"#,
+                r#"<a href="ftp://example.com/" rel="noopener noreferrer">d</a>"#,
+            )
+        );
+    }
+}
@@ -3,29 +3,176 @@
 //! VNode is processed in the following order in processor:
 //! VNode<T> -> MessageHandledVNode -> StyleExtractedVNode -> PortalExpendedVNodes -> RenderedVNodes
 
+use std::{
+    any::TypeId,
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    sync::Arc,
+    time::Duration,
+};
 use tokio::sync::mpsc::UnboundedSender;
 use typeshare::typeshare;
 use uuid::Uuid;
 
-use crate::vnode::event::{self, VNodeEventKind};
+use crate::vnode::{
+    event::{self, VNodeEventKind},
+    HtmlSanitizer, NodeRef, Props, RawHtml,
+};
 use crate::{event_list_macro, Message};
 
 mod message_handled_vnode;
-pub(crate) use message_handled_vnode::MessageHandledVNode;
+pub(crate) use message_handled_vnode::{LazyRender, MessageHandledVNode};
 mod style_extracted_vnode;
-pub(crate) use style_extracted_vnode::StyleExtractedVNode;
+pub(crate) use style_extracted_vnode::{StyleExtractedVNode, StyleExtractor};
 mod portal_expanded_vnode;
 pub(crate) use portal_expanded_vnode::{PortalExpandedVNode, PortalExpandedVNodes};
 mod rendered_vnode;
 pub(crate) use rendered_vnode::RenderedVNodes;
 
+/// Options of the event listener that the browser adds for an event handler.
+#[typeshare]
+#[derive(serde::Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ListenerOptions {
+    /// Handle the event in the capture phase, before the descendants handle it.
+    pub capture: bool,
+    /// Never prevent the default action, so that the browser does not wait for the listener
+    /// to scroll. Handlers that prevent the default action, such as
+    /// [`VNodeEventHandler::HandlePreventDefault`], do not prevent it either.
+    pub passive: bool,
+    /// Handle only the first event until the handled events of the element change.
+    pub once: bool,
+    /// Stop the event from propagating to the other elements.
+    pub stop_propagation: bool,
+    /// Stop the event from propagating to the other elements and the other listeners
+    /// of the element.
+    pub stop_immediate_propagation: bool,
+    /// How often the browser sends the events to the server.
+    pub rate: EventRate,
+    /// Prevent the default action of the handlers that prevent it, such as
+    /// [`VNodeEventHandler::HandlePreventDefault`], only when the event matches the condition,
+    /// which the browser evaluates.
+    pub prevent_default_if: Option<EventCondition>,
+}
+
+/// Condition on an event that the browser evaluates without asking the server.
+#[typeshare]
+#[derive(serde::Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "type", content = "content")]
+pub enum EventCondition {
+    /// `key` of the keyboard event is one of them, e.g. `"Enter"`.
+    Key(Vec<String>),
+    /// `code` of the keyboard event is one of them, e.g. `"KeyS"`.
+    Code(Vec<String>),
+    /// `button` of the mouse event is one of them.
+    Button(Vec<u8>),
+    CtrlKey,
+    ShiftKey,
+    AltKey,
+    MetaKey,
+    All(Vec<EventCondition>),
+    Any(Vec<EventCondition>),
+    Not(Box<EventCondition>),
+}
+impl EventCondition {
+    pub fn key(keys: &[&str]) -> Self {
+        Self::Key(keys.iter().map(|key| key.to_string()).collect())
+    }
+
+    pub fn code(codes: &[&str]) -> Self {
+        Self::Code(codes.iter().map(|code| code.to_string()).collect())
+    }
+
+    pub fn button(buttons: &[u8]) -> Self {
+        Self::Button(buttons.to_vec())
+    }
+
+    /// Both this and the other condition.
+    pub fn and(self, other: EventCondition) -> Self {
+        match self {
+            Self::All(mut conditions) => {
+                conditions.push(other);
+                Self::All(conditions)
+            }
+            condition => Self::All(vec![condition, other]),
+        }
+    }
+
+    /// Either this or the other condition.
+    pub fn or(self, other: EventCondition) -> Self {
+        match self {
+            Self::Any(mut conditions) => {
+                conditions.push(other);
+                Self::Any(conditions)
+            }
+            condition => Self::Any(vec![condition, other]),
+        }
+    }
+}
+impl std::ops::Not for EventCondition {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self::Not(Box::new(self))
+    }
+}
+
+/// Rate at which the browser sends the events of a handler to the server.
+///
+/// The browser drops or merges the events that exceed the rate, so that high-frequency events
+/// such as `mouse_move`, `pointer_move`, `scroll` and `wheel` don't flood the server.
+#[typeshare]
+#[derive(serde::Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(tag = "type", content = "content")]
+pub enum EventRate {
+    /// Send every event.
+    #[default]
+    Immediate,
+    /// Send the first event and then at most one event every given milliseconds,
+    /// the latest one.
+    Throttle(u32),
+    /// Send the latest event after no event for the given milliseconds.
+    Debounce(u32),
+    /// Send the latest event once per animation frame.
+    Coalesce,
+    /// Send one event per animation frame, the latest one with the deltas
+    /// (`delta_x`, `delta_y`, `delta_z`, `movement_x` and `movement_y`) of all the events
+    /// summed up.
+    Accumulate,
+}
+impl EventRate {
+    pub fn throttle(interval: Duration) -> Self {
+        Self::Throttle(millis(interval))
+    }
+
+    pub fn debounce(delay: Duration) -> Self {
+        Self::Debounce(millis(delay))
+    }
+}
+fn millis(duration: Duration) -> u32 {
+    duration.as_millis().try_into().unwrap_or(u32::MAX)
+}
+
 #[derive(Default)]
 pub enum VNodeEventHandler<Event, T: Message> {
     Handle {
         handler: Box<dyn Fn(Event) -> T + Sync + Send>,
+        options: ListenerOptions,
     },
     HandlePreventDefault {
         handler: Box<dyn Fn(Event) -> T + Sync + Send>,
+        options: ListenerOptions,
+    },
+    /// Handler that sends no message when it returns `None`.
+    HandleOption {
+        handler: Box<dyn Fn(Event) -> Option<T> + Sync + Send>,
+        prevent_default: bool,
+        options: ListenerOptions,
+    },
+    /// Handler that sends all the returned messages in order.
+    HandleVec {
+        handler: Box<dyn Fn(Event) -> Vec<T> + Sync + Send>,
+        prevent_default: bool,
+        options: ListenerOptions,
     },
     #[default]
     None,
@@ -37,6 +184,29 @@ impl<Event, T: Message> VNodeEventHandler<Event, T> {
     {
         Self::Handle {
             handler: Box::new(handler),
+            options: ListenerOptions::default(),
+        }
+    }
+    /// Handle the event with a handler that sends no message when it returns `None`.
+    pub fn handle_option<F>(handler: F) -> Self
+    where
+        F: Fn(Event) -> Option<T> + 'static + Sync + Send,
+    {
+        Self::HandleOption {
+            handler: Box::new(handler),
+            prevent_default: false,
+            options: ListenerOptions::default(),
+        }
+    }
+    /// Handle the event with a handler that sends all the returned messages in order.
+    pub fn handle_vec<F>(handler: F) -> Self
+    where
+        F: Fn(Event) -> Vec<T> + 'static + Sync + Send,
+    {
+        Self::HandleVec {
+            handler: Box::new(handler),
+            prevent_default: false,
+            options: ListenerOptions::default(),
         }
     }
     pub fn handle_prevent_default<F>(handler: F) -> Self
@@ -45,6 +215,165 @@ impl<Event, T: Message> VNodeEventHandler<Event, T> {
     {
         Self::HandlePreventDefault {
             handler: Box::new(handler),
+            options: ListenerOptions::default(),
+        }
+    }
+
+    /// Prevent the default action of the event.
+    pub fn prevent_default(self) -> Self {
+        match self {
+            Self::Handle { handler, options } => Self::HandlePreventDefault { handler, options },
+            Self::HandleOption {
+                handler, options, ..
+            } => Self::HandleOption {
+                handler,
+                prevent_default: true,
+                options,
+            },
+            Self::HandleVec {
+                handler, options, ..
+            } => Self::HandleVec {
+                handler,
+                prevent_default: true,
+                options,
+            },
+            handler => handler,
+        }
+    }
+
+    /// Prevent the default action of the event only when it matches `condition`,
+    /// e.g. only for the keys of the shortcuts so that typing is not blocked.
+    pub fn prevent_default_if(mut self, condition: EventCondition) -> Self {
+        if let Some(options) = self.options_mut() {
+            options.prevent_default_if = Some(condition);
+        }
+        self.prevent_default()
+    }
+
+    /// Replace the listener options.
+    pub fn options(mut self, options: ListenerOptions) -> Self {
+        if let Some(current) = self.options_mut() {
+            *current = options;
+        }
+        self
+    }
+
+    /// Handle the event in the capture phase.
+    pub fn capture(mut self) -> Self {
+        if let Some(options) = self.options_mut() {
+            options.capture = true;
+        }
+        self
+    }
+
+    /// Add the listener as passive.
+    pub fn passive(mut self) -> Self {
+        if let Some(options) = self.options_mut() {
+            options.passive = true;
+        }
+        self
+    }
+
+    /// Handle only the first event.
+    pub fn once(mut self) -> Self {
+        if let Some(options) = self.options_mut() {
+            options.once = true;
+        }
+        self
+    }
+
+    /// Stop the propagation of the handled event.
+    pub fn stop_propagation(mut self) -> Self {
+        if let Some(options) = self.options_mut() {
+            options.stop_propagation = true;
+        }
+        self
+    }
+
+    /// Stop the propagation of the handled event, including to the other listeners
+    /// of the element.
+    pub fn stop_immediate_propagation(mut self) -> Self {
+        if let Some(options) = self.options_mut() {
+            options.stop_immediate_propagation = true;
+        }
+        self
+    }
+
+    /// Set the rate at which the browser sends the events.
+    pub fn rate(mut self, rate: EventRate) -> Self {
+        if let Some(options) = self.options_mut() {
+            options.rate = rate;
+        }
+        self
+    }
+
+    /// Send at most one event every `interval`.
+    pub fn throttle(self, interval: Duration) -> Self {
+        self.rate(EventRate::throttle(interval))
+    }
+
+    /// Send the latest event when no event has happened for `delay`.
+    pub fn debounce(self, delay: Duration) -> Self {
+        self.rate(EventRate::debounce(delay))
+    }
+
+    /// Send the latest event once per animation frame.
+    pub fn coalesce(self) -> Self {
+        self.rate(EventRate::Coalesce)
+    }
+
+    /// Send one event per animation frame with the deltas of the events summed up.
+    pub fn accumulate(self) -> Self {
+        self.rate(EventRate::Accumulate)
+    }
+
+    fn options_mut(&mut self) -> Option<&mut ListenerOptions> {
+        match self {
+            Self::Handle { options, .. }
+            | Self::HandlePreventDefault { options, .. }
+            | Self::HandleOption { options, .. }
+            | Self::HandleVec { options, .. } => Some(options),
+            Self::None => None,
+        }
+    }
+
+    // options of the listener and whether it prevents the default action,
+    // None if the event is not handled
+    fn listener(&self) -> Option<(&ListenerOptions, bool)> {
+        match self {
+            Self::Handle { options, .. } => Some((options, false)),
+            Self::HandlePreventDefault { options, .. } => Some((options, true)),
+            Self::HandleOption {
+                options,
+                prevent_default,
+                ..
+            }
+            | Self::HandleVec {
+                options,
+                prevent_default,
+                ..
+            } => Some((options, *prevent_default)),
+            Self::None => None,
+        }
+    }
+
+    // send the messages that the handler creates from the event
+    fn dispatch(&self, event: Event, sender: &MessageSender<T>) {
+        match self {
+            Self::Handle { handler, .. } | Self::HandlePreventDefault { handler, .. } => {
+                sender.send(handler(event))
+            }
+            Self::HandleOption { handler, .. } => {
+                if let Some(message) = handler(event) {
+                    sender.send(message)
+                }
+            }
+            Self::HandleVec { handler, .. } => {
+                for message in handler(event) {
+                    sender.send(message)
+                }
+            }
+            Self::None => {}
         }
     }
 }
@@ -74,6 +403,7 @@ macro_rules! impl_default_vnode_event_handlers {
 }
 event_list_macro!(impl_default_vnode_event_handlers);
 
+#[allow(clippy::large_enum_variant)]
 pub enum VNode<T: Message> {
     Text {
         text: String,
@@ -81,14 +411,244 @@ pub enum VNode<T: Message> {
     Element {
         tag: String,
         class: Vec<String>,
-        props: Vec<String>,
+        props: Props,
         event: VNodeEventHandlers<T>,
         children: Vec<VNode<T>>,
         style: Option<String>,
+        /// Identifies the element among its siblings, so that reordered elements are moved
+        /// instead of being rebuilt.
+        key: Option<String>,
+        /// Namespace of the element, `None` to infer it from the tag and the parent element.
+        ///
+        /// `svg` and `math` elements start the SVG and MathML namespaces,
+        /// and the children of `foreignObject` are back in the HTML namespace.
+        namespace: Option<Namespace>,
+        /// Reference to query the rendered element, e.g. for its bounding rect.
+        ///
+        /// The element gets the `data-jubako-ref` attribute to be found by the browser.
+        node_ref: Option<NodeRef>,
     },
     Portal {
         children: Vec<VNode<T>>,
     },
+    /// Nodes that are rendered in place of the fragment as siblings, e.g. to return several nodes
+    /// from a helper function.
+    Fragment {
+        children: Vec<VNode<T>>,
+    },
+    /// Sanitized HTML that is set to `innerHTML` of a `display: contents` div,
+    /// and is replaced as a whole when it changes.
+    RawHtml(RawHtml),
+    /// Subtree whose messages are converted or handled by a component,
+    /// created by [`VNode::map`] or [`ComponentInstance::view`](crate::vnode::ComponentInstance::view).
+    Nested(NestedVNode<T>),
+    /// Subtree that is created only when its dependency changes, created by [`VNode::lazy`].
+    Lazy(LazyVNode<T>),
+}
+impl<T: Message> VNode<T> {
+    /// HTML sanitized by the default allowlist of [`HtmlSanitizer`].
+    pub fn raw_html(html: &str) -> Self {
+        VNode::RawHtml(HtmlSanitizer::new().sanitize(html))
+    }
+
+    /// Subtree that is created by `view` only when `dependency` differs from the previous render
+    /// at the same position.
+    ///
+    /// While the dependency is unchanged, `view` is not called and the previously rendered subtree
+    /// and its event handlers are reused. Portals in the subtree are rendered on every render.
+    ///
+    /// If `view` returns a fragment of several nodes, they are wrapped in a `div` with
+    /// `display: contents` so that the subtree is rendered as a single node.
+    pub fn lazy<D, F>(dependency: D, view: F) -> Self
+    where
+        D: Hash,
+        F: FnOnce() -> VNode<T> + 'static + Sync + Send,
+    {
+        VNode::Lazy(LazyVNode::new(None, dependency, view))
+    }
+
+    /// [`VNode::lazy`] with a key that identifies the subtree among its siblings.
+    pub fn lazy_with_key<D, F>(key: impl Into<String>, dependency: D, view: F) -> Self
+    where
+        D: Hash,
+        F: FnOnce() -> VNode<T> + 'static + Sync + Send,
+    {
+        VNode::Lazy(LazyVNode::new(Some(key.into()), dependency, view))
+    }
+
+    /// Convert the messages of the subtree, e.g. to embed a view of another message type.
+    pub fn map<U: Message>(self, f: impl Fn(T) -> U + 'static + Sync + Send) -> VNode<U> {
+        VNode::Nested(NestedVNode::new(MappedVNode {
+            vnode: self,
+            f: Arc::new(f),
+        }))
+    }
+}
+
+/// Namespace of an element, which decides how the web browser creates the element.
+#[typeshare]
+#[derive(serde::Serialize, Eq, PartialEq, Clone, Copy, Debug, Default)]
+pub enum Namespace {
+    #[default]
+    Html,
+    Svg,
+    MathMl,
+}
+impl Namespace {
+    // namespace of the element that has no explicit namespace
+    pub(crate) fn infer(tag: &str, parent: Namespace) -> Namespace {
+        match tag {
+            "svg" => Namespace::Svg,
+            "math" => Namespace::MathMl,
+            _ => parent,
+        }
+    }
+
+    // namespace of the children of the element
+    pub(crate) fn of_children(self, tag: &str) -> Namespace {
+        match (self, tag) {
+            (Namespace::Svg, "foreignObject") => Namespace::Html,
+            _ => self,
+        }
+    }
+}
+
+// subtree that sends the messages to the parent tree in its own way
+pub(crate) trait NestedNode<T: Message>: 'static + Sync + Send {
+    fn handle_message(self: Box<Self>, sender: MessageSender<T>) -> MessageHandledVNode;
+}
+
+/// Subtree of [`VNode::Nested`].
+pub struct NestedVNode<T: Message> {
+    node: Box<dyn NestedNode<T>>,
+}
+impl<T: Message> NestedVNode<T> {
+    pub(crate) fn new(node: impl NestedNode<T>) -> Self {
+        Self {
+            node: Box::new(node),
+        }
+    }
+
+    pub(crate) fn handle_message(self, sender: MessageSender<T>) -> MessageHandledVNode {
+        self.node.handle_message(sender)
+    }
+}
+
+/// Subtree of [`VNode::Lazy`].
+pub struct LazyVNode<T: Message> {
+    key: Option<String>,
+    dependency: u64,
+    view: Box<dyn FnOnce() -> VNode<T> + Sync + Send>,
+}
+impl<T: Message> LazyVNode<T> {
+    fn new<D, F>(key: Option<String>, dependency: D, view: F) -> Self
+    where
+        D: Hash,
+        F: FnOnce() -> VNode<T> + 'static + Sync + Send,
+    {
+        // the type of the view is hashed with the dependency, so that different views are not mixed up
+        let mut hasher = DefaultHasher::new();
+        TypeId::of::<F>().hash(&mut hasher);
+        dependency.hash(&mut hasher);
+        Self {
+            key,
+            dependency: hasher.finish(),
+            view: Box::new(view),
+        }
+    }
+
+    pub(crate) fn handle_message(self, sender: MessageSender<T>) -> MessageHandledVNode {
+        let view = self.view;
+        MessageHandledVNode::Lazy {
+            key: self.key,
+            dependency: self.dependency,
+            render: Box::new(move || MessageHandledVNode::handle_message(view(), sender)),
+        }
+    }
+}
+
+struct MappedVNode<S: Message, T: Message> {
+    vnode: VNode<S>,
+    f: Arc<dyn Fn(S) -> T + Sync + Send>,
+}
+impl<S: Message, T: Message> NestedNode<T> for MappedVNode<S, T> {
+    fn handle_message(self: Box<Self>, sender: MessageSender<T>) -> MessageHandledVNode {
+        let f = self.f;
+        MessageHandledVNode::handle_message(self.vnode, sender.map(move |message| Some(f(message))))
+    }
+}
+
+// destination of the messages that event handlers of a tree create
+pub(crate) struct MessageSender<T: Message> {
+    send: Arc<dyn Fn(T) + Sync + Send>,
+    request_render: Arc<dyn Fn() + Sync + Send>,
+}
+impl<T: Message> MessageSender<T> {
+    pub(crate) fn new(
+        sender: UnboundedSender<T>,
+        request_render: impl Fn() + 'static + Sync + Send,
+    ) -> Self {
+        Self {
+            send: Arc::new(move |message| {
+                let _ = sender.send(message);
+            }),
+            request_render: Arc::new(request_render),
+        }
+    }
+
+    // sender that handles the messages with `send`
+    pub(crate) fn from_fn(
+        send: impl Fn(T) + 'static + Sync + Send,
+        request_render: impl Fn() + 'static + Sync + Send,
+    ) -> Self {
+        Self {
+            send: Arc::new(send),
+            request_render: Arc::new(request_render),
+        }
+    }
+
+    pub(crate) fn send(&self, message: T) {
+        (self.send)(message)
+    }
+
+    pub(crate) fn request_render(&self) {
+        (self.request_render)()
+    }
+
+    // sender that converts the messages and sends them to this sender, `None` is not sent
+    pub(crate) fn map<S: Message>(
+        &self,
+        f: impl Fn(S) -> Option<T> + 'static + Sync + Send,
+    ) -> MessageSender<S> {
+        let send = self.send.clone();
+        MessageSender {
+            send: Arc::new(move |message| {
+                if let Some(message) = f(message) {
+                    send(message)
+                }
+            }),
+            request_render: self.request_render.clone(),
+        }
+    }
+
+    // sender that handles the messages with `handle` instead of this sender
+    pub(crate) fn with_handler<S: Message>(
+        &self,
+        handle: impl Fn(S) + 'static + Sync + Send,
+    ) -> MessageSender<S> {
+        MessageSender {
+            send: Arc::new(handle),
+            request_render: self.request_render.clone(),
+        }
+    }
+}
+impl<T: Message> Clone for MessageSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            send: self.send.clone(),
+            request_render: self.request_render.clone(),
+        }
+    }
 }
 
 // Event handle ID that is used to identify event handlers.
@@ -104,7 +664,7 @@ impl EventHandleId {
 macro_rules! define_vnode_event_type {
     ( $( $event_name_snake:ident, $event_name_pascal:ident => $event_type:ident, )* ) => {
         #[typeshare]
-        #[derive(serde::Serialize, Eq, PartialEq, Clone, Copy, Debug)]
+        #[derive(serde::Serialize, Eq, PartialEq, Clone, Copy, Debug, Hash)]
         pub(crate) enum VNodeEventType {
             $(
                 $event_name_pascal,
@@ -118,21 +678,39 @@ pub(crate) struct BundledEventHandler {
     pub(crate) handler: Box<dyn Fn(event::VNodeEventKind) + Sync + Send>,
     pub(crate) handle_events: Vec<VNodeEventType>,
     pub(crate) handle_prevent_default_events: Vec<VNodeEventType>,
+    // options of the handled events that are not the default
+    pub(crate) listener_options: HashMap<VNodeEventType, ListenerOptions>,
 }
 impl BundledEventHandler {
+    // handler of an element that handles no events
+    pub(crate) fn none() -> BundledEventHandler {
+        BundledEventHandler {
+            handler: Box::new(|_| ()),
+            handle_events: vec![],
+            handle_prevent_default_events: vec![],
+            listener_options: HashMap::new(),
+        }
+    }
+
     pub(crate) fn bundle<T: Message>(
         handlers: VNodeEventHandlers<T>,
-        sender: UnboundedSender<T>,
+        sender: MessageSender<T>,
     ) -> BundledEventHandler {
         let mut handle_events = vec![];
         let mut handle_prevent_default_events = vec![];
+        let mut listener_options = HashMap::new();
 
         macro_rules! handle_event {
             ($handler:ident, $ty:ident) => {
-                if let VNodeEventHandler::Handle { .. } = &handlers.$handler {
-                    handle_events.push(VNodeEventType::$ty);
-                } else if let VNodeEventHandler::HandlePreventDefault { .. } = &handlers.$handler {
-                    handle_prevent_default_events.push(VNodeEventType::$ty);
+                if let Some((options, prevent_default)) = handlers.$handler.listener() {
+                    if prevent_default {
+                        handle_prevent_default_events.push(VNodeEventType::$ty);
+                    } else {
+                        handle_events.push(VNodeEventType::$ty);
+                    }
+                    if *options != ListenerOptions::default() {
+                        listener_options.insert(VNodeEventType::$ty, options.clone());
+                    }
                 }
             };
         }
@@ -150,11 +728,7 @@ impl BundledEventHandler {
                 Box::new(move |event: VNodeEventKind| match event {
                     $(
                         VNodeEventKind::$event_name_pascal(evt) => {
-                            if let VNodeEventHandler::Handle { handler } = &handlers.$event_name_snake {
-                                sender.send(handler(evt)).unwrap();
-                            } else if let VNodeEventHandler::HandlePreventDefault { handler } = &handlers.$event_name_snake {
-                                sender.send(handler(evt)).unwrap();
-                            }
+                            handlers.$event_name_snake.dispatch(evt, &sender);
                         }
                     )*
                 }) as Box<dyn Fn(VNodeEventKind) + Sync + Send>
@@ -165,6 +739,7 @@ impl BundledEventHandler {
         BundledEventHandler {
             handle_events,
             handle_prevent_default_events,
+            listener_options,
             handler,
         }
     }
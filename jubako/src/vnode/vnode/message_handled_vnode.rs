@@ -1,9 +1,11 @@
-use tokio::sync::mpsc::UnboundedSender;
-
-use crate::vnode::{BundledEventHandler, VNode};
+use crate::vnode::{BundledEventHandler, MessageSender, Namespace, Prop, Props, VNode};
 use crate::Message;
 
+// attribute that identifies the element of a node ref in the browser
+const REF_ATTRIBUTE: &str = "data-jubako-ref";
+
 // MessageHandledVNode is a VNode that message is sent to given sender when event is fired
+#[allow(clippy::large_enum_variant)]
 pub(crate) enum MessageHandledVNode {
     Text {
         text: String,
@@ -11,44 +13,82 @@ pub(crate) enum MessageHandledVNode {
     Element {
         tag: String,
         class: Vec<String>,
-        props: Vec<String>,
+        props: Props,
         event: BundledEventHandler,
         children: Vec<MessageHandledVNode>,
         style: Option<String>,
+        key: Option<String>,
+        namespace: Option<Namespace>,
     },
     Portal {
         children: Vec<MessageHandledVNode>,
     },
+    Fragment {
+        children: Vec<MessageHandledVNode>,
+    },
+    RawHtml {
+        html: String,
+    },
+    Lazy {
+        key: Option<String>,
+        dependency: u64,
+        render: LazyRender,
+    },
 }
+
+// creates the subtree of a lazy vnode, called only when the subtree has to be rendered
+pub(crate) type LazyRender = Box<dyn FnOnce() -> MessageHandledVNode + Sync + Send>;
+
 impl MessageHandledVNode {
     // send message to given sender
-    pub(crate) fn handle_message<T: Message>(vnode: VNode<T>, sender: UnboundedSender<T>) -> Self {
+    pub(crate) fn handle_message<T: Message>(vnode: VNode<T>, sender: MessageSender<T>) -> Self {
         match vnode {
             VNode::Text { text } => Self::Text { text },
             VNode::Element {
                 tag,
                 class,
-                props,
+                mut props,
                 event,
                 children,
                 style,
-            } => Self::Element {
-                tag,
-                class,
-                props,
-                event: BundledEventHandler::bundle(event, sender.clone()),
+                key,
+                namespace,
+                node_ref,
+            } => {
+                if let Some(node_ref) = node_ref {
+                    props.insert(REF_ATTRIBUTE, Prop::Attribute(node_ref.id().into()));
+                }
+                Self::Element {
+                    tag,
+                    class,
+                    props,
+                    event: BundledEventHandler::bundle(event, sender.clone()),
+                    children: children
+                        .into_iter()
+                        .map(|v| MessageHandledVNode::handle_message(v, sender.clone()))
+                        .collect(),
+                    style,
+                    key,
+                    namespace,
+                }
+            }
+            VNode::Portal { children } => Self::Portal {
                 children: children
                     .into_iter()
                     .map(|v| MessageHandledVNode::handle_message(v, sender.clone()))
                     .collect(),
-                style,
             },
-            VNode::Portal { children } => Self::Portal {
+            VNode::Fragment { children } => Self::Fragment {
                 children: children
                     .into_iter()
                     .map(|v| MessageHandledVNode::handle_message(v, sender.clone()))
                     .collect(),
             },
+            VNode::RawHtml(html) => Self::RawHtml {
+                html: html.into_string(),
+            },
+            VNode::Nested(nested) => nested.handle_message(sender),
+            VNode::Lazy(lazy) => lazy.handle_message(sender),
         }
     }
 }
@@ -1,114 +1,210 @@
-use crate::vnode::vnode::{BundledEventHandler, StyleExtractedVNode};
+use crate::vnode::{
+    vnode::{BundledEventHandler, LazyRender, StyleExtractedVNode},
+    Namespace, Props,
+};
 
 pub(crate) enum PortalExpandedVNode {
     Text {
         text: String,
     },
+    RawHtml {
+        html: String,
+    },
     Element {
         tag: String,
         class: Vec<String>,
-        props: Vec<String>,
+        props: Props,
         event: BundledEventHandler,
         children: Vec<PortalExpandedVNode>,
+        key: Option<String>,
+        namespace: Namespace,
+    },
+    Lazy {
+        key: Option<String>,
+        dependency: u64,
+        render: LazyRender,
+        // namespace of the parent element, which the rendered subtree inherits
+        namespace: Namespace,
     },
 }
+impl PortalExpandedVNode {
+    pub(crate) fn key(&self) -> Option<&str> {
+        match self {
+            PortalExpandedVNode::Element { key, .. } | PortalExpandedVNode::Lazy { key, .. } => {
+                key.as_deref()
+            }
+            PortalExpandedVNode::Text { .. } | PortalExpandedVNode::RawHtml { .. } => None,
+        }
+    }
+}
 
-// vnode that expanded portals
+// vnode that expanded portals and fragments
 pub(crate) struct PortalExpandedVNodes {
     pub(crate) main: Vec<PortalExpandedVNode>,
     pub(crate) portals: Vec<PortalExpandedVNode>,
 }
 impl PortalExpandedVNodes {
-    pub(crate) fn expand(mut vnode: StyleExtractedVNode) -> Self {
+    // the root can be any vnode, a root fragment is rendered as several root nodes
+    pub(crate) fn expand(vnode: StyleExtractedVNode) -> Self {
+        let mut main = vec![vnode];
         let mut portals: Vec<StyleExtractedVNode> = vec![];
 
-        fn traverse_main(
-            main: &mut Vec<StyleExtractedVNode>,
-            portals: &mut Vec<StyleExtractedVNode>,
-        ) {
-            let mut i = 0;
-            while i < main.len() {
-                match &mut main[i] {
-                    StyleExtractedVNode::Portal { .. } => {
-                        let vnode = main.remove(i);
-                        if let StyleExtractedVNode::Portal { mut children, .. } = vnode {
-                            portals.append(&mut children);
-                        }
-                    }
-                    StyleExtractedVNode::Element { children, .. } => {
-                        traverse_main(children, portals);
-                        i += 1;
-                    }
-                    _ => {
-                        i += 1;
-                    }
-                }
-            }
+        traverse_main(&mut main, &mut portals);
+        traverse_portals(&mut portals);
+
+        PortalExpandedVNodes {
+            main: convert(main, Namespace::Html),
+            portals: convert(portals, Namespace::Html),
         }
-        fn traverse_portals(portals: &mut Vec<StyleExtractedVNode>) {
-            let mut i = 0;
-            while i < portals.len() {
-                let mut new_portals = vec![];
+    }
 
-                match &mut portals[i] {
-                    StyleExtractedVNode::Portal { .. } => {
-                        let vnode = portals.remove(i);
-                        if let StyleExtractedVNode::Portal { mut children, .. } = vnode {
-                            new_portals.append(&mut children);
-                        }
-                    }
-                    StyleExtractedVNode::Element { children, .. } => {
-                        traverse_main(children, &mut new_portals);
-                        i += 1;
-                    }
-                    _ => {
-                        i += 1;
+    // expand portals and fragments of the rendered subtree of a lazy vnode,
+    // the subtree is replaced with an empty text if it has no node in place,
+    // and wrapped in a `display: contents` div (or a group in SVG and MathML) if it has several nodes
+    pub(crate) fn expand_lazy(
+        vnode: StyleExtractedVNode,
+        namespace: Namespace,
+    ) -> (PortalExpandedVNode, Vec<PortalExpandedVNode>) {
+        let mut main = vec![vnode];
+        let mut portals: Vec<StyleExtractedVNode> = vec![];
+
+        traverse_main(&mut main, &mut portals);
+        traverse_portals(&mut portals);
+
+        let mut main = convert(main, namespace);
+        let vnode = match main.len() {
+            0 => PortalExpandedVNode::Text {
+                text: String::new(),
+            },
+            1 => main.pop().unwrap(),
+            _ => {
+                let (tag, props) = match namespace {
+                    Namespace::Html => {
+                        ("div", Props::new().attribute("style", "display: contents"))
                     }
+                    Namespace::Svg => ("g", Props::new()),
+                    Namespace::MathMl => ("mrow", Props::new()),
+                };
+                PortalExpandedVNode::Element {
+                    tag: tag.into(),
+                    class: vec![],
+                    props,
+                    event: BundledEventHandler::none(),
+                    children: main,
+                    key: None,
+                    namespace,
                 }
+            }
+        };
+        (vnode, convert(portals, Namespace::Html))
+    }
+}
 
-                portals.append(&mut new_portals);
+fn traverse_main(main: &mut Vec<StyleExtractedVNode>, portals: &mut Vec<StyleExtractedVNode>) {
+    let mut i = 0;
+    while i < main.len() {
+        match &mut main[i] {
+            StyleExtractedVNode::Portal { .. } => {
+                let vnode = main.remove(i);
+                if let StyleExtractedVNode::Portal { mut children, .. } = vnode {
+                    portals.append(&mut children);
+                }
+            }
+            StyleExtractedVNode::Fragment { .. } => {
+                // children of the fragment are placed in place of the fragment and traversed next
+                let vnode = main.remove(i);
+                if let StyleExtractedVNode::Fragment { children } = vnode {
+                    main.splice(i..i, children);
+                }
+            }
+            StyleExtractedVNode::Element { children, .. } => {
+                traverse_main(children, portals);
+                i += 1;
+            }
+            _ => {
+                i += 1;
             }
         }
+    }
+}
 
-        if let StyleExtractedVNode::Element {
-            ref mut children, ..
-        } = vnode
-        {
-            traverse_main(children, &mut portals);
-        } else {
-            unreachable!();
-        }
-        traverse_portals(&mut portals);
+fn traverse_portals(portals: &mut Vec<StyleExtractedVNode>) {
+    let mut i = 0;
+    while i < portals.len() {
+        let mut new_portals = vec![];
 
-        fn convert(vnodes: Vec<StyleExtractedVNode>) -> Vec<PortalExpandedVNode> {
-            let mut ret_vnodes = vec![];
-            for vnode in vnodes {
-                match vnode {
-                    StyleExtractedVNode::Text { text } => {
-                        ret_vnodes.push(PortalExpandedVNode::Text { text })
-                    }
-                    StyleExtractedVNode::Element {
-                        tag,
-                        class,
-                        props,
-                        event,
-                        children,
-                    } => ret_vnodes.push(PortalExpandedVNode::Element {
-                        tag,
-                        class,
-                        props,
-                        event,
-                        children: convert(children),
-                    }),
-                    StyleExtractedVNode::Portal { .. } => unreachable!(),
+        match &mut portals[i] {
+            StyleExtractedVNode::Portal { .. } => {
+                let vnode = portals.remove(i);
+                if let StyleExtractedVNode::Portal { mut children, .. } = vnode {
+                    new_portals.append(&mut children);
                 }
             }
-            ret_vnodes
+            StyleExtractedVNode::Fragment { .. } => {
+                let vnode = portals.remove(i);
+                if let StyleExtractedVNode::Fragment { children } = vnode {
+                    portals.splice(i..i, children);
+                }
+            }
+            StyleExtractedVNode::Element { children, .. } => {
+                traverse_main(children, &mut new_portals);
+                i += 1;
+            }
+            _ => {
+                i += 1;
+            }
         }
 
-        PortalExpandedVNodes {
-            main: convert(vec![vnode]),
-            portals: convert(portals),
+        portals.append(&mut new_portals);
+    }
+}
+
+// lazy vnodes are kept to be rendered in diff, portals do not have lazy vnodes.
+// namespaces of the elements are resolved from the namespace of the parent
+fn convert(vnodes: Vec<StyleExtractedVNode>, parent: Namespace) -> Vec<PortalExpandedVNode> {
+    let mut ret_vnodes = vec![];
+    for vnode in vnodes {
+        match vnode {
+            StyleExtractedVNode::Text { text } => {
+                ret_vnodes.push(PortalExpandedVNode::Text { text })
+            }
+            StyleExtractedVNode::RawHtml { html } => {
+                ret_vnodes.push(PortalExpandedVNode::RawHtml { html })
+            }
+            StyleExtractedVNode::Element {
+                tag,
+                class,
+                props,
+                event,
+                children,
+                key,
+                namespace,
+            } => {
+                let namespace = namespace.unwrap_or_else(|| Namespace::infer(&tag, parent));
+                ret_vnodes.push(PortalExpandedVNode::Element {
+                    children: convert(children, namespace.of_children(&tag)),
+                    tag,
+                    class,
+                    props,
+                    event,
+                    key,
+                    namespace,
+                })
+            }
+            StyleExtractedVNode::Lazy {
+                key,
+                dependency,
+                render,
+            } => ret_vnodes.push(PortalExpandedVNode::Lazy {
+                key,
+                dependency,
+                render,
+                namespace: parent,
+            }),
+            StyleExtractedVNode::Portal { .. } | StyleExtractedVNode::Fragment { .. } => {
+                unreachable!()
+            }
         }
     }
+    ret_vnodes
 }
@@ -1,17 +1,48 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 
 use crate::vnode::{
     command::*,
     event::VNodeEventKind,
-    vnode::{PortalExpandedVNode, PortalExpandedVNodes},
-    EventHandleId, VNodeEventType,
+    vnode::{
+        BundledEventHandler, LazyRender, PortalExpandedVNode, PortalExpandedVNodes, StyleExtractor,
+    },
+    EventHandleId, ListenerOptions, Namespace, Props, VNodeEventType,
 };
 
+type EventHandlers = HashMap<EventHandleId, Box<dyn Fn(VNodeEventKind) + Sync + Send>>;
+
 #[derive(Debug)]
 pub(crate) struct HandledEvents {
     id: EventHandleId,
     handle_events: Vec<VNodeEventType>,
     handle_prevent_default_events: Vec<VNodeEventType>,
+    listener_options: HashMap<VNodeEventType, ListenerOptions>,
+}
+impl HandledEvents {
+    fn new(id: EventHandleId, event: &BundledEventHandler) -> Self {
+        Self {
+            id,
+            handle_events: event.handle_events.clone(),
+            handle_prevent_default_events: event.handle_prevent_default_events.clone(),
+            listener_options: event.listener_options.clone(),
+        }
+    }
+
+    // the browser has to listen to the events again if the events or their options are different
+    fn is_different(&self, event: &BundledEventHandler) -> bool {
+        self.handle_events != event.handle_events
+            || self.handle_prevent_default_events != event.handle_prevent_default_events
+            || self.listener_options != event.listener_options
+    }
+
+    fn to_command(&self) -> HandleEvent {
+        HandleEvent {
+            handle_id: self.id.clone(),
+            handle_events: self.handle_events.clone(),
+            handle_prevent_default_events: self.handle_prevent_default_events.clone(),
+            listener_options: self.listener_options.clone(),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -19,12 +50,26 @@ pub(crate) enum RenderedVNode {
     Text {
         text: String,
     },
+    RawHtml {
+        html: String,
+    },
     Element {
         tag: String,
         class: Vec<String>,
-        props: Vec<String>,
+        props: Props,
         event: HandledEvents,
         children: Vec<RenderedVNode>,
+        key: Option<String>,
+        namespace: Namespace,
+    },
+    // subtree rendered by a lazy vnode
+    Lazy {
+        key: Option<String>,
+        dependency: u64,
+        // styles used in the subtree
+        styles: Vec<String>,
+        has_portals: bool,
+        vnode: Box<RenderedVNode>,
     },
 }
 impl RenderedVNode {
@@ -33,6 +78,10 @@ impl RenderedVNode {
             (RenderedVNode::Text { text }, PortalExpandedVNode::Text { text: other_text }) => {
                 text != other_text
             }
+            (
+                RenderedVNode::RawHtml { html },
+                PortalExpandedVNode::RawHtml { html: other_html },
+            ) => html != other_html,
             (
                 RenderedVNode::Element {
                     tag,
@@ -40,6 +89,8 @@ impl RenderedVNode {
                     props,
                     event,
                     children,
+                    key,
+                    namespace,
                 },
                 PortalExpandedVNode::Element {
                     tag: other_tag,
@@ -47,23 +98,96 @@ impl RenderedVNode {
                     props: other_props,
                     event: other_event,
                     children: other_children,
+                    key: other_key,
+                    namespace: other_namespace,
                 },
             ) => {
+                // keyed children are matched by their keys, so a different key must be diffed
                 tag != other_tag
+                    || key != other_key
+                    || namespace != other_namespace
                     || class != other_class
                     || props != other_props
-                    || event.handle_events != other_event.handle_events
-                    || event.handle_prevent_default_events
-                        != other_event.handle_prevent_default_events
+                    || event.is_different(other_event)
                     || children.len() != other_children.len()
                     || children
                         .iter()
                         .zip(other_children.iter())
                         .any(|(a, b)| a.is_different(b))
             }
+            (
+                RenderedVNode::Lazy {
+                    key,
+                    dependency,
+                    has_portals,
+                    ..
+                },
+                PortalExpandedVNode::Lazy {
+                    key: other_key,
+                    dependency: other_dependency,
+                    ..
+                },
+            ) => *has_portals || key != other_key || dependency != other_dependency,
             _ => true,
         }
     }
+
+    fn key(&self) -> Option<&str> {
+        match self {
+            RenderedVNode::Element { key, .. } | RenderedVNode::Lazy { key, .. } => key.as_deref(),
+            RenderedVNode::Text { .. } | RenderedVNode::RawHtml { .. } => None,
+        }
+    }
+
+    // replace event handlers of the vnode and its children with the handlers of the same vnode,
+    // and keep the styles of the reused lazy vnodes
+    fn reuse(&self, next: PortalExpandedVNode, ctx: &mut DiffContext) {
+        match (self, next) {
+            (
+                RenderedVNode::Element {
+                    event, children, ..
+                },
+                PortalExpandedVNode::Element {
+                    event: next_event,
+                    children: next_children,
+                    ..
+                },
+            ) => {
+                ctx.events.insert(event.id.clone(), next_event.handler);
+                for (child, next_child) in children.iter().zip(next_children) {
+                    child.reuse(next_child, ctx);
+                }
+            }
+            (RenderedVNode::Lazy { styles, .. }, PortalExpandedVNode::Lazy { .. }) => {
+                ctx.styles.reuse(styles);
+            }
+            _ => {}
+        }
+    }
+
+    // vnode rendered by the lazy vnode, or the vnode itself
+    fn into_rendered(self) -> RenderedVNode {
+        match self {
+            RenderedVNode::Lazy { vnode, .. } => *vnode,
+            vnode => vnode,
+        }
+    }
+
+    // remove event handlers of the vnode and its children
+    fn remove_handlers(&self, events: &mut EventHandlers) {
+        match self {
+            RenderedVNode::Element {
+                event, children, ..
+            } => {
+                events.remove(&event.id);
+                for child in children {
+                    child.remove_handlers(events);
+                }
+            }
+            RenderedVNode::Lazy { vnode, .. } => vnode.remove_handlers(events),
+            RenderedVNode::Text { .. } | RenderedVNode::RawHtml { .. } => {}
+        }
+    }
 }
 
 // rendered vnode
@@ -85,374 +209,926 @@ impl RenderedVNodes {
     pub(crate) fn diff(
         &mut self,
         next: PortalExpandedVNodes,
-        events: &mut HashMap<EventHandleId, Box<dyn Fn(VNodeEventKind) + Sync + Send>>,
+        events: &mut EventHandlers,
+        styles: &mut StyleExtractor,
     ) -> (Vec<DifferenceCommand>, Vec<DifferenceCommand>) {
-        fn diff_vnodes(
-            current: &mut Vec<RenderedVNode>,
-            mut next: Vec<PortalExpandedVNode>,
-            events: &mut HashMap<EventHandleId, Box<dyn Fn(VNodeEventKind) + Sync + Send>>,
-        ) -> Vec<DifferenceCommand> {
-            let mut commands = vec![];
-
-            let mut i = 0;
-            let mut remove_index = None;
-            while i < current.len().max(next.len()) as u32 {
-                if i >= current.len() as u32 && i < next.len() as u32 {
-                    // new element
-
-                    // dummy vnode
-                    let mut next_vnode = PortalExpandedVNode::Text {
-                        text: String::new(),
-                    };
-                    // take ownership of next[i]
-                    std::mem::swap(&mut next_vnode, &mut next[i as usize]);
-
-                    match next_vnode {
-                        PortalExpandedVNode::Element {
-                            tag,
-                            class,
-                            props,
-                            event,
-                            children,
-                        } => {
-                            // insert new event handler
-                            let event_id = EventHandleId::new();
-                            let handler = event.handler;
-                            events.insert(event_id.clone(), handler);
-
-                            let mut inserted_vnode_children = vec![];
-
-                            // add insert command
-                            commands.push(DifferenceCommand::InsertElement {
-                                index: i,
-                                tag: tag.clone(),
-                                classes: class.clone(),
-                                props: props.clone(),
-                                event: HandleEvent {
-                                    handle_id: event_id.clone(),
-                                    handle_events: event.handle_events.clone(),
-                                    handle_prevent_default_events: event
-                                        .handle_prevent_default_events
-                                        .clone(),
-                                },
-                                children: diff_vnodes(
-                                    &mut inserted_vnode_children,
-                                    children,
-                                    events,
-                                ),
-                            });
+        let mut ctx = DiffContext {
+            events,
+            styles,
+            portals: vec![],
+        };
+        let main_diff = diff_vnodes(&mut self.main, next.main, &mut ctx);
 
-                            // update current vnode
-                            current.push(RenderedVNode::Element {
-                                tag: tag.clone(),
-                                class: class.clone(),
-                                props: props.clone(),
-                                event: HandledEvents {
-                                    id: event_id,
-                                    handle_events: event.handle_events.clone(),
-                                    handle_prevent_default_events: event
-                                        .handle_prevent_default_events
-                                        .clone(),
-                                },
-                                children: inserted_vnode_children,
-                            });
-                        }
-                        PortalExpandedVNode::Text { text } => {
-                            // add insert command
-                            commands.push(DifferenceCommand::InsertText {
-                                index: i,
-                                text: text.clone(),
-                            });
+        // portals of the lazy vnodes are rendered after the other portals
+        let mut portals = next.portals;
+        portals.append(&mut ctx.portals);
+        let portals_diff = diff_vnodes(&mut self.portals, portals, &mut ctx);
 
-                            // update current vnode
-                            current.push(RenderedVNode::Text { text: text.clone() });
-                        }
-                    }
-                    i += 1;
-                    continue;
-                } else if i < current.len() as u32 && i >= next.len() as u32 {
-                    // removed element
-                    if remove_index.is_none() {
-                        // when removing multiple elements, the index is shifted,
-                        // so record the index of the first element to be removed
-                        remove_index = Some(i);
-                    }
+        (main_diff, portals_diff)
+    }
+}
 
-                    // add remove command
-                    commands.push(DifferenceCommand::Remove {
-                        index: remove_index.unwrap(),
-                    });
+// state shared while diffing a frame
+struct DiffContext<'a, 'b> {
+    events: &'a mut EventHandlers,
+    styles: &'a mut StyleExtractor<'b>,
+    // portals of the rendered lazy vnodes
+    portals: Vec<PortalExpandedVNode>,
+}
+impl DiffContext<'_, '_> {
+    // render the subtree of the lazy vnode
+    fn render(&mut self, render: LazyRender, namespace: Namespace) -> PortalExpandedVNode {
+        let vnode = self.styles.extract(render());
+        let (vnode, mut portals) = PortalExpandedVNodes::expand_lazy(vnode, namespace);
+        self.portals.append(&mut portals);
+        vnode
+    }
 
-                    // update current vnode
-                    current.remove(remove_index.unwrap() as usize);
+    // render the lazy vnode, and diff the rendered subtree by `diff`
+    fn render_lazy(
+        &mut self,
+        key: Option<String>,
+        dependency: u64,
+        render: LazyRender,
+        namespace: Namespace,
+        diff: impl FnOnce(&mut Self, PortalExpandedVNode) -> RenderedVNode,
+    ) -> RenderedVNode {
+        self.styles.start_recording();
+        let portals = self.portals.len();
+
+        let next = self.render(render, namespace);
+        let vnode = diff(self, next);
 
-                    i += 1;
-                    continue;
-                } else if i >= current.len() as u32 && i >= next.len() as u32 {
-                    // end of loop
-                    break;
+        RenderedVNode::Lazy {
+            key,
+            dependency,
+            styles: self.styles.finish_recording(),
+            has_portals: self.portals.len() > portals,
+            vnode: Box::new(vnode),
+        }
+    }
+}
+
+// diff children, children that have keys are matched by key and the others are matched by index
+fn diff_vnodes(
+    current: &mut Vec<RenderedVNode>,
+    next: Vec<PortalExpandedVNode>,
+    ctx: &mut DiffContext,
+) -> Vec<DifferenceCommand> {
+    if current.iter().any(|vnode| vnode.key().is_some())
+        || next.iter().any(|vnode| vnode.key().is_some())
+    {
+        return diff_keyed_vnodes(current, next, ctx);
+    }
+
+    let mut commands = vec![];
+
+    let next_len = next.len();
+    for (i, next_vnode) in next.into_iter().enumerate() {
+        if let Some(current_vnode) = current.get_mut(i) {
+            update_vnode(i as u32, current_vnode, next_vnode, ctx, &mut commands);
+        } else {
+            // new element
+            let (command, vnode) = insert_vnode(i as u32, next_vnode, ctx);
+            commands.push(command);
+            current.push(vnode);
+        }
+    }
+
+    // removed elements, the index of the next element to remove is not shifted
+    while current.len() > next_len {
+        current.remove(next_len).remove_handlers(ctx.events);
+        commands.push(DifferenceCommand::Remove {
+            index: next_len as u32,
+        });
+    }
+
+    commands
+}
+
+// diff children that are matched by key, and move the matched children to the new order
+fn diff_keyed_vnodes(
+    current: &mut Vec<RenderedVNode>,
+    next: Vec<PortalExpandedVNode>,
+    ctx: &mut DiffContext,
+) -> Vec<DifferenceCommand> {
+    let mut commands = vec![];
+
+    // index of the current vnode for each next vnode,
+    // vnodes without keys are matched to the vnodes without keys in order
+    let sources = {
+        let mut keyed = HashMap::new();
+        let mut unkeyed = VecDeque::new();
+        for (i, vnode) in current.iter().enumerate() {
+            match vnode.key() {
+                Some(key) => {
+                    keyed.entry(key).or_insert(i);
                 }
+                None => unkeyed.push_back(i),
+            }
+        }
+        next.iter()
+            .map(|vnode| match vnode.key() {
+                Some(key) => keyed.remove(key),
+                None => unkeyed.pop_front(),
+            })
+            .collect::<Vec<_>>()
+    };
 
-                let current_vnode = &mut current[i as usize];
-                // dummy vnode
-                let mut next_vnode = PortalExpandedVNode::Text {
-                    text: String::new(),
-                };
-                // take ownership of next[i]
-                std::mem::swap(&mut next_vnode, &mut next[i as usize]);
-
-                if !current_vnode.is_different(&next_vnode) {
-                    // no difference in vnode
-                    match (current_vnode, next_vnode) {
-                        (
-                            RenderedVNode::Element {
-                                event: current_event,
-                                ..
-                            },
-                            PortalExpandedVNode::Element {
-                                event: updated_event,
-                                ..
-                            },
-                        ) => {
-                            // update event handler
-                            let event_id = current_event.id.clone();
-                            let handler = updated_event.handler;
-                            events.insert(event_id, handler);
-                        }
-                        _ => (),
-                    }
-                    i += 1;
-                    continue;
+    // remove the current vnodes that are not matched, from the last to keep the index
+    let mut matched = vec![false; current.len()];
+    for &source in sources.iter().flatten() {
+        matched[source] = true;
+    }
+    for i in (0..current.len()).rev() {
+        if !matched[i] {
+            current.remove(i).remove_handlers(ctx.events);
+            commands.push(DifferenceCommand::Remove { index: i as u32 });
+        }
+    }
+    let mut shifted = Vec::with_capacity(matched.len());
+    let mut remaining = 0;
+    for is_matched in matched {
+        shifted.push(remaining);
+        if is_matched {
+            remaining += 1;
+        }
+    }
+    let sources: Vec<Option<usize>> = sources
+        .into_iter()
+        .map(|source| source.map(|source| shifted[source]))
+        .collect();
+
+    // vnodes in the longest increasing subsequence keep their position, and the others are moved
+    let mut stable = vec![false; remaining];
+    let matched_sources: Vec<usize> = sources.iter().flatten().copied().collect();
+    for i in longest_increasing_subsequence(&matched_sources) {
+        stable[matched_sources[i]] = true;
+    }
+
+    // place the vnodes before the next vnode from the last.
+    // the DOM keeps the vnodes in the order of slots: the current vnodes that are not placed yet
+    // stay after the stable vnode that precedes them, and the placed vnode `j` is right before
+    // the vnode `j + 1`. `slots` counts the vnodes in each slot to find their DOM index.
+    let mut targets = vec![0; remaining];
+    for (j, source) in sources.iter().enumerate() {
+        if let Some(source) = source {
+            targets[*source] = j;
+        }
+    }
+    let mut groups = vec![vec![]; next.len() + 1];
+    let mut group = 0;
+    for source in 0..remaining {
+        if stable[source] {
+            group = targets[source] + 1;
+        } else {
+            groups[group].push(source);
+        }
+    }
+    let mut unplaced_slots = vec![0; remaining];
+    let mut placed_slots = vec![0; next.len()];
+    let mut slot_count = 0;
+    for (j, group) in groups.into_iter().enumerate() {
+        for source in group {
+            unplaced_slots[source] = slot_count;
+            slot_count += 1;
+        }
+        if j < next.len() {
+            placed_slots[j] = slot_count;
+            slot_count += 1;
+        }
+    }
+    let mut slots = SlotCounter::new(slot_count);
+    for source in 0..remaining {
+        if stable[source] {
+            slots.add(placed_slots[targets[source]], 1);
+        } else {
+            slots.add(unplaced_slots[source], 1);
+        }
+    }
+
+    let mut next: Vec<Option<PortalExpandedVNode>> = next.into_iter().map(Some).collect();
+    let mut inserted: Vec<Option<RenderedVNode>> = next.iter().map(|_| None).collect();
+    for j in (0..next.len()).rev() {
+        let anchor = |slots: &SlotCounter| match placed_slots.get(j + 1) {
+            Some(&slot) => slots.count_before(slot),
+            None => slots.count_before(slot_count),
+        };
+        match sources[j] {
+            None => {
+                // new element
+                let index = anchor(&slots);
+                let (command, vnode) = insert_vnode(index as u32, next[j].take().unwrap(), ctx);
+                commands.push(command);
+                inserted[j] = Some(vnode);
+                slots.add(placed_slots[j], 1);
+            }
+            Some(source) if !stable[source] => {
+                // moved element
+                let from = slots.count_before(unplaced_slots[source]);
+                slots.add(unplaced_slots[source], -1);
+                let to = anchor(&slots);
+                slots.add(placed_slots[j], 1);
+                commands.push(DifferenceCommand::Move {
+                    from: from as u32,
+                    to: to as u32,
+                });
+            }
+            Some(_) => {}
+        }
+    }
+
+    // update the matched vnodes at the new index
+    let mut previous: Vec<Option<RenderedVNode>> = current.drain(..).map(Some).collect();
+    for (j, source) in sources.iter().enumerate() {
+        let vnode = match source {
+            Some(source) => {
+                let mut vnode = previous[*source].take().unwrap();
+                let next_vnode = next[j].take().unwrap();
+                update_vnode(j as u32, &mut vnode, next_vnode, ctx, &mut commands);
+                vnode
+            }
+            None => inserted[j].take().unwrap(),
+        };
+        current.push(vnode);
+    }
+
+    commands
+}
+
+// index of the longest strictly increasing subsequence
+fn longest_increasing_subsequence(sequence: &[usize]) -> Vec<usize> {
+    // tails[k] is the index of the smallest last value of the subsequences of length k + 1
+    let mut tails: Vec<usize> = vec![];
+    let mut predecessors = vec![None; sequence.len()];
+    for (i, value) in sequence.iter().enumerate() {
+        let k = tails.partition_point(|&tail| sequence[tail] < *value);
+        if k > 0 {
+            predecessors[i] = Some(tails[k - 1]);
+        }
+        if k == tails.len() {
+            tails.push(i);
+        } else {
+            tails[k] = i;
+        }
+    }
+
+    let mut subsequence = vec![];
+    let mut last = tails.last().copied();
+    while let Some(i) = last {
+        subsequence.push(i);
+        last = predecessors[i];
+    }
+    subsequence.reverse();
+    subsequence
+}
+
+// number of vnodes in each slot, that finds the number of vnodes before a slot in log time
+struct SlotCounter {
+    // binary indexed tree of the counts
+    tree: Vec<isize>,
+}
+impl SlotCounter {
+    fn new(len: usize) -> Self {
+        Self {
+            tree: vec![0; len + 1],
+        }
+    }
+
+    fn add(&mut self, slot: usize, delta: isize) {
+        let mut i = slot + 1;
+        while i < self.tree.len() {
+            self.tree[i] += delta;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    fn count_before(&self, slot: usize) -> usize {
+        let mut count = 0;
+        let mut i = slot;
+        while i > 0 {
+            count += self.tree[i];
+            i -= i & i.wrapping_neg();
+        }
+        count as usize
+    }
+}
+
+// create a rendered vnode and the command to insert it at the index
+fn insert_vnode(
+    index: u32,
+    next: PortalExpandedVNode,
+    ctx: &mut DiffContext,
+) -> (DifferenceCommand, RenderedVNode) {
+    match next {
+        PortalExpandedVNode::Element {
+            tag,
+            class,
+            props,
+            event,
+            children,
+            key,
+            namespace,
+        } => {
+            // insert new event handler
+            let handled_events = HandledEvents::new(EventHandleId::new(), &event);
+            ctx.events.insert(handled_events.id.clone(), event.handler);
+
+            let mut inserted_vnode_children = vec![];
+
+            // add insert command
+            let command = DifferenceCommand::InsertElement {
+                index,
+                tag: tag.clone(),
+                namespace,
+                classes: class.clone(),
+                props: props.clone(),
+                event: handled_events.to_command(),
+                children: diff_vnodes(&mut inserted_vnode_children, children, ctx),
+            };
+
+            let vnode = RenderedVNode::Element {
+                tag,
+                class,
+                props,
+                event: handled_events,
+                children: inserted_vnode_children,
+                key,
+                namespace,
+            };
+            (command, vnode)
+        }
+        PortalExpandedVNode::Text { text } => (
+            DifferenceCommand::InsertText {
+                index,
+                text: text.clone(),
+            },
+            RenderedVNode::Text { text },
+        ),
+        PortalExpandedVNode::RawHtml { html } => (
+            DifferenceCommand::InsertRawHtml {
+                index,
+                html: html.clone(),
+            },
+            RenderedVNode::RawHtml { html },
+        ),
+        PortalExpandedVNode::Lazy {
+            key,
+            dependency,
+            render,
+            namespace,
+        } => {
+            let mut command = None;
+            let vnode = ctx.render_lazy(key, dependency, render, namespace, |ctx, next| {
+                let (insert_command, vnode) = insert_vnode(index, next, ctx);
+                command = Some(insert_command);
+                vnode
+            });
+            (command.unwrap(), vnode)
+        }
+    }
+}
+
+// update the rendered vnode at the index to the next vnode
+fn update_vnode(
+    index: u32,
+    current_vnode: &mut RenderedVNode,
+    next_vnode: PortalExpandedVNode,
+    ctx: &mut DiffContext,
+    commands: &mut Vec<DifferenceCommand>,
+) {
+    if !current_vnode.is_different(&next_vnode) {
+        // no difference in vnode, only update event handlers
+        current_vnode.reuse(next_vnode, ctx);
+        return;
+    }
+
+    // render the changed lazy vnode, and diff the rendered subtree with the current subtree
+    if let PortalExpandedVNode::Lazy {
+        key,
+        dependency,
+        render,
+        namespace,
+    } = next_vnode
+    {
+        let mut vnode = std::mem::replace(
+            current_vnode,
+            RenderedVNode::Text {
+                text: String::new(),
+            },
+        )
+        .into_rendered();
+        *current_vnode = ctx.render_lazy(key, dependency, render, namespace, |ctx, next| {
+            update_vnode(index, &mut vnode, next, ctx, commands);
+            vnode
+        });
+        return;
+    }
+    if let RenderedVNode::Lazy { .. } = current_vnode {
+        let mut vnode = std::mem::replace(
+            current_vnode,
+            RenderedVNode::Text {
+                text: String::new(),
+            },
+        )
+        .into_rendered();
+        update_vnode(index, &mut vnode, next_vnode, ctx, commands);
+        *current_vnode = vnode;
+        return;
+    }
+
+    match (&mut *current_vnode, next_vnode) {
+        // lazy vnodes are handled above
+        (RenderedVNode::Lazy { .. }, _) | (_, PortalExpandedVNode::Lazy { .. }) => unreachable!(),
+        (
+            RenderedVNode::Element {
+                tag: current_tag,
+                class: current_class,
+                props: current_props,
+                event: current_event,
+                children: current_children,
+                namespace: current_namespace,
+                ..
+            },
+            PortalExpandedVNode::Element {
+                tag: next_tag,
+                class: next_class,
+                props: next_props,
+                event: next_event,
+                children: next_children,
+                namespace: next_namespace,
+                ..
+            },
+        ) => {
+            if current_tag != &next_tag || *current_namespace != next_namespace {
+                // change element tag or namespace
+
+                // remove prev event handlers
+                ctx.events.remove(&current_event.id);
+                for child in current_children.iter() {
+                    child.remove_handlers(ctx.events);
                 }
 
-                match (current_vnode, next_vnode) {
-                    (
-                        RenderedVNode::Element {
-                            tag: current_tag,
-                            class: current_class,
-                            props: current_props,
-                            event: current_event,
-                            children: current_children,
-                        },
-                        PortalExpandedVNode::Element {
-                            tag: next_tag,
-                            class: next_class,
-                            props: next_props,
-                            event: next_event,
-                            children: next_children,
-                        },
-                    ) => {
-                        if current_tag != &next_tag {
-                            // change element tag
-
-                            // remove prev event handler
-                            events.remove(&current_event.id);
-
-                            // insert new event handler
-                            let event_id = EventHandleId::new();
-                            let handler = next_event.handler;
-                            events.insert(event_id.clone(), handler);
-
-                            current_children.clear();
-
-                            // add replace command
-                            commands.push(DifferenceCommand::ReplaceToElement {
-                                index: i,
-                                new_tag: next_tag.clone(),
-                                classes: next_class.clone(),
-                                props: next_props.clone(),
-                                event: HandleEvent {
-                                    handle_id: event_id.clone(),
-                                    handle_events: next_event.handle_events.clone(),
-                                    handle_prevent_default_events: next_event
-                                        .handle_prevent_default_events
-                                        .clone(),
-                                },
-                                children: diff_vnodes(current_children, next_children, events),
-                            });
+                // insert new event handler
+                let handled_events = HandledEvents::new(EventHandleId::new(), &next_event);
+                ctx.events
+                    .insert(handled_events.id.clone(), next_event.handler);
 
-                            // update current vnode
-                            *current_tag = next_tag.clone();
-                            *current_class = next_class.clone();
-                            *current_props = next_props.clone();
-                            *current_event = HandledEvents {
-                                id: event_id,
-                                handle_events: next_event.handle_events.clone(),
-                                handle_prevent_default_events: next_event
-                                    .handle_prevent_default_events
-                                    .clone(),
-                            };
-                        } else {
-                            // difference of class
-                            let mut class_diff = vec![];
-                            if current_class != &next_class {
-                                // change class
-                                for class in next_class.iter() {
-                                    if !current_class.contains(class) {
-                                        // add add class command
-                                        class_diff.push(DifferenceClass::Add(class.clone()));
-                                        // update current vnode
-                                        current_class.push(class.clone());
-                                    }
-                                }
-                                for class in current_class.clone().iter() {
-                                    if !next_class.contains(class) {
-                                        // add remove class command
-                                        class_diff.push(DifferenceClass::Remove(class.clone()));
-                                        // update current vnode
-                                        if let Some(index) =
-                                            current_class.iter().position(|x| x == class)
-                                        {
-                                            current_class.remove(index);
-                                        }
-                                    }
-                                }
-                            }
+                current_children.clear();
 
-                            // difference of props
-                            let mut props_diff = vec![];
-                            if current_props != &next_props {
-                                // change props
-                                for props in next_props.iter() {
-                                    if !current_props.contains(props) {
-                                        // add add props command
-                                        props_diff.push(DifferenceProps::Add(props.clone()));
-                                        // update current vnode
-                                        current_props.push(props.clone());
-                                    }
-                                }
-                                for props in current_props.clone().iter() {
-                                    if !next_props.contains(props) {
-                                        // add remove props command
-                                        props_diff.push(DifferenceProps::Remove(props.clone()));
-                                        // update current vnode
-                                        if let Some(index) =
-                                            current_props.iter().position(|x| x == props)
-                                        {
-                                            current_props.remove(index);
-                                        }
-                                    }
-                                }
-                            }
+                // add replace command
+                commands.push(DifferenceCommand::ReplaceToElement {
+                    index,
+                    new_tag: next_tag.clone(),
+                    namespace: next_namespace,
+                    classes: next_class.clone(),
+                    props: next_props.clone(),
+                    event: handled_events.to_command(),
+                    children: diff_vnodes(current_children, next_children, ctx),
+                });
 
-                            // difference of events
-                            let mut event_diff = DifferenceEvent::None;
-                            if current_event.handle_events != next_event.handle_events
-                                && current_event.handle_prevent_default_events
-                                    != next_event.handle_prevent_default_events
-                            {
-                                // change handled event types
-                                event_diff = DifferenceEvent::Update(HandleEvent {
-                                    handle_id: current_event.id.clone(),
-                                    handle_events: next_event.handle_events.clone(),
-                                    handle_prevent_default_events: next_event
-                                        .handle_prevent_default_events
-                                        .clone(),
-                                });
-                                // update current vnode
-                                current_event.handle_events = next_event.handle_events.clone();
-                                current_event.handle_prevent_default_events =
-                                    next_event.handle_prevent_default_events.clone();
+                // update current vnode
+                *current_tag = next_tag.clone();
+                *current_namespace = next_namespace;
+                *current_class = next_class.clone();
+                *current_props = next_props.clone();
+                *current_event = handled_events;
+            } else {
+                // difference of class
+                let mut class_diff = vec![];
+                if current_class != &next_class {
+                    // change class
+                    for class in next_class.iter() {
+                        if !current_class.contains(class) {
+                            // add add class command
+                            class_diff.push(DifferenceClass::Add(class.clone()));
+                            // update current vnode
+                            current_class.push(class.clone());
+                        }
+                    }
+                    for class in current_class.clone().iter() {
+                        if !next_class.contains(class) {
+                            // add remove class command
+                            class_diff.push(DifferenceClass::Remove(class.clone()));
+                            // update current vnode
+                            if let Some(index) = current_class.iter().position(|x| x == class) {
+                                current_class.remove(index);
                             }
+                        }
+                    }
+                }
 
-                            // add update element command
-                            commands.push(DifferenceCommand::UpdateElement {
-                                index: i,
-                                class_diff,
-                                props_diff,
-                                event_diff,
-                                children: diff_vnodes(current_children, next_children, events),
+                // difference of props
+                let mut props_diff = vec![];
+                if current_props != &next_props {
+                    // change props
+                    for (name, value) in current_props.iter() {
+                        let is_removed = match next_props.get(name) {
+                            Some(next_value) => {
+                                std::mem::discriminant(value) != std::mem::discriminant(next_value)
+                            }
+                            None => true,
+                        };
+                        if is_removed {
+                            // add remove props command, props that change the kind are removed first
+                            props_diff.push(DifferenceProps::Remove {
+                                name: name.into(),
+                                value: value.clone(),
                             });
                         }
                     }
-                    (
-                        RenderedVNode::Text { text: current_text },
-                        PortalExpandedVNode::Text { text: next_text },
-                    ) => {
-                        // add update text command
-                        commands.push(DifferenceCommand::UpdateText {
-                            index: i,
-                            new_text: next_text.clone(),
-                        });
-                        // update current vnode
-                        *current_text = next_text.clone();
-                    }
-                    (
-                        RenderedVNode::Element { event, .. },
-                        PortalExpandedVNode::Text { text: next_text },
-                    ) => {
-                        // remove event handler
-                        events.remove(&event.id);
-
-                        // add replace command
-                        commands.push(DifferenceCommand::ReplaceToText {
-                            index: i,
-                            text: next_text.clone(),
-                        });
-
-                        // update current vnode
-                        current[i as usize] = RenderedVNode::Text {
-                            text: next_text.clone(),
-                        };
+                    for (name, value) in next_props.iter() {
+                        if current_props.get(name) != Some(value) {
+                            // add set props command
+                            props_diff.push(DifferenceProps::Set {
+                                name: name.into(),
+                                value: value.clone(),
+                            });
+                        }
                     }
-                    (
-                        RenderedVNode::Text { .. },
-                        PortalExpandedVNode::Element {
-                            tag: next_tag,
-                            class: next_class,
-                            props: next_props,
-                            event: next_event,
-                            children: next_children,
-                        },
-                    ) => {
-                        // insert new event handler
-                        let event_id = EventHandleId::new();
-                        let handler = next_event.handler;
-                        events.insert(event_id.clone(), handler);
-
-                        let mut new_children = vec![];
-
-                        // add replace command
-                        commands.push(DifferenceCommand::ReplaceToElement {
-                            index: i,
-                            new_tag: next_tag.clone(),
-                            classes: next_class.clone(),
-                            props: next_props.clone(),
-                            event: HandleEvent {
-                                handle_id: event_id.clone(),
-                                handle_events: next_event.handle_events.clone(),
-                                handle_prevent_default_events: next_event
-                                    .handle_prevent_default_events
-                                    .clone(),
-                            },
-                            children: diff_vnodes(&mut new_children, next_children, events),
-                        });
-
-                        // update current vnode
-                        current[i as usize] = RenderedVNode::Element {
-                            tag: next_tag.clone(),
-                            class: next_class.clone(),
-                            props: next_props.clone(),
-                            event: HandledEvents {
-                                id: event_id,
-                                handle_events: next_event.handle_events.clone(),
-                                handle_prevent_default_events: next_event
-                                    .handle_prevent_default_events
-                                    .clone(),
-                            },
-                            children: new_children,
-                        };
+                    // update current vnode
+                    *current_props = next_props.clone();
+                }
+
+                // difference of events
+                let mut event_diff = DifferenceEvent::None;
+                if current_event.is_different(&next_event) {
+                    // change handled event types
+                    *current_event = HandledEvents::new(current_event.id.clone(), &next_event);
+                    event_diff = DifferenceEvent::Update(current_event.to_command());
+                }
+
+                // update event handler
+                ctx.events
+                    .insert(current_event.id.clone(), next_event.handler);
+
+                // add update element command
+                commands.push(DifferenceCommand::UpdateElement {
+                    index,
+                    class_diff,
+                    props_diff,
+                    event_diff,
+                    children: diff_vnodes(current_children, next_children, ctx),
+                });
+            }
+        }
+        (
+            RenderedVNode::Text { text: current_text },
+            PortalExpandedVNode::Text { text: next_text },
+        ) => {
+            // add update text command
+            commands.push(DifferenceCommand::UpdateText {
+                index,
+                new_text: next_text.clone(),
+            });
+            // update current vnode
+            *current_text = next_text;
+        }
+        (_, PortalExpandedVNode::Text { text: next_text }) => {
+            // remove event handlers
+            current_vnode.remove_handlers(ctx.events);
+
+            // add replace command
+            commands.push(DifferenceCommand::ReplaceToText {
+                index,
+                text: next_text.clone(),
+            });
+
+            // update current vnode
+            *current_vnode = RenderedVNode::Text { text: next_text };
+        }
+        (_, next_vnode @ PortalExpandedVNode::Element { .. }) => {
+            // render the element as a new element, and replace the text with it
+            let (command, vnode) = insert_vnode(index, next_vnode, ctx);
+            if let DifferenceCommand::InsertElement {
+                index,
+                tag,
+                namespace,
+                classes,
+                props,
+                event,
+                children,
+            } = command
+            {
+                commands.push(DifferenceCommand::ReplaceToElement {
+                    index,
+                    new_tag: tag,
+                    namespace,
+                    classes,
+                    props,
+                    event,
+                    children,
+                });
+            }
+
+            // update current vnode
+            *current_vnode = vnode;
+        }
+        (_, PortalExpandedVNode::RawHtml { html: next_html }) => {
+            // raw html is replaced as a whole
+            current_vnode.remove_handlers(ctx.events);
+            commands.push(DifferenceCommand::ReplaceToRawHtml {
+                index,
+                html: next_html.clone(),
+            });
+            *current_vnode = RenderedVNode::RawHtml { html: next_html };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vnode::{vnode::BundledEventHandler, Prop};
+
+    // child of the diffed vnodes, labeled by the key of the element or the text
+    #[derive(Clone, Copy)]
+    enum Child {
+        Keyed(&'static str),
+        Text(&'static str),
+    }
+    use Child::*;
+
+    fn to_vnodes(children: &[Child]) -> Vec<PortalExpandedVNode> {
+        children
+            .iter()
+            .map(|child| match *child {
+                Keyed(key) => PortalExpandedVNode::Element {
+                    tag: "div".into(),
+                    class: vec![],
+                    props: Props::new().attribute("id", key),
+                    event: BundledEventHandler::none(),
+                    children: vec![],
+                    key: Some(key.into()),
+                    namespace: Namespace::Html,
+                },
+                Text(text) => PortalExpandedVNode::Text { text: text.into() },
+            })
+            .collect()
+    }
+
+    fn labels(children: &[Child]) -> Vec<String> {
+        children
+            .iter()
+            .map(|child| match *child {
+                Keyed(label) | Text(label) => label.to_string(),
+            })
+            .collect()
+    }
+
+    fn id(props: &Props) -> String {
+        match props.get("id") {
+            Some(Prop::Attribute(id)) => id.clone(),
+            _ => panic!("element without id"),
+        }
+    }
+
+    // apply the commands to the labels of the children in the way the browser does
+    fn apply(dom: &mut Vec<String>, commands: &[DifferenceCommand]) {
+        for command in commands {
+            match command {
+                DifferenceCommand::InsertElement { index, props, .. } => {
+                    dom.insert(*index as usize, id(props))
+                }
+                DifferenceCommand::InsertText { index, text } => {
+                    dom.insert(*index as usize, text.clone())
+                }
+                DifferenceCommand::InsertRawHtml { index, html } => {
+                    dom.insert(*index as usize, html.clone())
+                }
+                DifferenceCommand::Remove { index } => {
+                    dom.remove(*index as usize);
+                }
+                DifferenceCommand::Move { from, to } => {
+                    let label = dom.remove(*from as usize);
+                    dom.insert(*to as usize, label);
+                }
+                DifferenceCommand::UpdateElement {
+                    index, props_diff, ..
+                } => {
+                    for diff in props_diff {
+                        if let DifferenceProps::Set {
+                            name,
+                            value: Prop::Attribute(value),
+                        } = diff
+                        {
+                            if name == "id" {
+                                dom[*index as usize] = value.clone();
+                            }
+                        }
                     }
                 }
-                i += 1;
+                DifferenceCommand::UpdateText { index, new_text } => {
+                    dom[*index as usize] = new_text.clone()
+                }
+                DifferenceCommand::ReplaceToElement { index, props, .. } => {
+                    dom[*index as usize] = id(props)
+                }
+                DifferenceCommand::ReplaceToText { index, text } => {
+                    dom[*index as usize] = text.clone()
+                }
+                DifferenceCommand::ReplaceToRawHtml { index, html } => {
+                    dom[*index as usize] = html.clone()
+                }
+            }
+        }
+    }
+
+    // render `current`, diff it to `next`, and check that the commands produce `next`,
+    // return the commands of the diff
+    fn diff(current: &[Child], next: &[Child]) -> Vec<DifferenceCommand> {
+        let mut events = HashMap::new();
+        let mut style_map = HashMap::new();
+        let mut styles = StyleExtractor::new(&mut style_map);
+        let mut ctx = DiffContext {
+            events: &mut events,
+            styles: &mut styles,
+            portals: vec![],
+        };
+
+        let mut rendered = vec![];
+        let mut dom = vec![];
+        apply(
+            &mut dom,
+            &diff_vnodes(&mut rendered, to_vnodes(current), &mut ctx),
+        );
+        assert_eq!(dom, labels(current));
+
+        let commands = diff_vnodes(&mut rendered, to_vnodes(next), &mut ctx);
+        apply(&mut dom, &commands);
+        assert_eq!(dom, labels(next));
+        assert_eq!(
+            rendered.iter().map(|vnode| vnode.key()).collect::<Vec<_>>(),
+            to_vnodes(next)
+                .iter()
+                .map(|vnode| vnode.key())
+                .collect::<Vec<_>>()
+        );
+        commands
+    }
+
+    fn count_moves(commands: &[DifferenceCommand]) -> usize {
+        commands
+            .iter()
+            .filter(|command| matches!(command, DifferenceCommand::Move { .. }))
+            .count()
+    }
+
+    #[test]
+    fn keyed_insert() {
+        let commands = diff(
+            &[Keyed("a"), Keyed("c")],
+            &[Keyed("x"), Keyed("a"), Keyed("b"), Keyed("c"), Keyed("d")],
+        );
+        assert_eq!(count_moves(&commands), 0);
+    }
+
+    #[test]
+    fn keyed_remove() {
+        let commands = diff(
+            &[Keyed("a"), Keyed("b"), Keyed("c"), Keyed("d")],
+            &[Keyed("b"), Keyed("d")],
+        );
+        assert_eq!(count_moves(&commands), 0);
+    }
+
+    #[test]
+    fn keyed_reorder() {
+        let commands = diff(
+            &[Keyed("a"), Keyed("b"), Keyed("c"), Keyed("d")],
+            &[Keyed("b"), Keyed("c"), Keyed("d"), Keyed("a")],
+        );
+        assert_eq!(count_moves(&commands), 1);
+
+        diff(
+            &[Keyed("a"), Keyed("b"), Keyed("c"), Keyed("d"), Keyed("e")],
+            &[Keyed("d"), Keyed("x"), Keyed("b"), Keyed("e"), Keyed("a")],
+        );
+    }
+
+    #[test]
+    fn keyed_reverse() {
+        let commands = diff(
+            &[Keyed("a"), Keyed("b"), Keyed("c"), Keyed("d")],
+            &[Keyed("d"), Keyed("c"), Keyed("b"), Keyed("a")],
+        );
+        assert_eq!(count_moves(&commands), 3);
+    }
+
+    #[test]
+    fn keyed_permutations() {
+        let keys = ["a", "b", "c", "d", "e"];
+        let current: Vec<Child> = keys.iter().map(|&key| Keyed(key)).collect();
+        // every order of the keys, with the last key removed and a new key inserted
+        let mut orders = vec![vec![]];
+        for &key in &keys[..4] {
+            orders = orders
+                .into_iter()
+                .flat_map(|order: Vec<Child>| {
+                    (0..=order.len()).map(move |i| {
+                        let mut order = order.clone();
+                        order.insert(i, Keyed(key));
+                        order
+                    })
+                })
+                .collect();
+        }
+        for order in orders {
+            for i in 0..=order.len() {
+                let mut next = order.clone();
+                next.insert(i, Keyed("x"));
+                diff(&current, &next);
             }
+        }
+    }
 
-            commands
+    #[test]
+    fn nested_keyed_reorder() {
+        // the items differ only in their keys
+        let list = |keys: &[&'static str]| PortalExpandedVNode::Element {
+            tag: "ul".into(),
+            class: vec![],
+            props: Props::new(),
+            event: BundledEventHandler::none(),
+            children: keys
+                .iter()
+                .map(|&key| PortalExpandedVNode::Element {
+                    tag: "li".into(),
+                    class: vec![],
+                    props: Props::new(),
+                    event: BundledEventHandler::none(),
+                    children: vec![],
+                    key: Some(key.into()),
+                    namespace: Namespace::Html,
+                })
+                .collect(),
+            key: None,
+            namespace: Namespace::Html,
+        };
+        let mut events = HashMap::new();
+        let mut style_map = HashMap::new();
+        let mut styles = StyleExtractor::new(&mut style_map);
+        let mut ctx = DiffContext {
+            events: &mut events,
+            styles: &mut styles,
+            portals: vec![],
+        };
+
+        let mut rendered = vec![];
+        diff_vnodes(&mut rendered, vec![list(&["a", "b"])], &mut ctx);
+        let commands = diff_vnodes(&mut rendered, vec![list(&["b", "a"])], &mut ctx);
+        match &commands[..] {
+            [DifferenceCommand::UpdateElement { children, .. }] => {
+                assert_eq!(count_moves(children), 1)
+            }
+            _ => panic!("unexpected commands: {commands:?}"),
+        }
+        match &rendered[..] {
+            [RenderedVNode::Element { children, .. }] => assert_eq!(
+                children.iter().map(|vnode| vnode.key()).collect::<Vec<_>>(),
+                [Some("b"), Some("a")]
+            ),
+            _ => panic!("unexpected vnodes"),
         }
+    }
 
-        let main_diff = diff_vnodes(&mut self.main, next.main, events);
-        let portals_diff = diff_vnodes(&mut self.portals, next.portals, events);
+    #[test]
+    fn duplicate_keys() {
+        // only the first vnode of a key is matched, the others are inserted and removed
+        diff(
+            &[Keyed("a"), Keyed("a"), Keyed("b")],
+            &[Keyed("b"), Keyed("a"), Keyed("b"), Keyed("a")],
+        );
+    }
 
-        (main_diff, portals_diff)
+    #[test]
+    fn mixed_keyed_and_unkeyed() {
+        diff(
+            &[Text("x"), Keyed("a"), Text("y"), Keyed("b")],
+            &[Keyed("b"), Text("x"), Keyed("a"), Text("z")],
+        );
+        diff(
+            &[Text("x"), Text("y")],
+            &[Keyed("a"), Text("y"), Keyed("b")],
+        );
+        diff(&[Keyed("a"), Text("x"), Keyed("b")], &[Text("x")]);
+    }
+
+    #[test]
+    fn unkeyed() {
+        diff(&[Text("x"), Text("y")], &[Text("y"), Text("x"), Text("z")]);
+        diff(&[Text("x"), Text("y"), Text("z")], &[Text("x")]);
+    }
+
+    #[test]
+    fn increasing_subsequence() {
+        assert_eq!(longest_increasing_subsequence(&[]), Vec::<usize>::new());
+        assert_eq!(longest_increasing_subsequence(&[0, 1, 2]), vec![0, 1, 2]);
+        assert_eq!(longest_increasing_subsequence(&[3, 0, 1, 2]), vec![1, 2, 3]);
+        assert_eq!(longest_increasing_subsequence(&[2, 1, 0]).len(), 1);
+        assert_eq!(
+            longest_increasing_subsequence(&[4, 1, 5, 2, 6, 3, 7]),
+            vec![1, 3, 5, 6]
+        );
     }
 }
@@ -7,7 +7,8 @@ use uuid::Uuid;
 
 use crate::vnode::{
     command::StyleDifferenceCommand,
-    vnode::{BundledEventHandler, MessageHandledVNode},
+    vnode::{BundledEventHandler, LazyRender, MessageHandledVNode},
+    Namespace, Props,
 };
 
 // StyleExtractedVNode is a vnode with style replaced to class
@@ -18,106 +19,102 @@ pub(crate) enum StyleExtractedVNode {
     Element {
         tag: String,
         class: Vec<String>,
-        props: Vec<String>,
+        props: Props,
         event: BundledEventHandler,
         children: Vec<StyleExtractedVNode>,
+        key: Option<String>,
+        namespace: Option<Namespace>,
     },
     Portal {
         children: Vec<StyleExtractedVNode>,
     },
+    Fragment {
+        children: Vec<StyleExtractedVNode>,
+    },
+    RawHtml {
+        html: String,
+    },
+    Lazy {
+        key: Option<String>,
+        dependency: u64,
+        render: LazyRender,
+    },
 }
-impl StyleExtractedVNode {
-    // extract style from vnode and replace to class, and generate style diff commands
-    pub(crate) fn extract(
-        vnode: MessageHandledVNode,
-        styles: &mut HashMap<String, Uuid>,
-    ) -> (Self, Vec<StyleDifferenceCommand>) {
-        let mut style_commands = vec![];
 
-        // use hashmap to detect removed styles
+// extract style from vnodes rendered in a frame and replace to class,
+// and generate style diff commands at the end of the frame
+pub(crate) struct StyleExtractor<'a> {
+    styles: &'a mut HashMap<String, Uuid>,
+    // use hashmap to detect removed styles
+    prev_styles: HashMap<Uuid, bool>,
+    // record new styles
+    new_styles: HashMap<String, Uuid>,
+    // styles used by the lazy vnodes that are being rendered
+    recordings: Vec<Vec<String>>,
+}
+impl<'a> StyleExtractor<'a> {
+    pub(crate) fn new(styles: &'a mut HashMap<String, Uuid>) -> Self {
         let mut prev_styles = HashMap::new();
         for (_, uuid) in styles.iter() {
-            prev_styles.insert(uuid.clone(), false);
+            prev_styles.insert(*uuid, false);
+        }
+        Self {
+            styles,
+            prev_styles,
+            new_styles: HashMap::new(),
+            recordings: vec![],
         }
+    }
 
-        // record new styles
-        let mut new_styles = HashMap::<String, Uuid>::new();
-
-        // replace style to uuid class
-        let mut style_to_class = |vnode: &mut MessageHandledVNode| {
-            if let MessageHandledVNode::Element {
-                ref mut style,
-                ref mut class,
-                ..
-            } = vnode
-            {
-                if let Some(style_text) = style.clone() {
-                    if styles.contains_key(&style_text) {
-                        // already registered style
-                        let uuid = styles[&style_text].clone();
-                        class.push(format!("style-{}", uuid.to_string()));
-                        *style = None;
-                        prev_styles.insert(uuid, true);
-                    } else if new_styles.contains_key(&style_text) {
-                        // already registered style
-                        let uuid = new_styles[&style_text].clone();
-                        class.push(format!("style-{}", uuid.to_string()));
-                        *style = None;
-                    } else {
-                        // new style
-                        let uuid = Uuid::new_v4();
-                        class.push(format!("style-{}", uuid.to_string()));
-                        new_styles.insert(style_text.clone(), uuid);
-                    }
-                }
-            }
-        };
-        // extract style from vnode
-        fn traverse(
-            mut vnode: MessageHandledVNode,
-            style_to_class: &mut dyn FnMut(&mut MessageHandledVNode),
-        ) -> StyleExtractedVNode {
-            style_to_class(&mut vnode);
-            match vnode {
-                MessageHandledVNode::Text { text } => StyleExtractedVNode::Text { text },
-                MessageHandledVNode::Element {
-                    tag,
-                    class,
-                    props,
-                    children,
-                    event,
-                    ..
-                } => {
-                    let mut new_children = vec![];
-                    for child in children {
-                        new_children.push(traverse(child, style_to_class));
-                    }
-                    StyleExtractedVNode::Element {
-                        tag,
-                        class,
-                        props,
-                        children: new_children,
-                        event,
-                    }
-                }
-                MessageHandledVNode::Portal { children, .. } => {
-                    let mut new_children = vec![];
-                    for child in children {
-                        new_children.push(traverse(child, style_to_class));
-                    }
-                    StyleExtractedVNode::Portal {
-                        children: new_children,
-                    }
-                }
+    // extract style from vnode, lazy vnodes are extracted when they are rendered
+    // except the root and the lazy vnodes in portals, which are rendered here
+    pub(crate) fn extract(&mut self, mut vnode: MessageHandledVNode) -> StyleExtractedVNode {
+        while let MessageHandledVNode::Lazy { render, .. } = vnode {
+            vnode = render();
+        }
+        self.traverse(vnode, false)
+    }
+
+    // mark styles of the reused lazy vnode as used
+    pub(crate) fn reuse(&mut self, styles: &[String]) {
+        for style_text in styles {
+            if let Some(uuid) = self.styles.get(style_text) {
+                self.prev_styles.insert(*uuid, true);
             }
+            self.record(style_text);
         }
-        let vnode = traverse(vnode, &mut style_to_class);
+    }
+
+    // record styles used until `finish_recording`
+    pub(crate) fn start_recording(&mut self) {
+        self.recordings.push(vec![]);
+    }
+
+    pub(crate) fn finish_recording(&mut self) -> Vec<String> {
+        let mut styles = self.recordings.pop().unwrap_or_default();
+        styles.sort();
+        styles.dedup();
+        if let Some(parent) = self.recordings.last_mut() {
+            parent.extend(styles.iter().cloned());
+        }
+        styles
+    }
+
+    // generate style diff commands
+    pub(crate) fn finish(self) -> Vec<StyleDifferenceCommand> {
+        let mut style_commands = vec![];
 
         // styles that are not used in the new vnode are removed
-        for (uuid, is_used) in prev_styles {
+        for (uuid, is_used) in self.prev_styles {
             if !is_used {
-                let style_text = styles.iter().find(|(_, v)| **v == uuid).unwrap().0.clone();
-                styles.remove(&style_text);
+                let style_text = self
+                    .styles
+                    .iter()
+                    .find(|(_, v)| **v == uuid)
+                    .unwrap()
+                    .0
+                    .clone();
+                self.styles.remove(&style_text);
                 style_commands.push(StyleDifferenceCommand::RemoveStyle {
                     class_name: uuid.to_string(),
                 });
@@ -125,8 +122,8 @@ impl StyleExtractedVNode {
         }
 
         // register new styles
-        for (style_text, uuid) in new_styles {
-            styles.insert(style_text.clone(), uuid);
+        for (style_text, uuid) in self.new_styles {
+            self.styles.insert(style_text.clone(), uuid);
 
             let style_sheet = format!(".style-{} {{ {} }}", uuid, style_text);
             let mut style_sheet: StyleSheet = StyleSheet::parse(
@@ -151,12 +148,100 @@ impl StyleExtractedVNode {
                 .code;
 
             style_commands.push(StyleDifferenceCommand::AddStyle {
-                class_name: format!("style-{}", uuid.to_string()),
+                class_name: format!("style-{}", uuid),
                 value: style_sheet,
             });
         }
 
-        // return style extracted vnode and style diff commands
-        (vnode, style_commands)
+        style_commands
+    }
+
+    fn record(&mut self, style_text: &str) {
+        if let Some(recording) = self.recordings.last_mut() {
+            recording.push(style_text.to_string());
+        }
+    }
+
+    // replace style to uuid class
+    fn style_to_class(&mut self, style_text: String) -> String {
+        self.record(&style_text);
+        if let Some(uuid) = self.styles.get(&style_text) {
+            // already registered style
+            self.prev_styles.insert(*uuid, true);
+            format!("style-{}", uuid)
+        } else if let Some(uuid) = self.new_styles.get(&style_text) {
+            // already registered style
+            format!("style-{}", uuid)
+        } else {
+            // new style
+            let uuid = Uuid::new_v4();
+            self.new_styles.insert(style_text, uuid);
+            format!("style-{}", uuid)
+        }
+    }
+
+    fn traverse(&mut self, vnode: MessageHandledVNode, in_portal: bool) -> StyleExtractedVNode {
+        match vnode {
+            MessageHandledVNode::Text { text } => StyleExtractedVNode::Text { text },
+            MessageHandledVNode::RawHtml { html } => StyleExtractedVNode::RawHtml { html },
+            MessageHandledVNode::Element {
+                tag,
+                mut class,
+                props,
+                event,
+                children,
+                style,
+                key,
+                namespace,
+            } => {
+                if let Some(style_text) = style {
+                    class.push(self.style_to_class(style_text));
+                }
+                let mut new_children = vec![];
+                for child in children {
+                    new_children.push(self.traverse(child, in_portal));
+                }
+                StyleExtractedVNode::Element {
+                    tag,
+                    class,
+                    props,
+                    children: new_children,
+                    event,
+                    key,
+                    namespace,
+                }
+            }
+            MessageHandledVNode::Portal { children } => {
+                let mut new_children = vec![];
+                for child in children {
+                    new_children.push(self.traverse(child, true));
+                }
+                StyleExtractedVNode::Portal {
+                    children: new_children,
+                }
+            }
+            MessageHandledVNode::Fragment { children } => {
+                let mut new_children = vec![];
+                for child in children {
+                    new_children.push(self.traverse(child, in_portal));
+                }
+                StyleExtractedVNode::Fragment {
+                    children: new_children,
+                }
+            }
+            // portals are rendered on every render, so lazy vnodes in portals are rendered here
+            MessageHandledVNode::Lazy { render, .. } if in_portal => {
+                self.traverse(render(), in_portal)
+            }
+            MessageHandledVNode::Lazy {
+                key,
+                dependency,
+                render,
+            } => StyleExtractedVNode::Lazy {
+                key,
+                dependency,
+                render,
+            },
+        }
     }
 }
@@ -1,8 +1,25 @@
 //! Window hosted by server and related types.
 
-use std::fmt::Debug;
+use parking_lot::Mutex;
+use std::{collections::HashMap, fmt::Debug, sync::Arc, time::Duration};
+use tokio::sync::watch;
+use typeshare::typeshare;
+use uuid::Uuid;
 
+use crate::vnode::{
+    BrowserCommand, DomCommand, ElementQuery, ElementQueryResponse, ElementQueryResult, NodeRef,
+};
+
+mod command;
+pub mod dock_window;
+pub mod model_window;
 pub mod simple_window;
+mod subscription;
+
+pub use command::Command;
+pub(crate) use command::CommandRunner;
+pub use subscription::Subscription;
+pub(crate) use subscription::SubscriptionRunner;
 
 pub trait Message: 'static + Debug + Clone + Send + Sync {}
 impl<T: 'static + Debug + Clone + Send + Sync> Message for T {}
@@ -12,3 +29,262 @@ pub enum DirtyFlag {
     ShouldRender,
     None,
 }
+
+/// What the browser does with events that occur while it is disconnected from the server.
+#[typeshare]
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum OfflineEventPolicy {
+    /// Drop the events.
+    #[default]
+    Discard,
+    /// Send the events after reconnecting. The events reach the window only if the session
+    /// is resumed.
+    Queue,
+}
+
+/// State of the connection to the browser window.
+///
+/// The window receives it as a message by registering a handler with
+/// [`SimpleWindowContext::on_connection_changed`](simple_window::SimpleWindowContext::on_connection_changed).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// The browser window connected to a new session.
+    Connected,
+    /// The browser window is disconnected, and the session is kept for the grace period.
+    Disconnected,
+    /// The browser window reconnected and resumed the session.
+    Resumed,
+}
+
+// settings of the connections that render the windows
+#[derive(Clone, Debug)]
+pub(crate) struct SessionConfig {
+    // minimum interval between two frames pushed to a browser
+    pub(crate) frame_interval: Duration,
+    // time to keep a disconnected session alive for the browser to resume it
+    pub(crate) grace_period: Duration,
+    pub(crate) offline_events: OfflineEventPolicy,
+}
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            frame_interval: Duration::from_secs(1) / 60,
+            grace_period: Duration::from_secs(30),
+            offline_events: OfflineEventPolicy::default(),
+        }
+    }
+}
+
+// called with the result of an element query, or None if the element is not rendered
+// or the browser does not respond
+type ElementQueryCallback = Box<dyn FnOnce(Option<ElementQueryResult>) + Send>;
+
+// time to wait for the browser to respond to an element query
+const ELEMENT_QUERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+// shows a panel of the dock window of the session
+type PanelOpener = Box<dyn Fn(&str) + Send + Sync>;
+
+// browser and DOM commands waiting to be sent to a browser window
+#[derive(Default)]
+struct CommandQueue {
+    browser: Vec<BrowserCommand>,
+    dom: Vec<DomCommand>,
+}
+
+// commands are sent to every connection that receives them,
+// and kept for the next connection while there is none
+#[derive(Default)]
+struct CommandQueues {
+    pending: CommandQueue,
+    receivers: HashMap<u64, CommandQueue>,
+    next_receiver: u64,
+}
+impl CommandQueues {
+    fn push(&mut self, push: impl Fn(&mut CommandQueue)) {
+        if self.receivers.is_empty() {
+            push(&mut self.pending);
+        }
+        for queue in self.receivers.values_mut() {
+            push(queue);
+        }
+    }
+}
+
+// state shared by the windows of a session and the connections that render them
+#[derive(Clone)]
+pub(crate) struct SessionHandle {
+    id: Uuid,
+    render_version: Arc<watch::Sender<u64>>,
+    commands: Arc<Mutex<CommandQueues>>,
+    // element queries waiting for the browser to respond
+    element_queries: Arc<Mutex<HashMap<String, ElementQueryCallback>>>,
+    // set by the dock window of the session
+    panel_opener: Arc<Mutex<Option<PanelOpener>>>,
+}
+impl SessionHandle {
+    pub(crate) fn new() -> Self {
+        let (render_version, _) = watch::channel(1);
+        Self {
+            id: Uuid::new_v4(),
+            render_version: Arc::new(render_version),
+            commands: Default::default(),
+            element_queries: Arc::new(Mutex::new(HashMap::new())),
+            panel_opener: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub(crate) fn id(&self) -> Uuid {
+        self.id
+    }
+
+    // mark every connection of the session to render the windows again
+    pub(crate) fn request_render(&self) {
+        self.render_version.send_modify(|version| *version += 1);
+    }
+
+    // receiver that is notified every time a render is requested
+    pub(crate) fn subscribe_render(&self) -> watch::Receiver<u64> {
+        self.render_version.subscribe()
+    }
+
+    // ask the browser to open a new window that renders the panel, or the whole window
+    // when `panel` is None, bound to this session
+    pub(crate) fn open_window(&self, panel: Option<&str>) {
+        let mut url = format!("./?session={}", self.id);
+        if let Some(panel) = panel {
+            url.push_str("&panel=");
+            url.push_str(&encode_uri_component(panel));
+        }
+        self.push_browser_command(BrowserCommand::OpenWindow { url });
+    }
+
+    pub(crate) fn set_panel_opener(&self, opener: impl Fn(&str) + 'static + Send + Sync) {
+        *self.panel_opener.lock() = Some(Box::new(opener));
+    }
+
+    // show the panel of the dock window, nothing happens when the session has no dock window
+    pub(crate) fn open_panel(&self, panel: &str) {
+        if let Some(opener) = &*self.panel_opener.lock() {
+            opener(panel);
+        }
+    }
+
+    // send the command with the next frame to every browser window that receives commands,
+    // the one that created the session or all browsers that share the window
+    pub(crate) fn push_browser_command(&self, command: BrowserCommand) {
+        self.commands
+            .lock()
+            .push(|queue| queue.browser.push(command.clone()));
+        self.request_render();
+    }
+
+    // apply the operation to the rendered element after the next frame,
+    // sent to the same browser windows as the browser commands
+    pub(crate) fn push_dom_command(&self, command: DomCommand) {
+        self.commands
+            .lock()
+            .push(|queue| queue.dom.push(command.clone()));
+        self.request_render();
+    }
+
+    // start receiving the commands on a connection,
+    // the first receiver takes the commands pushed while there was none
+    pub(crate) fn add_command_receiver(&self) -> u64 {
+        let mut commands = self.commands.lock();
+        let id = commands.next_receiver;
+        commands.next_receiver += 1;
+        let pending = std::mem::take(&mut commands.pending);
+        commands.receivers.insert(id, pending);
+        id
+    }
+
+    // stop receiving the commands, the commands that are not sent yet are kept for the next
+    // receiver when this is the last one
+    pub(crate) fn remove_command_receiver(&self, receiver: u64) {
+        let mut commands = self.commands.lock();
+        if let Some(mut queue) = commands.receivers.remove(&receiver) {
+            if commands.receivers.is_empty() {
+                queue.browser.append(&mut commands.pending.browser);
+                queue.dom.append(&mut commands.pending.dom);
+                commands.pending = queue;
+            }
+        }
+    }
+
+    pub(crate) fn take_commands(&self, receiver: u64) -> (Vec<BrowserCommand>, Vec<DomCommand>) {
+        let mut commands = self.commands.lock();
+        let queue = commands.receivers.entry(receiver).or_default();
+        (
+            std::mem::take(&mut queue.browser),
+            std::mem::take(&mut queue.dom),
+        )
+    }
+
+    // ask the browser about the element of the node ref, `callback` is called with the response
+    pub(crate) fn query_element(
+        &self,
+        node_ref: &NodeRef,
+        query: ElementQuery,
+        callback: ElementQueryCallback,
+    ) {
+        let query_id = Uuid::new_v4().to_string();
+        self.element_queries
+            .lock()
+            .insert(query_id.clone(), callback);
+        self.push_browser_command(BrowserCommand::QueryElement {
+            query_id: query_id.clone(),
+            node_ref: node_ref.id().into(),
+            query,
+        });
+
+        // the query is resolved with None if the browser does not respond in time
+        let element_queries = Arc::downgrade(&self.element_queries);
+        tokio::spawn(async move {
+            tokio::time::sleep(ELEMENT_QUERY_TIMEOUT).await;
+            let callback = element_queries
+                .upgrade()
+                .and_then(|element_queries| element_queries.lock().remove(&query_id));
+            if let Some(callback) = callback {
+                callback(None);
+            }
+        });
+    }
+
+    pub(crate) fn resolve_element_query(&self, response: ElementQueryResponse) {
+        let callback = self.element_queries.lock().remove(&response.query_id);
+        if let Some(callback) = callback {
+            callback(response.result);
+        }
+    }
+
+    // resolve the element queries with None, e.g. when the browser disconnected
+    pub(crate) fn cancel_element_queries(&self) {
+        let callbacks: Vec<_> = self.element_queries.lock().drain().collect();
+        for (_, callback) in callbacks {
+            callback(None);
+        }
+    }
+}
+impl Debug for SessionHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionHandle")
+            .field("id", &self.id)
+            .field("render_version", &*self.render_version.borrow())
+            .finish()
+    }
+}
+
+// percent-encode everything except the unreserved characters of RFC 3986
+fn encode_uri_component(text: &str) -> String {
+    let mut encoded = String::new();
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
@@ -0,0 +1,229 @@
+//! Commands that windows return from `update` to run asynchronous work.
+
+use parking_lot::Mutex;
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Weak},
+};
+use tokio::{sync::mpsc::UnboundedSender, task::JoinHandle};
+
+use crate::Message;
+
+type CommandFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+
+enum CommandKind<T: Message> {
+    None,
+    Message(T),
+    Future(CommandFuture<T>),
+    Task(String, CommandFuture<T>),
+    Cancel(String),
+    Batch(Vec<Command<T>>),
+}
+
+/// Work to run after `update`, whose results are dispatched to the window as messages.
+///
+/// Commands are run on tokio, and the running commands are cancelled when the window is
+/// disconnected.
+pub struct Command<T: Message> {
+    kind: CommandKind<T>,
+}
+impl<T: Message> Command<T> {
+    /// Command that does nothing.
+    pub fn none() -> Self {
+        Self {
+            kind: CommandKind::None,
+        }
+    }
+
+    /// Dispatch the message.
+    pub fn message(message: T) -> Self {
+        Self {
+            kind: CommandKind::Message(message),
+        }
+    }
+
+    /// Run the future and dispatch its output.
+    pub fn perform(future: impl Future<Output = T> + Send + 'static) -> Self {
+        Self {
+            kind: CommandKind::Future(Box::pin(future)),
+        }
+    }
+
+    /// Run the future as a task named `key` and dispatch its output.
+    ///
+    /// A running task with the same key is cancelled, so only the latest task of the key
+    /// dispatches its output.
+    pub fn task(key: impl Into<String>, future: impl Future<Output = T> + Send + 'static) -> Self {
+        Self {
+            kind: CommandKind::Task(key.into(), Box::pin(future)),
+        }
+    }
+
+    /// Cancel the running task named `key`.
+    pub fn cancel(key: impl Into<String>) -> Self {
+        Self {
+            kind: CommandKind::Cancel(key.into()),
+        }
+    }
+
+    /// Run all commands.
+    pub fn batch(commands: impl IntoIterator<Item = Command<T>>) -> Self {
+        Self {
+            kind: CommandKind::Batch(commands.into_iter().collect()),
+        }
+    }
+
+    /// Convert the messages that the command dispatches.
+    pub fn map<U: Message>(self, f: impl Fn(T) -> U + Clone + Send + Sync + 'static) -> Command<U> {
+        let kind = match self.kind {
+            CommandKind::None => CommandKind::None,
+            CommandKind::Message(message) => CommandKind::Message(f(message)),
+            CommandKind::Future(future) => {
+                CommandKind::Future(Box::pin(async move { f(future.await) }))
+            }
+            CommandKind::Task(key, future) => {
+                CommandKind::Task(key, Box::pin(async move { f(future.await) }))
+            }
+            CommandKind::Cancel(key) => CommandKind::Cancel(key),
+            CommandKind::Batch(commands) => CommandKind::Batch(
+                commands
+                    .into_iter()
+                    .map(|command| command.map(f.clone()))
+                    .collect(),
+            ),
+        };
+        Command { kind }
+    }
+}
+impl<T: Message> Default for Command<T> {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+impl<T: Message> Debug for Command<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            CommandKind::None => f.write_str("Command::None"),
+            CommandKind::Message(message) => {
+                f.debug_tuple("Command::Message").field(message).finish()
+            }
+            CommandKind::Future(_) => f.write_str("Command::Future"),
+            CommandKind::Task(key, _) => f.debug_tuple("Command::Task").field(key).finish(),
+            CommandKind::Cancel(key) => f.debug_tuple("Command::Cancel").field(key).finish(),
+            CommandKind::Batch(commands) => {
+                f.debug_tuple("Command::Batch").field(commands).finish()
+            }
+        }
+    }
+}
+
+struct CommandRunnerInner<T: Message> {
+    sender: UnboundedSender<T>,
+    next_id: u64,
+    tasks: HashMap<u64, JoinHandle<()>>,
+    keys: HashMap<String, u64>,
+    cancelled: bool,
+}
+
+// runs commands of a window and dispatches their results to the window
+pub(crate) struct CommandRunner<T: Message> {
+    inner: Arc<Mutex<CommandRunnerInner<T>>>,
+}
+impl<T: Message> CommandRunner<T> {
+    pub(crate) fn new(sender: UnboundedSender<T>) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(CommandRunnerInner {
+                sender,
+                next_id: 0,
+                tasks: HashMap::new(),
+                keys: HashMap::new(),
+                cancelled: false,
+            })),
+        }
+    }
+
+    pub(crate) fn run(&self, command: Command<T>) {
+        match command.kind {
+            CommandKind::None => (),
+            CommandKind::Message(message) => {
+                let _ = self.inner.lock().sender.send(message);
+            }
+            CommandKind::Future(future) => self.spawn(None, future),
+            CommandKind::Task(key, future) => self.spawn(Some(key), future),
+            CommandKind::Cancel(key) => {
+                let mut inner = self.inner.lock();
+                if let Some(id) = inner.keys.remove(&key) {
+                    if let Some(task) = inner.tasks.remove(&id) {
+                        task.abort();
+                    }
+                }
+            }
+            CommandKind::Batch(commands) => {
+                for command in commands {
+                    self.run(command);
+                }
+            }
+        }
+    }
+
+    // cancel the running commands, and ignore the commands run after this
+    pub(crate) fn cancel_all(&self) {
+        let mut inner = self.inner.lock();
+        inner.cancelled = true;
+        inner.keys.clear();
+        for (_, task) in inner.tasks.drain() {
+            task.abort();
+        }
+    }
+
+    fn spawn(&self, key: Option<String>, future: CommandFuture<T>) {
+        let mut inner = self.inner.lock();
+        if inner.cancelled {
+            return;
+        }
+
+        let id = inner.next_id;
+        inner.next_id += 1;
+        if let Some(key) = &key {
+            if let Some(previous) = inner.keys.insert(key.clone(), id) {
+                if let Some(task) = inner.tasks.remove(&previous) {
+                    task.abort();
+                }
+            }
+        }
+
+        let runner: Weak<Mutex<CommandRunnerInner<T>>> = Arc::downgrade(&self.inner);
+        let sender = inner.sender.clone();
+        let task = tokio::spawn(async move {
+            let message = future.await;
+            if let Some(runner) = runner.upgrade() {
+                let mut inner = runner.lock();
+                inner.tasks.remove(&id);
+                if let Some(key) = key {
+                    if inner.keys.get(&key) == Some(&id) {
+                        inner.keys.remove(&key);
+                    }
+                }
+            }
+            let _ = sender.send(message);
+        });
+        inner.tasks.insert(id, task);
+    }
+}
+impl<T: Message> Clone for CommandRunner<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+impl<T: Message> Debug for CommandRunner<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CommandRunner")
+            .field("tasks", &self.inner.lock().tasks.len())
+            .finish()
+    }
+}
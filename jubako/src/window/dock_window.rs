@@ -0,0 +1,1101 @@
+//! DockWindow and related types.
+//!
+//! [`DockWindow`] hosts several panels in a tree of horizontal/vertical splits and tab stacks
+//! described by [`DockLayout`]. Each panel is an independent
+//! [`SimpleWindow<Message = T>`](simple_window::SimpleWindow) created by its own
+//! [`SimpleWindowCreator<Message = T>`], so panels have their own messages, `update` and `view`.
+//!
+//! The user can rearrange the layout in the browser:
+//! - drag a tab onto another tab stack to dock it as a tab or onto an edge to split the stack
+//! - drag a tab onto another tab to reorder the tabs
+//! - drag the splitter between two areas to resize them
+//! - drop a tab outside of the dock slots to float the panel above the layout
+//!
+//! A drag that is cancelled, e.g. with Esc or by dropping outside of the window, leaves the layout
+//! unchanged.
+//!
+//! Floating panels are rendered in the portal layer. They follow the pointer while their title
+//! bar is dragged, are resized by the handles on their right and bottom edges, raised when
+//! clicked and can be minimized or closed. Dropping the title bar on a dock slot docks the panel
+//! again. The position and size of each floating panel is kept in [`DockWindowLayout`] by the server.
+//!
+//! When a [`DockLayoutStorage`] is given, the layout is saved every time the user finishes
+//! rearranging it and is restored when a new session of the route is created.
+//! Registered panels that are missing from the restored layout, because they were closed or
+//! added after the layout was saved, are docked as tabs of the first tab stack.
+//!
+//! Panels can show another panel again, e.g. a closed one, with
+//! [`SimpleWindowContext::open_panel`](crate::simple_window::SimpleWindowContext::open_panel).
+//!
+//! # Example
+
+use parking_lot::Mutex;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+    sync::Arc,
+};
+use tokio::sync::mpsc::unbounded_channel;
+
+use crate::simple_window::{self, SimpleWindowCallback, SimpleWindowCreator, SimpleWindowRunner};
+use crate::vnode::{
+    event, BundledEventHandler, MessageHandledVNode, MessageSender, Props, VNodeEventHandler,
+    VNodeEventHandlers,
+};
+use crate::{ConnectionState, DirtyFlag, Message, SessionHandle};
+
+mod layout;
+pub(crate) use layout::{DockDropZone, DockPath, DockResizeEdge};
+pub use layout::{
+    DockFloatingPanel, DockLayout, DockPanelId, DockSplitDirection, DockWindowLayout,
+};
+mod storage;
+pub use storage::{DockLayoutFileStorage, DockLayoutStorage};
+
+type DockPanelCreator =
+    Arc<dyn Fn(SessionHandle, String) -> Box<dyn SimpleWindowCallback> + Send + Sync>;
+
+#[derive(Clone)]
+struct DockPanel {
+    id: DockPanelId,
+    title: String,
+    creator: DockPanelCreator,
+}
+
+/// Window that docks panels in splits and tab stacks.
+#[derive(Clone)]
+pub struct DockWindow {
+    layout: DockWindowLayout,
+    panels: Vec<DockPanel>,
+    storage: Option<Arc<dyn DockLayoutStorage>>,
+}
+impl DockWindow {
+    /// Create a dock window with the initial layout.
+    pub fn new(layout: DockLayout) -> Self {
+        Self {
+            layout: DockWindowLayout {
+                docked: layout,
+                floating: vec![],
+            },
+            panels: vec![],
+            storage: None,
+        }
+    }
+
+    /// Save the layout to the storage and restore it in new sessions.
+    pub fn storage(mut self, storage: impl DockLayoutStorage) -> Self {
+        self.storage = Some(Arc::new(storage));
+        self
+    }
+
+    /// Float a panel above the docked panels in the initial layout.
+    ///
+    /// The panel is taken out of the docked layout if it appears there.
+    pub fn floating(mut self, floating: DockFloatingPanel) -> Self {
+        self.layout.insert_floating(floating);
+        self
+    }
+
+    /// Add a panel that is placed where `id` appears in the layout.
+    pub fn panel<T: Message>(
+        mut self,
+        id: impl Into<DockPanelId>,
+        title: impl Into<String>,
+        panel_creator: impl SimpleWindowCreator<Message = T>,
+    ) -> Self {
+        self.panels.push(DockPanel {
+            id: id.into(),
+            title: title.into(),
+            creator: Arc::new(move |session, panel| {
+                simple_window::create_window(panel_creator.clone(), session, Some(panel))
+            }),
+        });
+        self
+    }
+
+    // restore the saved layout or use the initial layout
+    fn load_layout(&self, key: &str) -> DockWindowLayout {
+        if let Some(storage) = &self.storage {
+            if let Ok(Some(mut layout)) = storage.load(key) {
+                layout.sanitize(&|id| self.panels.iter().any(|p| &p.id == id));
+                let registered: Vec<_> = self.panels.iter().map(|p| p.id.clone()).collect();
+                layout.restore_missing(&registered);
+                if !layout.is_empty() {
+                    return layout;
+                }
+            }
+        }
+        self.layout.clone()
+    }
+
+    // create panels of a new session and a runner that renders them in the dock layout,
+    // `key` identifies the route in the layout storage
+    pub(crate) fn create_runner(&self, key: &str) -> SimpleWindowRunner {
+        let session = SessionHandle::new();
+
+        let panels = self
+            .panels
+            .iter()
+            .map(|panel| {
+                let window = (panel.creator)(session.clone(), panel.id.as_str().to_string());
+                (
+                    panel.id.clone(),
+                    DockPanelInstance {
+                        title: panel.title.clone(),
+                        window,
+                    },
+                )
+            })
+            .collect();
+
+        let layout = self.load_layout(key);
+        let state = Arc::new(Mutex::new(DockState::new(layout.clone())));
+
+        let (sender, mut receiver) = unbounded_channel();
+        session.set_panel_opener({
+            // weak, so that the session does not keep the message loop running
+            let sender = sender.downgrade();
+            let registered: HashSet<_> = self.panels.iter().map(|p| p.id.clone()).collect();
+            move |panel| {
+                let panel = DockPanelId::new(panel);
+                if let (true, Some(sender)) = (registered.contains(&panel), sender.upgrade()) {
+                    let _ = sender.send(DockMessage::Open { panel });
+                }
+            }
+        });
+        tokio::spawn({
+            let state = state.clone();
+            let session = session.clone();
+            let storage = self.storage.clone();
+            let key = key.to_string();
+            async move {
+                let mut saved_layout = layout;
+                while let Some(message) = receiver.recv().await {
+                    if let DockMessage::PopOut { panel } = &message {
+                        session.open_window(Some(panel.as_str()));
+                        continue;
+                    }
+
+                    let changed_layout = {
+                        let mut state = state.lock();
+                        let should_render = state.update(message);
+                        if should_render == DirtyFlag::ShouldRender {
+                            session.request_render();
+                        }
+                        (storage.is_some()
+                            && !state.is_interacting()
+                            && state.layout != saved_layout)
+                            .then(|| state.layout.clone())
+                    };
+
+                    // save the layout when the user finishes rearranging it
+                    if let (Some(storage), Some(layout)) = (&storage, changed_layout) {
+                        // the storage may block, and failing to save must not break the session
+                        let saved = tokio::task::spawn_blocking({
+                            let storage = storage.clone();
+                            let key = key.clone();
+                            let layout = layout.clone();
+                            move || storage.save(&key, &layout)
+                        })
+                        .await;
+                        match saved {
+                            Ok(Ok(())) => {}
+                            Ok(Err(err)) => eprintln!("failed to save the dock layout: {err:#}"),
+                            Err(err) => eprintln!("failed to save the dock layout: {err}"),
+                        }
+                        saved_layout = layout;
+                    }
+                }
+            }
+        });
+
+        let window = Box::new(DockWindowCallback {
+            state,
+            panels,
+            sender: MessageSender::new(sender, || ()),
+        });
+        SimpleWindowRunner::with_window(window, session)
+    }
+}
+impl Debug for DockWindow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DockWindow")
+            .field("layout", &self.layout)
+            .field(
+                "panels",
+                &self.panels.iter().map(|p| &p.id).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+// messages of the dock layout interactions
+#[derive(Clone, Debug)]
+enum DockMessage {
+    Activate {
+        panel: DockPanelId,
+    },
+    DragStart {
+        panel: DockPanelId,
+        client_x: i32,
+        client_y: i32,
+    },
+    DragOver {
+        path: DockPath,
+        zone: DockDropZone,
+    },
+    Drop {
+        path: DockPath,
+        zone: DockDropZone,
+    },
+    Drag {
+        client_x: i32,
+        client_y: i32,
+    },
+    // dragged or dropped outside of the dock slots
+    DragOverFloat,
+    DropFloat {
+        client_x: i32,
+        client_y: i32,
+    },
+    DragEnd,
+    SplitterDown {
+        path: DockPath,
+    },
+    ResizeDown {
+        panel: DockPanelId,
+        edge: DockResizeEdge,
+    },
+    PointerMove {
+        movement_x: i32,
+        movement_y: i32,
+    },
+    PointerUp,
+    Resize {
+        path: DockPath,
+        width: u32,
+        height: u32,
+    },
+    Raise {
+        panel: DockPanelId,
+    },
+    ToggleMinimize {
+        panel: DockPanelId,
+    },
+    Close {
+        panel: DockPanelId,
+    },
+    PopOut {
+        panel: DockPanelId,
+    },
+    Open {
+        panel: DockPanelId,
+    },
+}
+
+// panel dragged by its tab or title bar
+#[derive(Clone, Debug)]
+struct DockPanelDrag {
+    panel: DockPanelId,
+    start_x: i32,
+    start_y: i32,
+    // position of the floating panel when the drag started, restored when the drag is cancelled
+    origin: Option<(i32, i32)>,
+}
+
+// what is dragged with the pointer
+#[derive(Clone, Debug)]
+enum DockPointerDrag {
+    Splitter(DockPath),
+    Resize(DockPanelId, DockResizeEdge),
+}
+
+// layout and the state of the ongoing interaction
+struct DockState {
+    layout: DockWindowLayout,
+    dragging_panel: Option<DockPanelDrag>,
+    drop_target: Option<(DockPath, DockDropZone)>,
+    pointer_drag: Option<DockPointerDrag>,
+    split_sizes: HashMap<DockPath, (u32, u32)>,
+    popped_out: HashSet<DockPanelId>,
+}
+impl DockState {
+    fn new(layout: DockWindowLayout) -> Self {
+        Self {
+            layout,
+            dragging_panel: None,
+            drop_target: None,
+            pointer_drag: None,
+            split_sizes: HashMap::new(),
+            popped_out: HashSet::new(),
+        }
+    }
+
+    fn is_interacting(&self) -> bool {
+        self.dragging_panel.is_some() || self.pointer_drag.is_some()
+    }
+
+    // move the dragged floating panel by the pointer movement since the drag started,
+    // returns false when the panel is not floating
+    fn move_floating(&mut self, drag: &DockPanelDrag, client_x: i32, client_y: i32) -> bool {
+        let (Some(floating), Some((x, y))) = (self.layout.floating_mut(&drag.panel), drag.origin)
+        else {
+            return false;
+        };
+        floating.x = x + client_x - drag.start_x;
+        floating.y = y + client_y - drag.start_y;
+        true
+    }
+
+    fn update(&mut self, message: DockMessage) -> DirtyFlag {
+        match message {
+            DockMessage::Activate { panel } => {
+                if self.layout.docked.activate(&panel) {
+                    DirtyFlag::ShouldRender
+                } else {
+                    DirtyFlag::None
+                }
+            }
+            DockMessage::DragStart {
+                panel,
+                client_x,
+                client_y,
+            } => {
+                let origin = self.layout.floating_mut(&panel).map(|f| (f.x, f.y));
+                self.dragging_panel = Some(DockPanelDrag {
+                    panel,
+                    start_x: client_x,
+                    start_y: client_y,
+                    origin,
+                });
+                self.drop_target = None;
+                DirtyFlag::ShouldRender
+            }
+            DockMessage::Drag { client_x, client_y } => {
+                // browsers report (0, 0) for the last drag event
+                if (client_x, client_y) == (0, 0) {
+                    return DirtyFlag::None;
+                }
+                if let Some(drag) = &self.dragging_panel {
+                    if self.move_floating(&drag.clone(), client_x, client_y) {
+                        return DirtyFlag::ShouldRender;
+                    }
+                }
+                DirtyFlag::None
+            }
+            DockMessage::DragOver { path, zone } => {
+                let drop_target = Some((path, zone));
+                if self.drop_target != drop_target {
+                    self.drop_target = drop_target;
+                    DirtyFlag::ShouldRender
+                } else {
+                    DirtyFlag::None
+                }
+            }
+            DockMessage::Drop { path, zone } => {
+                if let Some(drag) = self.dragging_panel.take() {
+                    self.layout.dock(&drag.panel, &path, zone);
+                }
+                self.drop_target = None;
+                DirtyFlag::ShouldRender
+            }
+            DockMessage::DragOverFloat => {
+                if self.drop_target.take().is_some() {
+                    DirtyFlag::ShouldRender
+                } else {
+                    DirtyFlag::None
+                }
+            }
+            DockMessage::DropFloat { client_x, client_y } => {
+                if let Some(drag) = self.dragging_panel.take() {
+                    if !self.move_floating(&drag, client_x, client_y) {
+                        self.layout.float(&drag.panel, client_x - 40, client_y - 12);
+                    }
+                    self.layout.raise(&drag.panel);
+                }
+                self.drop_target = None;
+                DirtyFlag::ShouldRender
+            }
+            DockMessage::DragEnd => {
+                // the drag is cancelled when it ends without a drop
+                if let Some(drag) = self.dragging_panel.take() {
+                    if let (Some(floating), Some((x, y))) =
+                        (self.layout.floating_mut(&drag.panel), drag.origin)
+                    {
+                        floating.x = x;
+                        floating.y = y;
+                    }
+                }
+                self.drop_target = None;
+                DirtyFlag::ShouldRender
+            }
+            DockMessage::SplitterDown { path } => {
+                self.pointer_drag = Some(DockPointerDrag::Splitter(path));
+                DirtyFlag::ShouldRender
+            }
+            DockMessage::ResizeDown { panel, edge } => {
+                self.layout.raise(&panel);
+                self.pointer_drag = Some(DockPointerDrag::Resize(panel, edge));
+                DirtyFlag::ShouldRender
+            }
+            DockMessage::PointerMove {
+                movement_x,
+                movement_y,
+            } => match &self.pointer_drag {
+                Some(DockPointerDrag::Splitter(path)) => {
+                    let Some(&(width, height)) = self.split_sizes.get(path) else {
+                        return DirtyFlag::None;
+                    };
+                    if let Some(DockLayout::Split {
+                        direction, ratio, ..
+                    }) = self.layout.docked.node(path)
+                    {
+                        let delta = match direction {
+                            DockSplitDirection::Horizontal => {
+                                movement_x as f64 / width.max(1) as f64
+                            }
+                            DockSplitDirection::Vertical => {
+                                movement_y as f64 / height.max(1) as f64
+                            }
+                        };
+                        let new_ratio = ratio + delta;
+                        self.layout.docked.set_ratio(&path.clone(), new_ratio);
+                        DirtyFlag::ShouldRender
+                    } else {
+                        DirtyFlag::None
+                    }
+                }
+                Some(DockPointerDrag::Resize(panel, edge)) => {
+                    let edge = *edge;
+                    if let Some(floating) = self.layout.floating_mut(&panel.clone()) {
+                        floating.resize(edge, movement_x, movement_y);
+                        DirtyFlag::ShouldRender
+                    } else {
+                        DirtyFlag::None
+                    }
+                }
+                None => DirtyFlag::None,
+            },
+            DockMessage::PointerUp => {
+                self.pointer_drag = None;
+                DirtyFlag::ShouldRender
+            }
+            DockMessage::Resize {
+                path,
+                width,
+                height,
+            } => {
+                self.split_sizes.insert(path, (width, height));
+                DirtyFlag::None
+            }
+            DockMessage::Raise { panel } => {
+                if self.layout.raise(&panel) {
+                    DirtyFlag::ShouldRender
+                } else {
+                    DirtyFlag::None
+                }
+            }
+            DockMessage::ToggleMinimize { panel } => {
+                if let Some(floating) = self.layout.floating_mut(&panel) {
+                    floating.minimized = !floating.minimized;
+                }
+                DirtyFlag::ShouldRender
+            }
+            DockMessage::Close { panel } => {
+                self.layout.close(&panel);
+                DirtyFlag::ShouldRender
+            }
+            DockMessage::Open { panel } => {
+                if self.layout.open(&panel) {
+                    DirtyFlag::ShouldRender
+                } else {
+                    DirtyFlag::None
+                }
+            }
+            // handled by the session
+            DockMessage::PopOut { .. } => DirtyFlag::None,
+        }
+    }
+}
+
+struct DockPanelInstance {
+    title: String,
+    window: Box<dyn SimpleWindowCallback>,
+}
+
+// renders the dock layout and the views of the panels in it
+struct DockWindowCallback {
+    state: Arc<Mutex<DockState>>,
+    panels: HashMap<DockPanelId, DockPanelInstance>,
+    // dock messages request renders in the message loop
+    sender: MessageSender<DockMessage>,
+}
+impl DockWindowCallback {
+    fn element(
+        &self,
+        tag: &str,
+        style: &str,
+        props: Props,
+        event: VNodeEventHandlers<DockMessage>,
+        children: Vec<MessageHandledVNode>,
+    ) -> MessageHandledVNode {
+        MessageHandledVNode::Element {
+            tag: tag.into(),
+            class: vec![],
+            props,
+            event: BundledEventHandler::bundle(event, self.sender.clone()),
+            children,
+            style: Some(style.into()),
+            key: None,
+            namespace: None,
+        }
+    }
+
+    fn view_layout(
+        &self,
+        state: &DockState,
+        layout: &DockLayout,
+        path: DockPath,
+    ) -> MessageHandledVNode {
+        match layout {
+            DockLayout::Split {
+                direction,
+                ratio,
+                first,
+                second,
+            } => {
+                let (container_style, splitter_style) = match direction {
+                    DockSplitDirection::Horizontal => {
+                        ("flex-direction: row;", "flex: 0 0 4px; cursor: col-resize;")
+                    }
+                    DockSplitDirection::Vertical => (
+                        "flex-direction: column;",
+                        "flex: 0 0 4px; cursor: row-resize;",
+                    ),
+                };
+
+                let mut first_path = path.clone();
+                first_path.push(0);
+                let mut second_path = path.clone();
+                second_path.push(1);
+
+                let first = self.element(
+                    "div",
+                    &format!("display: flex; flex: {ratio} 1 0; min-width: 0; min-height: 0; overflow: hidden;"),
+                    Props::new(),
+                    Default::default(),
+                    vec![self.view_layout(state, first, first_path)],
+                );
+                let splitter = self.element(
+                    "div",
+                    &format!(
+                        "{splitter_style} background: #d0d0d0; &:hover {{ background: #a0a0a0; }}"
+                    ),
+                    Props::new(),
+                    VNodeEventHandlers {
+                        pointer_down: VNodeEventHandler::handle_prevent_default({
+                            let path = path.clone();
+                            move |_| DockMessage::SplitterDown { path: path.clone() }
+                        }),
+                        ..Default::default()
+                    },
+                    vec![],
+                );
+                let second = self.element(
+                    "div",
+                    &format!(
+                        "display: flex; flex: {} 1 0; min-width: 0; min-height: 0; overflow: hidden;",
+                        1.0 - ratio
+                    ),
+                    Props::new(),
+                    Default::default(),
+                    vec![self.view_layout(state, second, second_path)],
+                );
+
+                self.element(
+                    "div",
+                    &format!("display: flex; {container_style} width: 100%; height: 100%;"),
+                    Props::new(),
+                    VNodeEventHandlers {
+                        resize: VNodeEventHandler::handle({
+                            let path = path.clone();
+                            move |evt: event::Event| {
+                                let target = evt.target.unwrap_or_default();
+                                DockMessage::Resize {
+                                    path: path.clone(),
+                                    width: target.client_width,
+                                    height: target.client_height,
+                                }
+                            }
+                        }),
+                        ..Default::default()
+                    },
+                    vec![first, splitter, second],
+                )
+            }
+            DockLayout::Tabs { panels, active } => {
+                let tabs = panels
+                    .iter()
+                    .enumerate()
+                    .map(|(index, panel)| {
+                        let title = self
+                            .panels
+                            .get(panel)
+                            .map(|p| p.title.clone())
+                            .unwrap_or_else(|| panel.as_str().to_string());
+                        let hovered = state.drop_target.as_ref()
+                            == Some(&(path.clone(), DockDropZone::Tab(index)));
+                        let style = if index == *active {
+                            "padding: 4px 12px; white-space: nowrap; cursor: pointer; background: #ffffff;"
+                        } else {
+                            "padding: 4px 12px; white-space: nowrap; cursor: pointer; color: #606060;"
+                        };
+                        let style = if hovered {
+                            format!("{style} box-shadow: inset 2px 0 0 #0078d7;")
+                        } else {
+                            style.to_string()
+                        };
+                        self.element(
+                            "div",
+                            &style,
+                            Props::new().attribute("draggable", "true"),
+                            VNodeEventHandlers {
+                                click: VNodeEventHandler::handle({
+                                    let panel = panel.clone();
+                                    move |_| DockMessage::Activate {
+                                        panel: panel.clone(),
+                                    }
+                                }),
+                                drag_start: self.drag_start_handler(panel),
+                                drag_over: VNodeEventHandler::handle_prevent_default({
+                                    let path = path.clone();
+                                    move |_| DockMessage::DragOver {
+                                        path: path.clone(),
+                                        zone: DockDropZone::Tab(index),
+                                    }
+                                })
+                                .stop_propagation(),
+                                drop: VNodeEventHandler::handle_prevent_default({
+                                    let path = path.clone();
+                                    move |_| DockMessage::Drop {
+                                        path: path.clone(),
+                                        zone: DockDropZone::Tab(index),
+                                    }
+                                })
+                                .stop_propagation(),
+                                drag_end: self.drag_end_handler(),
+                                ..Default::default()
+                            },
+                            vec![MessageHandledVNode::Text { text: title }],
+                        )
+                    })
+                    .collect();
+                let header = self.element(
+                    "div",
+                    "display: flex; flex: 0 0 auto; overflow-x: auto; background: #e8e8e8; font-size: 13px;",
+                    Props::new(),
+                    VNodeEventHandlers {
+                        drag_over: VNodeEventHandler::handle_prevent_default({
+                            let path = path.clone();
+                            let len = panels.len();
+                            move |_| DockMessage::DragOver {
+                                path: path.clone(),
+                                zone: DockDropZone::Tab(len),
+                            }
+                        })
+                        .stop_propagation(),
+                        drop: VNodeEventHandler::handle_prevent_default({
+                            let path = path.clone();
+                            let len = panels.len();
+                            move |_| DockMessage::Drop {
+                                path: path.clone(),
+                                zone: DockDropZone::Tab(len),
+                            }
+                        })
+                        .stop_propagation(),
+                        ..Default::default()
+                    },
+                    tabs,
+                );
+
+                let mut body_children = vec![];
+                if let Some(view) = panels
+                    .get(*active)
+                    .and_then(|id| self.view_panel_body(state, id))
+                {
+                    body_children.push(view);
+                }
+                if state.dragging_panel.is_some() {
+                    body_children.push(self.view_drop_zones(state, &path));
+                }
+                let body = self.element(
+                    "div",
+                    "position: relative; flex: 1 1 0; min-height: 0; overflow: auto;",
+                    Props::new(),
+                    Default::default(),
+                    body_children,
+                );
+
+                self.element(
+                    "div",
+                    "display: flex; flex-direction: column; width: 100%; height: 100%;",
+                    Props::new(),
+                    Default::default(),
+                    vec![header, body],
+                )
+            }
+        }
+    }
+
+    // view of the panel, or a placeholder while the panel is popped out
+    fn view_panel_body(&self, state: &DockState, id: &DockPanelId) -> Option<MessageHandledVNode> {
+        let panel = self.panels.get(id)?;
+        if state.popped_out.contains(id) {
+            Some(self.element(
+                "div",
+                "display: grid; place-items: center; width: 100%; height: 100%; color: #808080; font-size: 13px;",
+                Props::new(),
+                Default::default(),
+                vec![MessageHandledVNode::Text {
+                    text: "Opened in another window".into(),
+                }],
+            ))
+        } else {
+            Some(panel.window.view())
+        }
+    }
+
+    // areas that a dragged tab can be dropped on to dock it,
+    // the area between them floats the panel
+    fn view_drop_zones(&self, state: &DockState, path: &DockPath) -> MessageHandledVNode {
+        let zones = [
+            (
+                DockDropZone::Left,
+                "left: 0; top: 25%; width: 20%; height: 50%;",
+            ),
+            (
+                DockDropZone::Right,
+                "left: 80%; top: 25%; width: 20%; height: 50%;",
+            ),
+            (
+                DockDropZone::Top,
+                "left: 25%; top: 0; width: 50%; height: 20%;",
+            ),
+            (
+                DockDropZone::Bottom,
+                "left: 25%; top: 80%; width: 50%; height: 20%;",
+            ),
+            (
+                DockDropZone::Center,
+                "left: 30%; top: 30%; width: 40%; height: 40%;",
+            ),
+        ];
+        let zones = zones
+            .into_iter()
+            .map(|(zone, rect)| {
+                let hovered = state.drop_target.as_ref() == Some(&(path.clone(), zone));
+                let background = if hovered {
+                    "background: rgba(0, 120, 215, 0.3);"
+                } else {
+                    "background: rgba(0, 120, 215, 0.08);"
+                };
+                self.element(
+                    "div",
+                    &format!("position: absolute; {rect} {background}"),
+                    Props::new(),
+                    VNodeEventHandlers {
+                        drag_over: VNodeEventHandler::handle_prevent_default({
+                            let path = path.clone();
+                            move |_| DockMessage::DragOver {
+                                path: path.clone(),
+                                zone,
+                            }
+                        })
+                        .stop_propagation(),
+                        drop: VNodeEventHandler::handle_prevent_default({
+                            let path = path.clone();
+                            move |_| DockMessage::Drop {
+                                path: path.clone(),
+                                zone,
+                            }
+                        })
+                        .stop_propagation(),
+                        ..Default::default()
+                    },
+                    vec![],
+                )
+            })
+            .collect();
+
+        self.element(
+            "div",
+            "position: absolute; inset: 0;",
+            Props::new(),
+            Default::default(),
+            zones,
+        )
+    }
+
+    fn drag_start_handler(
+        &self,
+        panel: &DockPanelId,
+    ) -> VNodeEventHandler<event::DragEvent, DockMessage> {
+        let panel = panel.clone();
+        VNodeEventHandler::handle(move |evt: event::DragEvent| DockMessage::DragStart {
+            panel: panel.clone(),
+            client_x: evt.client_x,
+            client_y: evt.client_y,
+        })
+    }
+
+    fn drag_end_handler(&self) -> VNodeEventHandler<event::DragEvent, DockMessage> {
+        VNodeEventHandler::handle(|_| DockMessage::DragEnd)
+    }
+
+    // panel floating above the dock layout with a title bar and resize handles
+    fn view_floating(
+        &self,
+        state: &DockState,
+        floating: &DockFloatingPanel,
+    ) -> MessageHandledVNode {
+        let DockFloatingPanel {
+            panel: id,
+            x,
+            y,
+            width,
+            height,
+            minimized,
+            z_index,
+        } = floating;
+        let panel = self.panels.get(id);
+        let title = panel
+            .map(|p| p.title.clone())
+            .unwrap_or_else(|| id.as_str().to_string());
+
+        let button = |text: &str, message: DockMessage| {
+            self.element(
+                "div",
+                "width: 20px; text-align: center; cursor: pointer; &:hover { background: #d0d0d0; }",
+                Props::new(),
+                VNodeEventHandlers {
+                    click: VNodeEventHandler::handle(move |_| message.clone()),
+                    ..Default::default()
+                },
+                vec![MessageHandledVNode::Text { text: text.into() }],
+            )
+        };
+        let title_bar = self.element(
+            "div",
+            "display: flex; flex: 0 0 24px; align-items: center; padding-left: 8px; background: #e8e8e8; font-size: 13px; cursor: move;",
+            Props::new().attribute("draggable", "true"),
+            VNodeEventHandlers {
+                drag_start: self.drag_start_handler(id),
+                drag: VNodeEventHandler::handle(|evt: event::DragEvent| DockMessage::Drag {
+                    client_x: evt.client_x,
+                    client_y: evt.client_y,
+                })
+                .coalesce(),
+                drag_end: self.drag_end_handler(),
+                ..Default::default()
+            },
+            vec![
+                self.element(
+                    "div",
+                    "flex: 1 1 0; overflow: hidden; white-space: nowrap; text-overflow: ellipsis;",
+                    Props::new(),
+                    Default::default(),
+                    vec![MessageHandledVNode::Text { text: title }],
+                ),
+                button(
+                    if *minimized { "□" } else { "_" },
+                    DockMessage::ToggleMinimize { panel: id.clone() },
+                ),
+                button("⧉", DockMessage::PopOut { panel: id.clone() }),
+                button("×", DockMessage::Close { panel: id.clone() }),
+            ],
+        );
+
+        let mut children = vec![title_bar];
+        if !minimized {
+            let mut body_children = vec![];
+            if let Some(view) = self.view_panel_body(state, id) {
+                body_children.push(view);
+            }
+            children.push(self.element(
+                "div",
+                "position: relative; flex: 1 1 0; min-height: 0; overflow: auto;",
+                Props::new(),
+                Default::default(),
+                body_children,
+            ));
+
+            let handles = [
+                (
+                    DockResizeEdge::Right,
+                    "right: -3px; top: 0; width: 6px; height: 100%; cursor: ew-resize;",
+                ),
+                (
+                    DockResizeEdge::Bottom,
+                    "left: 0; bottom: -3px; width: 100%; height: 6px; cursor: ns-resize;",
+                ),
+                (
+                    DockResizeEdge::BottomRight,
+                    "right: -4px; bottom: -4px; width: 10px; height: 10px; cursor: nwse-resize;",
+                ),
+            ];
+            for (edge, rect) in handles {
+                children.push(self.element(
+                    "div",
+                    &format!("position: absolute; {rect}"),
+                    Props::new(),
+                    VNodeEventHandlers {
+                        pointer_down: VNodeEventHandler::handle_prevent_default({
+                            let id = id.clone();
+                            move |_| DockMessage::ResizeDown {
+                                panel: id.clone(),
+                                edge,
+                            }
+                        }),
+                        ..Default::default()
+                    },
+                    vec![],
+                ));
+            }
+        }
+
+        // let dragged panels pass through floating panels to reach the dock slots under them
+        let pointer_events = if state.dragging_panel.is_some() {
+            "none"
+        } else {
+            "auto"
+        };
+        let height = if *minimized {
+            "auto".to_string()
+        } else {
+            format!("{height}px")
+        };
+        self.element(
+            "div",
+            &format!(
+                "position: absolute; left: {x}px; top: {y}px; width: {width}px; height: {height}; z-index: {z_index}; \
+                 display: flex; flex-direction: column; background: #ffffff; border: 1px solid #a0a0a0; \
+                 box-shadow: 0 4px 16px rgba(0, 0, 0, 0.25); pointer-events: {pointer_events};"
+            ),
+            Props::new(),
+            VNodeEventHandlers {
+                pointer_down: VNodeEventHandler::handle({
+                    let id = id.clone();
+                    move |_| DockMessage::Raise { panel: id.clone() }
+                }),
+                ..Default::default()
+            },
+            children,
+        )
+    }
+
+    // transparent overlay that tracks the pointer while a splitter or a resize handle is dragged
+    fn view_pointer_overlay(&self, drag: &DockPointerDrag) -> MessageHandledVNode {
+        let cursor = match drag {
+            DockPointerDrag::Splitter(_) => "grabbing",
+            DockPointerDrag::Resize(_, DockResizeEdge::Right) => "ew-resize",
+            DockPointerDrag::Resize(_, DockResizeEdge::Bottom) => "ns-resize",
+            DockPointerDrag::Resize(_, DockResizeEdge::BottomRight) => "nwse-resize",
+        };
+        let overlay = self.element(
+            "div",
+            &format!(
+                "position: fixed; inset: 0; z-index: 2147483647; pointer-events: auto; cursor: {cursor};"
+            ),
+            Props::new(),
+            VNodeEventHandlers {
+                pointer_move: VNodeEventHandler::handle_prevent_default(
+                    |evt: event::PointerEvent| DockMessage::PointerMove {
+                        movement_x: evt.movement_x,
+                        movement_y: evt.movement_y,
+                    },
+                ),
+                pointer_up: VNodeEventHandler::handle(|_| DockMessage::PointerUp),
+                pointer_leave: VNodeEventHandler::handle(|_| DockMessage::PointerUp),
+                ..Default::default()
+            },
+            vec![],
+        );
+        MessageHandledVNode::Portal {
+            children: vec![overlay],
+        }
+    }
+}
+impl SimpleWindowCallback for DockWindowCallback {
+    fn view(&self) -> MessageHandledVNode {
+        let state = self.state.lock();
+
+        let mut children = vec![self.view_layout(&state, &state.layout.docked, vec![])];
+        children.push(MessageHandledVNode::Portal {
+            children: state
+                .layout
+                .floating
+                .iter()
+                .map(|floating| self.view_floating(&state, floating))
+                .collect(),
+        });
+        if let Some(drag) = &state.pointer_drag {
+            children.push(self.view_pointer_overlay(drag));
+        }
+
+        // a panel dropped outside of the dock slots floats where it is dropped
+        let event = if state.dragging_panel.is_some() {
+            VNodeEventHandlers {
+                drag_over: VNodeEventHandler::handle_prevent_default(|_| {
+                    DockMessage::DragOverFloat
+                }),
+                drop: VNodeEventHandler::handle_prevent_default(|evt: event::DragEvent| {
+                    DockMessage::DropFloat {
+                        client_x: evt.client_x,
+                        client_y: evt.client_y,
+                    }
+                }),
+                ..Default::default()
+            }
+        } else {
+            Default::default()
+        };
+        self.element(
+            "div",
+            "display: flex; width: 100%; height: 100%; overflow: hidden;",
+            Props::new(),
+            event,
+            children,
+        )
+    }
+
+    fn view_panel(&self, panel: &str) -> Option<MessageHandledVNode> {
+        let panel = self.panels.get(&DockPanelId::new(panel))?;
+        Some(self.element(
+            "div",
+            "width: 100%; height: 100%; overflow: auto;",
+            Props::new(),
+            Default::default(),
+            vec![panel.window.view()],
+        ))
+    }
+
+    fn set_popped_out(&self, panel: &str, popped_out: bool) {
+        let mut state = self.state.lock();
+        if popped_out {
+            state.popped_out.insert(DockPanelId::new(panel));
+        } else {
+            state.popped_out.remove(&DockPanelId::new(panel));
+        }
+    }
+
+    fn disconnected(&self) {
+        for panel in self.panels.values() {
+            panel.window.disconnected();
+        }
+    }
+    fn connection_changed(&self, state: ConnectionState) {
+        for panel in self.panels.values() {
+            panel.window.connection_changed(state);
+        }
+    }
+}
@@ -0,0 +1,723 @@
+//! Dock layout tree and the operations applied to it by docking interactions.
+
+/// Identifier of a panel docked in a [`DockWindow`](super::DockWindow).
+#[derive(
+    serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord,
+)]
+pub struct DockPanelId(String);
+impl DockPanelId {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self(id.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+impl From<&str> for DockPanelId {
+    fn from(id: &str) -> Self {
+        Self::new(id)
+    }
+}
+impl From<String> for DockPanelId {
+    fn from(id: String) -> Self {
+        Self::new(id)
+    }
+}
+
+/// Direction in which a split divides its area.
+///
+/// `Horizontal` places the children side by side, `Vertical` stacks them top to bottom.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DockSplitDirection {
+    Horizontal,
+    Vertical,
+}
+
+/// Tree of splits and tab stacks that describes where each panel is docked.
+///
+/// The layout is serializable so that it can be saved by a
+/// [`DockLayoutStorage`](super::DockLayoutStorage) and restored in a later session.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", content = "content")]
+pub enum DockLayout {
+    /// Area divided into two children. `ratio` is the share of the first child.
+    Split {
+        direction: DockSplitDirection,
+        ratio: f64,
+        first: Box<DockLayout>,
+        second: Box<DockLayout>,
+    },
+    /// Stack of panels shown as tabs. `active` is the index of the visible panel.
+    Tabs {
+        panels: Vec<DockPanelId>,
+        active: usize,
+    },
+}
+impl DockLayout {
+    /// Create a tab stack of the given panels with the first one active.
+    pub fn tabs<I>(panels: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<DockPanelId>,
+    {
+        Self::Tabs {
+            panels: panels.into_iter().map(Into::into).collect(),
+            active: 0,
+        }
+    }
+
+    /// Create a split that places `first` on the left and `second` on the right.
+    pub fn horizontal(ratio: f64, first: DockLayout, second: DockLayout) -> Self {
+        Self::Split {
+            direction: DockSplitDirection::Horizontal,
+            ratio: ratio.clamp(MIN_RATIO, MAX_RATIO),
+            first: Box::new(first),
+            second: Box::new(second),
+        }
+    }
+
+    /// Create a split that places `first` above `second`.
+    pub fn vertical(ratio: f64, first: DockLayout, second: DockLayout) -> Self {
+        Self::Split {
+            direction: DockSplitDirection::Vertical,
+            ratio: ratio.clamp(MIN_RATIO, MAX_RATIO),
+            first: Box::new(first),
+            second: Box::new(second),
+        }
+    }
+
+    /// All panels in the layout, in tree order.
+    pub fn panels(&self) -> Vec<&DockPanelId> {
+        match self {
+            Self::Split { first, second, .. } => {
+                let mut panels = first.panels();
+                panels.append(&mut second.panels());
+                panels
+            }
+            Self::Tabs { panels, .. } => panels.iter().collect(),
+        }
+    }
+}
+
+const MIN_RATIO: f64 = 0.05;
+const MAX_RATIO: f64 = 0.95;
+
+// path from the root to a node, 0 for the first child of a split and 1 for the second
+pub(crate) type DockPath = Vec<u8>;
+
+// where a dragged panel is dropped in a tab stack
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum DockDropZone {
+    Center,
+    Left,
+    Right,
+    Top,
+    Bottom,
+    Tab(usize),
+}
+
+impl DockLayout {
+    pub(crate) fn node(&self, path: &[u8]) -> Option<&DockLayout> {
+        match (path.split_first(), self) {
+            (None, _) => Some(self),
+            (Some((0, rest)), Self::Split { first, .. }) => first.node(rest),
+            (Some((1, rest)), Self::Split { second, .. }) => second.node(rest),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn node_mut(&mut self, path: &[u8]) -> Option<&mut DockLayout> {
+        match (path.split_first(), self) {
+            (None, node) => Some(node),
+            (Some((0, rest)), Self::Split { first, .. }) => first.node_mut(rest),
+            (Some((1, rest)), Self::Split { second, .. }) => second.node_mut(rest),
+            _ => None,
+        }
+    }
+
+    // find the tab stack that contains the panel and the index of the panel in it
+    pub(crate) fn find_panel(&self, panel: &DockPanelId) -> Option<(DockPath, usize)> {
+        match self {
+            Self::Split { first, second, .. } => {
+                if let Some((mut path, index)) = first.find_panel(panel) {
+                    path.insert(0, 0);
+                    Some((path, index))
+                } else if let Some((mut path, index)) = second.find_panel(panel) {
+                    path.insert(0, 1);
+                    Some((path, index))
+                } else {
+                    None
+                }
+            }
+            Self::Tabs { panels, .. } => panels
+                .iter()
+                .position(|p| p == panel)
+                .map(|index| (vec![], index)),
+        }
+    }
+
+    // make the panel visible in its tab stack
+    pub(crate) fn activate(&mut self, panel: &DockPanelId) -> bool {
+        if let Some((path, index)) = self.find_panel(panel) {
+            if let Some(Self::Tabs { active, .. }) = self.node_mut(&path) {
+                if *active != index {
+                    *active = index;
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    pub(crate) fn set_ratio(&mut self, path: &[u8], new_ratio: f64) {
+        if let Some(Self::Split { ratio, .. }) = self.node_mut(path) {
+            *ratio = new_ratio.clamp(MIN_RATIO, MAX_RATIO);
+        }
+    }
+
+    // move the panel into the tab stack at `target`, splitting the stack when dropped on an edge
+    pub(crate) fn move_panel(&mut self, panel: &DockPanelId, target: &[u8], zone: DockDropZone) {
+        let Some((source, index)) = self.find_panel(panel) else {
+            return;
+        };
+        let target_len = match self.node(target) {
+            Some(Self::Tabs { panels, .. }) => panels.len(),
+            _ => return,
+        };
+
+        if source == target {
+            match zone {
+                DockDropZone::Center => {
+                    self.activate(panel);
+                    return;
+                }
+                DockDropZone::Tab(to) => {
+                    // reorder tabs in the same stack
+                    if let Some(Self::Tabs { panels, active }) = self.node_mut(target) {
+                        let moved = panels.remove(index);
+                        let to = if to > index { to - 1 } else { to }.min(panels.len());
+                        panels.insert(to, moved);
+                        *active = to;
+                    }
+                    return;
+                }
+                _ if target_len == 1 => {
+                    // a panel cannot be split with itself
+                    return;
+                }
+                _ => (),
+            }
+        }
+
+        // detach without collapsing the tree so that `target` stays valid
+        self.detach(&source, index);
+        self.attach(panel, target, zone);
+        self.normalize();
+    }
+
+    // dock the panel that is not in the tree into the tab stack at `target`
+    pub(crate) fn insert_panel(&mut self, panel: &DockPanelId, target: &[u8], zone: DockDropZone) {
+        if self.find_panel(panel).is_some() || !matches!(self.node(target), Some(Self::Tabs { .. }))
+        {
+            return;
+        }
+        self.attach(panel, target, zone);
+        self.normalize();
+    }
+
+    // remove the panel from the tree
+    pub(crate) fn remove_panel(&mut self, panel: &DockPanelId) -> bool {
+        if let Some((source, index)) = self.find_panel(panel) {
+            self.detach(&source, index);
+            self.normalize();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn detach(&mut self, source: &[u8], index: usize) {
+        if let Some(Self::Tabs { panels, active }) = self.node_mut(source) {
+            panels.remove(index);
+            if *active > index || *active >= panels.len() {
+                *active = active.saturating_sub(1);
+            }
+        }
+    }
+
+    fn attach(&mut self, panel: &DockPanelId, target: &[u8], zone: DockDropZone) {
+        if let Some(node) = self.node_mut(target) {
+            match zone {
+                DockDropZone::Center | DockDropZone::Tab(_) => {
+                    if let Self::Tabs { panels, active } = node {
+                        let to = match zone {
+                            DockDropZone::Tab(to) => to.min(panels.len()),
+                            _ => panels.len(),
+                        };
+                        panels.insert(to, panel.clone());
+                        *active = to;
+                    }
+                }
+                DockDropZone::Left
+                | DockDropZone::Right
+                | DockDropZone::Top
+                | DockDropZone::Bottom => {
+                    let direction = match zone {
+                        DockDropZone::Left | DockDropZone::Right => DockSplitDirection::Horizontal,
+                        _ => DockSplitDirection::Vertical,
+                    };
+                    let new_tabs = Box::new(Self::tabs([panel.clone()]));
+                    let old = Box::new(std::mem::replace(node, Self::empty()));
+                    let (first, second) = match zone {
+                        DockDropZone::Left | DockDropZone::Top => (new_tabs, old),
+                        _ => (old, new_tabs),
+                    };
+                    *node = Self::Split {
+                        direction,
+                        ratio: 0.5,
+                        first,
+                        second,
+                    };
+                }
+            }
+        }
+    }
+
+    // path of the first tab stack in tree order
+    fn first_tabs(&self) -> DockPath {
+        match self {
+            Self::Split { first, .. } => {
+                let mut path = first.first_tabs();
+                path.insert(0, 0);
+                path
+            }
+            Self::Tabs { .. } => vec![],
+        }
+    }
+
+    // remove empty tab stacks by collapsing the splits that contain them
+    pub(crate) fn normalize(&mut self) {
+        if let Self::Split { first, second, .. } = self {
+            first.normalize();
+            second.normalize();
+            if first.is_empty() {
+                *self = std::mem::replace(second, Self::empty());
+            } else if second.is_empty() {
+                *self = std::mem::replace(first, Self::empty());
+            }
+        }
+    }
+
+    // drop unknown or duplicated panels and fix out of range values, used for restored layouts
+    pub(crate) fn sanitize(&mut self, is_known: &dyn Fn(&DockPanelId) -> bool) {
+        fn traverse(
+            layout: &mut DockLayout,
+            is_known: &dyn Fn(&DockPanelId) -> bool,
+            seen: &mut Vec<DockPanelId>,
+        ) {
+            match layout {
+                DockLayout::Split {
+                    ratio,
+                    first,
+                    second,
+                    ..
+                } => {
+                    *ratio = if ratio.is_finite() {
+                        ratio.clamp(MIN_RATIO, MAX_RATIO)
+                    } else {
+                        0.5
+                    };
+                    traverse(first, is_known, seen);
+                    traverse(second, is_known, seen);
+                }
+                DockLayout::Tabs { panels, active } => {
+                    panels.retain(|panel| {
+                        if is_known(panel) && !seen.contains(panel) {
+                            seen.push(panel.clone());
+                            true
+                        } else {
+                            false
+                        }
+                    });
+                    *active = (*active).min(panels.len().saturating_sub(1));
+                }
+            }
+        }
+        traverse(self, is_known, &mut vec![]);
+        self.normalize();
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        matches!(self, Self::Tabs { panels, .. } if panels.is_empty())
+    }
+
+    fn empty() -> Self {
+        Self::Tabs {
+            panels: vec![],
+            active: 0,
+        }
+    }
+}
+
+const MIN_FLOATING_WIDTH: u32 = 160;
+const MIN_FLOATING_HEIGHT: u32 = 80;
+
+/// Panel floating above the docked panels.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DockFloatingPanel {
+    pub panel: DockPanelId,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub minimized: bool,
+    /// Stacking order among floating panels, larger is drawn above.
+    pub z_index: u32,
+}
+impl DockFloatingPanel {
+    /// Create a floating panel at (`x`, `y`) with the given size.
+    pub fn new(panel: impl Into<DockPanelId>, x: i32, y: i32, width: u32, height: u32) -> Self {
+        Self {
+            panel: panel.into(),
+            x,
+            y,
+            width: width.max(MIN_FLOATING_WIDTH),
+            height: height.max(MIN_FLOATING_HEIGHT),
+            minimized: false,
+            z_index: 0,
+        }
+    }
+
+    // move the edges of the panel by the pointer movement
+    pub(crate) fn resize(&mut self, edge: DockResizeEdge, movement_x: i32, movement_y: i32) {
+        if matches!(edge, DockResizeEdge::Right | DockResizeEdge::BottomRight) {
+            self.width = (self.width as i32 + movement_x).max(MIN_FLOATING_WIDTH as i32) as u32;
+        }
+        if matches!(edge, DockResizeEdge::Bottom | DockResizeEdge::BottomRight) {
+            self.height = (self.height as i32 + movement_y).max(MIN_FLOATING_HEIGHT as i32) as u32;
+        }
+    }
+}
+
+// edge of a floating panel that is dragged to resize it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum DockResizeEdge {
+    Right,
+    Bottom,
+    BottomRight,
+}
+
+/// Layout of a whole dock window, the docked panels and the floating panels.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct DockWindowLayout {
+    pub docked: DockLayout,
+    pub floating: Vec<DockFloatingPanel>,
+}
+impl DockWindowLayout {
+    pub(crate) fn floating_mut(&mut self, panel: &DockPanelId) -> Option<&mut DockFloatingPanel> {
+        self.floating.iter_mut().find(|f| &f.panel == panel)
+    }
+
+    fn is_floating(&self, panel: &DockPanelId) -> bool {
+        self.floating.iter().any(|f| &f.panel == panel)
+    }
+
+    // dock the panel into the tab stack at `target`, wherever it currently is
+    pub(crate) fn dock(&mut self, panel: &DockPanelId, target: &[u8], zone: DockDropZone) {
+        if self.is_floating(panel) {
+            if !matches!(self.docked.node(target), Some(DockLayout::Tabs { .. })) {
+                return;
+            }
+            self.floating.retain(|f| &f.panel != panel);
+            self.docked.insert_panel(panel, target, zone);
+        } else {
+            self.docked.move_panel(panel, target, zone);
+        }
+    }
+
+    // undock the panel and float it at (`x`, `y`) above the other floating panels
+    pub(crate) fn float(&mut self, panel: &DockPanelId, x: i32, y: i32) {
+        if self.docked.remove_panel(panel) {
+            let mut floating = DockFloatingPanel::new(panel.clone(), x, y, 400, 300);
+            floating.z_index = self.top_z_index() + 1;
+            self.floating.push(floating);
+        }
+    }
+
+    // float the panel at the front, it is taken out of the docked layout if it appears there
+    pub(crate) fn insert_floating(&mut self, mut floating: DockFloatingPanel) {
+        self.docked.remove_panel(&floating.panel);
+        self.floating.retain(|f| f.panel != floating.panel);
+        floating.z_index = self.top_z_index() + 1;
+        self.floating.push(floating);
+    }
+
+    // bring the floating panel to the front, returns false when it is already there
+    pub(crate) fn raise(&mut self, panel: &DockPanelId) -> bool {
+        let top = self.top_z_index();
+        let unique_top = self.floating.iter().filter(|f| f.z_index == top).count() == 1;
+        match self.floating_mut(panel) {
+            Some(floating) if floating.z_index == top && unique_top => false,
+            Some(floating) => {
+                floating.z_index = top + 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // remove the panel from the window
+    pub(crate) fn close(&mut self, panel: &DockPanelId) {
+        self.floating.retain(|f| &f.panel != panel);
+        self.docked.remove_panel(panel);
+    }
+
+    // show the panel: activate its tab, restore and raise it when it is floating, or dock it
+    // as the active tab of the first tab stack when it is closed.
+    // returns false when nothing changed
+    pub(crate) fn open(&mut self, panel: &DockPanelId) -> bool {
+        if let Some(floating) = self.floating_mut(panel) {
+            let minimized = std::mem::replace(&mut floating.minimized, false);
+            return self.raise(panel) || minimized;
+        }
+        if self.docked.find_panel(panel).is_some() {
+            return self.docked.activate(panel);
+        }
+        let target = self.docked.first_tabs();
+        self.docked
+            .insert_panel(panel, &target, DockDropZone::Center);
+        true
+    }
+
+    // dock the panels that are missing from the layout as tabs of the first tab stack,
+    // e.g. panels added after the layout was saved or closed before it was saved
+    pub(crate) fn restore_missing(&mut self, panels: &[DockPanelId]) {
+        let target = self.docked.first_tabs();
+        for panel in panels {
+            if self.docked.find_panel(panel).is_some() || self.is_floating(panel) {
+                continue;
+            }
+            if let Some(DockLayout::Tabs { panels, .. }) = self.docked.node_mut(&target) {
+                panels.push(panel.clone());
+            }
+        }
+    }
+
+    fn top_z_index(&self) -> u32 {
+        self.floating.iter().map(|f| f.z_index).max().unwrap_or(0)
+    }
+
+    // drop unknown or duplicated panels and fix out of range values, used for restored layouts
+    pub(crate) fn sanitize(&mut self, is_known: &dyn Fn(&DockPanelId) -> bool) {
+        self.docked.sanitize(is_known);
+        let mut seen: Vec<DockPanelId> = self.docked.panels().into_iter().cloned().collect();
+        self.floating.retain(|f| {
+            if is_known(&f.panel) && !seen.contains(&f.panel) {
+                seen.push(f.panel.clone());
+                true
+            } else {
+                false
+            }
+        });
+        for floating in self.floating.iter_mut() {
+            floating.width = floating.width.max(MIN_FLOATING_WIDTH);
+            floating.height = floating.height.max(MIN_FLOATING_HEIGHT);
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.docked.is_empty() && self.floating.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(panel: &str) -> DockPanelId {
+        DockPanelId::new(panel)
+    }
+
+    fn ids(layout: &DockLayout) -> Vec<&str> {
+        layout.panels().into_iter().map(|p| p.as_str()).collect()
+    }
+
+    // a | (b, c) / d
+    fn layout() -> DockWindowLayout {
+        DockWindowLayout {
+            docked: DockLayout::horizontal(
+                0.3,
+                DockLayout::tabs(["a"]),
+                DockLayout::vertical(0.6, DockLayout::tabs(["b", "c"]), DockLayout::tabs(["d"])),
+            ),
+            floating: vec![DockFloatingPanel::new("e", 10, 20, 300, 200)],
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut layout = layout();
+        layout
+            .docked
+            .move_panel(&id("c"), &[0], DockDropZone::Bottom);
+        layout.docked.set_ratio(&[1], 0.25);
+        layout.float(&id("a"), 40, 50);
+
+        let json = serde_json::to_string(&layout).unwrap();
+        let restored: DockWindowLayout = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, layout);
+    }
+
+    #[test]
+    fn move_panel_between_stacks() {
+        let mut layout = layout().docked;
+        layout.move_panel(&id("b"), &[0], DockDropZone::Center);
+        assert_eq!(ids(&layout), ["a", "b", "c", "d"]);
+        assert_eq!(
+            layout.node(&[0]),
+            Some(&DockLayout::Tabs {
+                panels: vec![id("a"), id("b")],
+                active: 1,
+            })
+        );
+
+        // moving the last panel of a stack collapses its split
+        layout.move_panel(&id("d"), &[0], DockDropZone::Tab(0));
+        assert_eq!(ids(&layout), ["d", "a", "b", "c"]);
+        assert_eq!(layout.node(&[1]), Some(&DockLayout::tabs(["c"])));
+    }
+
+    #[test]
+    fn move_panel_to_edge() {
+        let mut layout = layout().docked;
+        layout.move_panel(&id("c"), &[1, 1], DockDropZone::Left);
+        assert_eq!(ids(&layout), ["a", "b", "c", "d"]);
+        assert!(matches!(
+            layout.node(&[1, 1]),
+            Some(DockLayout::Split {
+                direction: DockSplitDirection::Horizontal,
+                ..
+            })
+        ));
+
+        // a panel can't be split with itself
+        let before = layout.clone();
+        layout.move_panel(&id("d"), &[1, 1, 1], DockDropZone::Right);
+        assert_eq!(layout, before);
+    }
+
+    #[test]
+    fn reorder_tabs() {
+        let mut layout = DockLayout::tabs(["a", "b", "c"]);
+        layout.move_panel(&id("a"), &[], DockDropZone::Tab(3));
+        assert_eq!(
+            layout,
+            DockLayout::Tabs {
+                panels: vec![id("b"), id("c"), id("a")],
+                active: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn remove_panel_keeps_active() {
+        let mut layout = DockLayout::Tabs {
+            panels: vec![id("a"), id("b"), id("c")],
+            active: 2,
+        };
+        layout.remove_panel(&id("a"));
+        assert_eq!(
+            layout,
+            DockLayout::Tabs {
+                panels: vec![id("b"), id("c")],
+                active: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn sanitize_after_panel_removal() {
+        // `b`, `d` and `e` are no longer registered
+        let mut layout = layout();
+        if let Some(DockLayout::Tabs { active, .. }) = layout.docked.node_mut(&[1, 0]) {
+            *active = 1;
+        }
+        let json = serde_json::to_string(&layout).unwrap();
+        let mut restored: DockWindowLayout = serde_json::from_str(&json).unwrap();
+        restored.sanitize(&|panel| ["a", "c"].contains(&panel.as_str()));
+
+        assert_eq!(
+            restored,
+            DockWindowLayout {
+                docked: DockLayout::Split {
+                    direction: DockSplitDirection::Horizontal,
+                    ratio: 0.3,
+                    first: Box::new(DockLayout::tabs(["a"])),
+                    second: Box::new(DockLayout::tabs(["c"])),
+                },
+                floating: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn sanitize_duplicates_and_ratios() {
+        let mut layout = DockWindowLayout {
+            docked: DockLayout::Split {
+                direction: DockSplitDirection::Vertical,
+                ratio: f64::NAN,
+                first: Box::new(DockLayout::tabs(["a", "a"])),
+                second: Box::new(DockLayout::Tabs {
+                    panels: vec![id("a"), id("b")],
+                    active: 5,
+                }),
+            },
+            floating: vec![
+                DockFloatingPanel::new("b", 0, 0, 200, 100),
+                DockFloatingPanel::new("c", 0, 0, 200, 100),
+            ],
+        };
+        layout.sanitize(&|_| true);
+
+        assert_eq!(
+            layout.docked,
+            DockLayout::Split {
+                direction: DockSplitDirection::Vertical,
+                ratio: 0.5,
+                first: Box::new(DockLayout::tabs(["a"])),
+                second: Box::new(DockLayout::tabs(["b"])),
+            }
+        );
+        assert_eq!(
+            layout.floating,
+            [DockFloatingPanel::new("c", 0, 0, 200, 100)]
+        );
+    }
+
+    #[test]
+    fn insert_floating_undocks_panel() {
+        let mut layout = layout();
+        layout.insert_floating(DockFloatingPanel::new("a", 0, 0, 300, 200));
+        layout.insert_floating(DockFloatingPanel::new("e", 50, 60, 300, 200));
+
+        assert_eq!(ids(&layout.docked), ["b", "c", "d"]);
+        let floating: Vec<_> = layout
+            .floating
+            .iter()
+            .map(|f| (f.panel.as_str(), f.x, f.z_index))
+            .collect();
+        assert_eq!(floating, [("a", 0, 1), ("e", 50, 2)]);
+    }
+
+    #[test]
+    fn restore_missing_panels() {
+        let mut layout = layout();
+        layout.close(&id("a"));
+        layout.close(&id("e"));
+        layout.restore_missing(&[id("a"), id("b"), id("e")]);
+
+        assert_eq!(ids(&layout.docked), ["b", "c", "a", "e", "d"]);
+        assert!(layout.floating.is_empty());
+    }
+}
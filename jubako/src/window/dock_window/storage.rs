@@ -0,0 +1,62 @@
+//! Storages that persist dock layouts between sessions.
+
+use std::path::PathBuf;
+
+use crate::dock_window::DockWindowLayout;
+
+/// Storage that saves and restores the dock layout of each route.
+///
+/// `key` is the route path of the dock window, e.g. `/dock-window`.
+pub trait DockLayoutStorage: 'static + Send + Sync {
+    /// Load the saved layout. `Ok(None)` means that no layout is saved yet.
+    fn load(&self, key: &str) -> anyhow::Result<Option<DockWindowLayout>>;
+    /// Save the layout. This is called every time the user finishes rearranging the layout.
+    fn save(&self, key: &str, layout: &DockWindowLayout) -> anyhow::Result<()>;
+}
+
+/// Storage that saves each layout as a json file in a directory.
+///
+/// The file name is the percent-encoded key, e.g. `%2Fdock-window.json` for `/dock-window`,
+/// so that every key has its own file.
+#[derive(Clone, Debug)]
+pub struct DockLayoutFileStorage {
+    dir: PathBuf,
+}
+impl DockLayoutFileStorage {
+    /// Create a storage that saves layouts in `dir`. The directory is created on first save.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        // percent-encode every byte but ascii alphanumerics, `-` and `_`, which is reversible
+        let name: String = key
+            .bytes()
+            .map(|b| {
+                if b.is_ascii_alphanumeric() || b == b'-' || b == b'_' {
+                    char::from(b).to_string()
+                } else {
+                    format!("%{b:02X}")
+                }
+            })
+            .collect();
+        self.dir.join(format!("{name}.json"))
+    }
+}
+impl DockLayoutStorage for DockLayoutFileStorage {
+    fn load(&self, key: &str) -> anyhow::Result<Option<DockWindowLayout>> {
+        let path = self.path(key);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let json = std::fs::read_to_string(path)?;
+        Ok(Some(serde_json::from_str(&json)?))
+    }
+
+    fn save(&self, key: &str, layout: &DockWindowLayout) -> anyhow::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let json = serde_json::to_string_pretty(layout)?;
+        std::fs::write(self.path(key), json)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,112 @@
+//! Model trait and the adapter that runs a model as a [`SimpleWindow`].
+//!
+//! [`Model`] is an alternative to [`SimpleWindow`] for windows whose state is owned by the
+//! runner. `update` takes `&mut self`, so the state does not need interior mutability.
+//! [`ModelWindowCreator`] creates a model for each session and runs it as a [`SimpleWindow`],
+//! so it can be routed or docked wherever a [`SimpleWindowCreator`] is accepted.
+//!
+//! # Example
+
+use parking_lot::Mutex;
+use std::{fmt::Debug, marker::PhantomData, sync::Arc};
+
+use crate::simple_window::{SimpleWindow, SimpleWindowContext, SimpleWindowCreator};
+use crate::vnode::VNode;
+use crate::{Command, DirtyFlag, Message, Subscription};
+
+pub trait Model: 'static + Send {
+    type Message: Message;
+    /// Handle the message. Implement either this or [`Model::update_with_command`].
+    fn update(&mut self, _message: Self::Message) -> DirtyFlag {
+        DirtyFlag::None
+    }
+    /// Handle the message and return the command to run after that.
+    fn update_with_command(
+        &mut self,
+        message: Self::Message,
+    ) -> (DirtyFlag, Command<Self::Message>) {
+        (self.update(message), Command::none())
+    }
+    fn view(&self) -> VNode<Self::Message>;
+    /// Subscriptions that the model needs in the current state.
+    fn subscriptions(&self) -> Vec<Subscription<Self::Message>> {
+        vec![]
+    }
+    fn disconnected(&mut self) {}
+}
+
+/// Adapter that runs a [`Model`] as a [`SimpleWindow`].
+pub struct ModelWindow<M: Model> {
+    model: Mutex<M>,
+}
+impl<M: Model> ModelWindow<M> {
+    pub fn new(model: M) -> Self {
+        Self {
+            model: Mutex::new(model),
+        }
+    }
+}
+impl<M: Model> SimpleWindow for ModelWindow<M> {
+    type Message = M::Message;
+    fn update_with_command(&self, message: Self::Message) -> (DirtyFlag, Command<Self::Message>) {
+        self.model.lock().update_with_command(message)
+    }
+    fn view(&self) -> VNode<Self::Message> {
+        self.model.lock().view()
+    }
+    fn subscriptions(&self) -> Vec<Subscription<Self::Message>> {
+        self.model.lock().subscriptions()
+    }
+    fn disconnected(&self) {
+        self.model.lock().disconnected()
+    }
+}
+impl<M: Model> Debug for ModelWindow<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ModelWindow").finish()
+    }
+}
+
+/// [`SimpleWindowCreator`] that creates a [`Model`] with `create` for each session.
+pub struct ModelWindowCreator<M, F> {
+    create: F,
+    _model: PhantomData<fn() -> M>,
+}
+impl<M, F> ModelWindowCreator<M, F>
+where
+    M: Model,
+    F: FnMut(SimpleWindowContext<M::Message>) -> M + 'static + Clone + Send + Sync,
+{
+    pub fn new(create: F) -> Self {
+        Self {
+            create,
+            _model: PhantomData,
+        }
+    }
+}
+impl<M, F: Clone> Clone for ModelWindowCreator<M, F> {
+    fn clone(&self) -> Self {
+        Self {
+            create: self.create.clone(),
+            _model: PhantomData,
+        }
+    }
+}
+impl<M, F> SimpleWindowCreator for ModelWindowCreator<M, F>
+where
+    M: Model,
+    F: FnMut(SimpleWindowContext<M::Message>) -> M + 'static + Clone + Send + Sync,
+{
+    type Message = M::Message;
+    fn create(
+        &mut self,
+        ctx: SimpleWindowContext<Self::Message>,
+    ) -> Arc<dyn SimpleWindow<Message = Self::Message>> {
+        Arc::new(ModelWindow::new((self.create)(ctx)))
+    }
+}
+impl<M, F> Debug for ModelWindowCreator<M, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ModelWindowCreator").finish()
+    }
+}
@@ -6,30 +6,55 @@
 //!
 //! # Example
 
-use axum::extract::ws::{Message as WebSocketMessage, WebSocket};
+use axum::extract::ws::{CloseFrame, Message as WebSocketMessage, WebSocket};
 use parking_lot::Mutex;
 use std::{
+    collections::BTreeMap,
     fmt::Debug,
     sync::{
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicUsize, Ordering},
         Arc,
     },
 };
-use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tokio::{
+    sync::{
+        mpsc::{unbounded_channel, UnboundedSender},
+        watch,
+    },
+    time::Instant,
+};
+use typeshare::typeshare;
+use uuid::Uuid;
 
-use crate::vnode::{MessageHandledVNode, VNode};
-use crate::{DirtyFlag, Message};
+use crate::vnode::Processor;
+use crate::vnode::{
+    BrowserCommand, DomCommand, ElementQuery, ElementQueryResponse, ElementQueryResult,
+    ElementRect, MessageHandledVNode, MessageSender, NodeRef, ScrollIntoViewOptions, ScrollMetrics,
+    VNode,
+};
+use crate::{
+    Command, CommandRunner, ConnectionState, DirtyFlag, Message, SessionConfig, SessionHandle,
+    Subscription, SubscriptionRunner,
+};
 
 struct SimpleWindowContextInner<T: Message> {
     sender: UnboundedSender<T>,
+    session: SessionHandle,
+    panel: Option<String>,
+    connection_handler: Option<Arc<dyn Fn(ConnectionState) -> T + Send + Sync>>,
 }
 #[derive(Clone)]
 pub struct SimpleWindowContext<T: Message> {
     inner: Arc<Mutex<SimpleWindowContextInner<T>>>,
 }
 impl<T: Message> SimpleWindowContext<T> {
-    fn new(sender: UnboundedSender<T>) -> Self {
-        let inner = SimpleWindowContextInner { sender };
+    fn new(sender: UnboundedSender<T>, session: SessionHandle, panel: Option<String>) -> Self {
+        let inner = SimpleWindowContextInner {
+            sender,
+            session,
+            panel,
+            connection_handler: None,
+        };
         Self {
             inner: Arc::new(Mutex::new(inner)),
         }
@@ -39,6 +64,167 @@ impl<T: Message> SimpleWindowContext<T> {
         let inner = self.inner.lock();
         inner.sender.send(message).unwrap();
     }
+
+    /// Dispatch the message created from the state when the browser window disconnects or
+    /// reconnects, e.g. to show the connection status.
+    pub fn on_connection_changed(
+        &self,
+        handler: impl Fn(ConnectionState) -> T + Send + Sync + 'static,
+    ) {
+        self.inner.lock().connection_handler = Some(Arc::new(handler));
+    }
+
+    // dispatch the message of the connection state if the window handles it
+    fn connection_changed(&self, state: ConnectionState) {
+        let inner = self.inner.lock();
+        if let Some(handler) = &inner.connection_handler {
+            let _ = inner.sender.send(handler(state));
+        }
+    }
+
+    /// Open this window in a new browser window that shares the same session.
+    ///
+    /// For a panel of a dock window only the panel is opened, and the panel is hidden from
+    /// the dock until the new browser window is closed.
+    /// Browsers may block the new window unless this is called in response to a user input.
+    pub fn pop_out(&self) {
+        let inner = self.inner.lock();
+        inner.session.open_window(inner.panel.as_deref());
+    }
+
+    /// Show the panel of the dock window that this window is a panel of.
+    ///
+    /// The tab of a docked panel is activated, a floating panel is restored and raised,
+    /// and a closed panel is docked again as a tab of the first tab stack.
+    /// Nothing happens when the window is not in a dock window or `panel` is not registered.
+    pub fn open_panel(&self, panel: &str) {
+        self.inner.lock().session.open_panel(panel);
+    }
+
+    /// Focus the referenced element.
+    ///
+    /// DOM operations are applied in order after the browser renders the next frame,
+    /// so the element can be one that is rendered by the current update.
+    pub fn focus(&self, node_ref: &NodeRef) {
+        self.push_dom_command(DomCommand::Focus {
+            node_ref: node_ref.id().into(),
+        });
+    }
+
+    /// Remove the focus from the referenced element.
+    pub fn blur(&self, node_ref: &NodeRef) {
+        self.push_dom_command(DomCommand::Blur {
+            node_ref: node_ref.id().into(),
+        });
+    }
+
+    /// Scroll the ancestors of the referenced element to show the element.
+    pub fn scroll_into_view(&self, node_ref: &NodeRef, options: ScrollIntoViewOptions) {
+        self.push_dom_command(DomCommand::ScrollIntoView {
+            node_ref: node_ref.id().into(),
+            options,
+        });
+    }
+
+    /// Select the text from `start` to `end` in the referenced input or textarea.
+    pub fn set_selection_range(&self, node_ref: &NodeRef, start: u32, end: u32) {
+        self.push_dom_command(DomCommand::SetSelectionRange {
+            node_ref: node_ref.id().into(),
+            start,
+            end,
+        });
+    }
+
+    /// Select all text in the referenced input or textarea.
+    pub fn select(&self, node_ref: &NodeRef) {
+        self.push_dom_command(DomCommand::Select {
+            node_ref: node_ref.id().into(),
+        });
+    }
+
+    /// Click the referenced element, e.g. to open the dialog of a file input.
+    pub fn click(&self, node_ref: &NodeRef) {
+        self.push_dom_command(DomCommand::Click {
+            node_ref: node_ref.id().into(),
+        });
+    }
+
+    fn push_dom_command(&self, command: DomCommand) {
+        self.inner.lock().session.push_dom_command(command);
+    }
+
+    /// Ask the browser for the bounding rect of the referenced element, and dispatch the message
+    /// created from the rect when the browser responds.
+    ///
+    /// The rect is `None` if the element is not rendered, or the browser does not respond because
+    /// it is disconnected. Elements in popped out browser windows are not queried.
+    pub fn bounding_rect(
+        &self,
+        node_ref: &NodeRef,
+        f: impl FnOnce(Option<ElementRect>) -> T + Send + 'static,
+    ) {
+        self.query_element(
+            node_ref,
+            ElementQuery::BoundingRect,
+            |result| match result {
+                Some(ElementQueryResult::BoundingRect(rect)) => f(Some(rect)),
+                _ => f(None),
+            },
+        );
+    }
+
+    /// Ask the browser for the computed values of the CSS properties of the referenced element,
+    /// like [`SimpleWindowContext::bounding_rect`].
+    pub fn computed_style(
+        &self,
+        node_ref: &NodeRef,
+        properties: &[&str],
+        f: impl FnOnce(Option<BTreeMap<String, String>>) -> T + Send + 'static,
+    ) {
+        let properties = properties.iter().map(|name| name.to_string()).collect();
+        self.query_element(
+            node_ref,
+            ElementQuery::ComputedStyle(properties),
+            |result| match result {
+                Some(ElementQueryResult::ComputedStyle(style)) => f(Some(style)),
+                _ => f(None),
+            },
+        );
+    }
+
+    /// Ask the browser for the scroll position and sizes of the referenced element,
+    /// like [`SimpleWindowContext::bounding_rect`].
+    pub fn scroll_metrics(
+        &self,
+        node_ref: &NodeRef,
+        f: impl FnOnce(Option<ScrollMetrics>) -> T + Send + 'static,
+    ) {
+        self.query_element(
+            node_ref,
+            ElementQuery::ScrollMetrics,
+            |result| match result {
+                Some(ElementQueryResult::ScrollMetrics(metrics)) => f(Some(metrics)),
+                _ => f(None),
+            },
+        );
+    }
+
+    fn query_element(
+        &self,
+        node_ref: &NodeRef,
+        query: ElementQuery,
+        f: impl FnOnce(Option<ElementQueryResult>) -> T + Send + 'static,
+    ) {
+        let inner = self.inner.lock();
+        let sender = inner.sender.clone();
+        inner.session.query_element(
+            node_ref,
+            query,
+            Box::new(move |result| {
+                let _ = sender.send(f(result));
+            }),
+        );
+    }
 }
 impl<T: Message> Debug for SimpleWindowContext<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -56,8 +242,20 @@ pub trait SimpleWindowCreator: 'static + Clone + Send + Sync {
 
 pub trait SimpleWindow: 'static + Send + Sync {
     type Message: Message;
-    fn update(&self, message: Self::Message) -> DirtyFlag;
+    /// Handle the message. Implement either this or [`SimpleWindow::update_with_command`].
+    fn update(&self, _message: Self::Message) -> DirtyFlag {
+        DirtyFlag::None
+    }
+    /// Handle the message and return the command to run after that.
+    fn update_with_command(&self, message: Self::Message) -> (DirtyFlag, Command<Self::Message>) {
+        (self.update(message), Command::none())
+    }
     fn view(&self) -> VNode<Self::Message>;
+    /// Subscriptions that the window needs in the current state.
+    /// This is called after every update to start and stop the subscriptions.
+    fn subscriptions(&self) -> Vec<Subscription<Self::Message>> {
+        vec![]
+    }
     fn disconnected(&self) {}
 }
 impl<T: SimpleWindow + ?Sized> SimpleWindow for Arc<T> {
@@ -65,153 +263,518 @@ impl<T: SimpleWindow + ?Sized> SimpleWindow for Arc<T> {
     fn update(&self, message: Self::Message) -> DirtyFlag {
         T::update(self, message)
     }
+    fn update_with_command(&self, message: Self::Message) -> (DirtyFlag, Command<Self::Message>) {
+        T::update_with_command(self, message)
+    }
     fn view(&self) -> crate::vnode::VNode<Self::Message> {
         T::view(self)
     }
+    fn subscriptions(&self) -> Vec<Subscription<Self::Message>> {
+        T::subscriptions(self)
+    }
     fn disconnected(&self) {
         T::disconnected(self)
     }
 }
 
-trait SimpleWindowCallback: 'static + Send + Sync {
+pub(crate) trait SimpleWindowCallback: 'static + Send + Sync {
     fn view(&self) -> MessageHandledVNode;
+    // view of the panel that is popped out into another browser window,
+    // the root can be any vnode such as a text or a fragment
+    fn view_panel(&self, _panel: &str) -> Option<MessageHandledVNode> {
+        None
+    }
+    // called when a browser window of the panel is opened or closed
+    fn set_popped_out(&self, _panel: &str, _popped_out: bool) {}
     fn disconnected(&self) {}
+    fn connection_changed(&self, _state: ConnectionState) {}
 }
+// number of the event messages that the window has not handled yet,
+// counted for each connection that received the events
+type PendingEventMessages = Arc<AtomicUsize>;
+
+tokio::task_local! {
+    // pending event messages of the connection that is handling an event
+    static EVENT_CONNECTION: PendingEventMessages;
+}
+
 struct SimpleWindowCallbackImpl<T: Message> {
     window: Arc<dyn SimpleWindow<Message = T>>,
-    sender: UnboundedSender<T>,
+    // sender of the messages of event handlers, with the connection that received the event
+    sender: UnboundedSender<(T, Option<PendingEventMessages>)>,
+    context: SimpleWindowContext<T>,
+    session: SessionHandle,
+    commands: CommandRunner<T>,
+    subscriptions: SubscriptionRunner<T>,
 }
 impl<T: Message> SimpleWindowCallbackImpl<T> {
-    fn new(window: Arc<dyn SimpleWindow<Message = T>>, sender: UnboundedSender<T>) -> Box<Self> {
-        Box::new(Self { window, sender })
+    fn new(
+        window: Arc<dyn SimpleWindow<Message = T>>,
+        sender: UnboundedSender<(T, Option<PendingEventMessages>)>,
+        context: SimpleWindowContext<T>,
+        session: SessionHandle,
+        commands: CommandRunner<T>,
+        subscriptions: SubscriptionRunner<T>,
+    ) -> Box<Self> {
+        Box::new(Self {
+            window,
+            sender,
+            context,
+            session,
+            commands,
+            subscriptions,
+        })
     }
 }
 impl<T: Message> SimpleWindowCallback for SimpleWindowCallbackImpl<T> {
     fn view(&self) -> MessageHandledVNode {
-        MessageHandledVNode::handle_message(self.window.view(), self.sender.clone())
+        let session = self.session.clone();
+        let sender = self.sender.clone();
+        let sender = MessageSender::from_fn(
+            move |message| {
+                let pending = EVENT_CONNECTION
+                    .try_with(|pending| {
+                        pending.fetch_add(1, Ordering::SeqCst);
+                        pending.clone()
+                    })
+                    .ok();
+                let _ = sender.send((message, pending));
+            },
+            move || session.request_render(),
+        );
+        MessageHandledVNode::handle_message(self.window.view(), sender)
     }
     fn disconnected(&self) {
+        self.commands.cancel_all();
+        self.subscriptions.cancel_all();
         self.window.disconnected()
     }
+    fn connection_changed(&self, state: ConnectionState) {
+        self.context.connection_changed(state);
+    }
+}
+
+// create a window and spawn the task that updates the window with dispatched messages,
+// `panel` is the id of the dock panel that the window is shown in
+pub(crate) fn create_window<T: Message>(
+    mut window_creator: impl SimpleWindowCreator<Message = T>,
+    session: SessionHandle,
+    panel: Option<String>,
+) -> Box<dyn SimpleWindowCallback> {
+    let (message_sender, mut message_receiver) = unbounded_channel();
+    // messages of event handlers are counted for the connection that received the event,
+    // so that its frames tell the browser which events are reflected in the view
+    let (event_sender, mut event_receiver) =
+        unbounded_channel::<(T, Option<PendingEventMessages>)>();
+
+    let context = SimpleWindowContext::new(message_sender.clone(), session.clone(), panel);
+    let callback_session = session.clone();
+    let window = window_creator.create(context.clone());
+    let commands = CommandRunner::new(message_sender.clone());
+    let subscriptions = SubscriptionRunner::new(message_sender.clone());
+    subscriptions.update(window.subscriptions());
+
+    tokio::spawn({
+        let window = window.clone();
+        let commands = commands.clone();
+        let subscriptions = subscriptions.clone();
+        async move {
+            loop {
+                // messages of events are handled first, so that the browser sees the input
+                // reflected as soon as possible
+                let (message, is_event, pending) = tokio::select! {
+                    biased;
+                    Some((message, pending)) = event_receiver.recv() => (message, true, pending),
+                    Some(message) = message_receiver.recv() => (message, false, None),
+                    else => break,
+                };
+                let (should_render, command) = window.update_with_command(message);
+                if let Some(pending) = pending {
+                    pending.fetch_sub(1, Ordering::SeqCst);
+                }
+                // the frame tells the browser that the event is handled even if nothing changed,
+                // so it is requested after the count is updated
+                if is_event || should_render == DirtyFlag::ShouldRender {
+                    session.request_render();
+                }
+                commands.run(command);
+                subscriptions.update(window.subscriptions());
+            }
+        }
+    });
+
+    SimpleWindowCallbackImpl::new(
+        window,
+        event_sender,
+        context,
+        callback_session,
+        commands,
+        subscriptions,
+    )
+}
+
+// close codes that tell the browser not to resume the session again,
+// kept in sync with connection.ts
+// another browser window resumed the session, e.g. a duplicated tab
+const CLOSE_SUPERSEDED: u16 = 4001;
+// the session of the pop-out window has ended
+pub(crate) const CLOSE_SESSION_EXPIRED: u16 = 4002;
+
+// close the websocket with the code
+pub(crate) async fn close_websocket(mut websocket: WebSocket, code: u16, reason: &'static str) {
+    let frame = CloseFrame {
+        code,
+        reason: reason.into(),
+    };
+    let _ = websocket.send(WebSocketMessage::Close(Some(frame))).await;
+}
+
+// number of frames that can be sent before the browser acknowledges them
+const MAX_FRAMES_IN_FLIGHT: u64 = 2;
+
+// acknowledgement sent by the browser after it applied a frame
+#[typeshare]
+#[derive(serde::Deserialize, Clone, Debug)]
+pub(crate) struct FrameAck {
+    pub(crate) frame: u64,
+}
+
+// role of a websocket connection in the session
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ConnectionRole {
+    // the connection that drives the session, with its generation
+    Primary(u64),
+    // another browser window that renders a panel, or the whole window
+    PopOut,
+    // one of the connections that share the window
+    Shared,
+}
+impl ConnectionRole {
+    // browser commands are sent to the browser window that created the session,
+    // or to every browser that shares the window
+    fn receives_browser_commands(self) -> bool {
+        self != ConnectionRole::PopOut
+    }
 }
 
 struct SimpleWindowRunnerInner {
     window: Box<dyn SimpleWindowCallback>,
-    dirty: Arc<AtomicBool>,
-    vnode_processor: crate::vnode::Processor,
+    session: SessionHandle,
+    // generation of the connection that currently drives the session
+    primary: watch::Sender<u64>,
+    // rendered tree of the disconnected primary connection, kept to resume the session
+    primary_processor: Mutex<Option<Processor>>,
+    closed: watch::Sender<bool>,
 }
 impl Debug for SimpleWindowRunnerInner {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("SimpleWindowRunnerInner")
-            .field("dirty", &self.dirty)
-            .field("vnode_processor", &self.vnode_processor)
+            .field("session", &self.session)
+            .field("primary", &*self.primary.borrow())
+            .field("closed", &*self.closed.borrow())
             .finish()
     }
 }
 
-#[derive(Debug)]
+// a session of a window, that is rendered by one or more websocket connections
+#[derive(Clone, Debug)]
 pub(crate) struct SimpleWindowRunner {
-    inner: Arc<Mutex<SimpleWindowRunnerInner>>,
+    inner: Arc<SimpleWindowRunnerInner>,
 }
 impl SimpleWindowRunner {
-    pub(crate) fn new<T: Message>(
-        mut window_creator: impl SimpleWindowCreator<Message = T>,
+    pub(crate) fn new<T: Message>(window_creator: impl SimpleWindowCreator<Message = T>) -> Self {
+        let session = SessionHandle::new();
+        let window = create_window(window_creator, session.clone(), None);
+        Self::with_window(window, session)
+    }
+
+    // run the window that is already created in the session
+    pub(crate) fn with_window(
+        window: Box<dyn SimpleWindowCallback>,
+        session: SessionHandle,
     ) -> Self {
-        let (message_sender, mut message_receiver) = unbounded_channel();
-
-        let context = SimpleWindowContext::new(message_sender.clone());
-        let window = window_creator.create(context);
-
-        let dirty = Arc::new(AtomicBool::new(true));
-
-        tokio::spawn({
-            let window = window.clone();
-            let dirty = dirty.clone();
-            async move {
-                loop {
-                    if let Some(message) = message_receiver.recv().await {
-                        let should_render = window.update(message);
-                        if should_render == DirtyFlag::ShouldRender {
-                            dirty.store(true, Ordering::Relaxed);
-                        }
-                    } else {
-                        break;
-                    }
-                }
+        let (primary, _) = watch::channel(0);
+        let (closed, _) = watch::channel(false);
+        Self {
+            inner: Arc::new(SimpleWindowRunnerInner {
+                window,
+                session,
+                primary,
+                primary_processor: Mutex::new(None),
+                closed,
+            }),
+        }
+    }
+
+    pub(crate) fn session_id(&self) -> Uuid {
+        self.inner.session.id()
+    }
+
+    // true while the primary connection is lost and the grace period is running
+    pub(crate) fn is_awaiting_resume(&self) -> bool {
+        !*self.inner.closed.borrow() && self.inner.primary_processor.lock().is_some()
+    }
+
+    // run the connection that creates or resumes the session, and returns true if the session
+    // ended. the session ends when no connection resumes it within the grace period.
+    pub(crate) async fn run(&self, websocket: WebSocket, config: SessionConfig) -> bool {
+        let generation = {
+            self.inner
+                .primary
+                .send_modify(|generation| *generation += 1);
+            *self.inner.primary.borrow()
+        };
+        self.inner
+            .session
+            .push_browser_command(BrowserCommand::SetSession {
+                id: self.session_id().to_string(),
+            });
+
+        // handlers of the resumed tree handle the events that the browser queued while offline
+        let vnode_processor = match self.inner.primary_processor.lock().take() {
+            Some(mut vnode_processor) => {
+                vnode_processor.reset();
+                vnode_processor
             }
+            None => Processor::new(),
+        };
+        self.inner.window.connection_changed(if generation == 1 {
+            ConnectionState::Connected
+        } else {
+            ConnectionState::Resumed
         });
 
-        let vnode_processor = crate::vnode::Processor::new();
-
-        Self {
-            inner: Arc::new(Mutex::new(SimpleWindowRunnerInner {
-                window: SimpleWindowCallbackImpl::new(window, message_sender),
-                dirty,
+        let vnode_processor = self
+            .run_connection(
+                websocket,
                 vnode_processor,
-            })),
+                None,
+                ConnectionRole::Primary(generation),
+                config.clone(),
+            )
+            .await;
+
+        let mut primary = self.inner.primary.subscribe();
+        if *self.inner.closed.borrow() {
+            return true;
+        }
+        if *primary.borrow_and_update() != generation {
+            // another connection resumed the session
+            return false;
         }
+        *self.inner.primary_processor.lock() = Some(vnode_processor);
+        // the browser can't respond to the queries sent to the closed connection
+        self.inner.session.cancel_element_queries();
+        self.inner
+            .window
+            .connection_changed(ConnectionState::Disconnected);
+        let resumed = tokio::time::timeout(config.grace_period, primary.changed())
+            .await
+            .is_ok();
+        if resumed {
+            return false;
+        }
+
+        self.end();
+        true
     }
 
-    fn is_dirty(&self) -> bool {
-        self.inner.lock().dirty.load(Ordering::Relaxed)
+    // end the session, the window is disconnected and the remaining connections are closed
+    pub(crate) fn end(&self) {
+        self.inner.session.cancel_element_queries();
+        self.inner.window.disconnected();
+        self.inner.closed.send_replace(true);
     }
 
-    fn reset_dirty(&self) {
-        self.inner.lock().dirty.store(false, Ordering::Relaxed)
+    // run a connection of a window that is shared by all connections,
+    // each connection renders the window on its own and dispatches events to the shared window
+    pub(crate) async fn run_shared(&self, websocket: WebSocket, config: SessionConfig) {
+        self.run_connection(
+            websocket,
+            Processor::new(),
+            None,
+            ConnectionRole::Shared,
+            config,
+        )
+        .await;
+    }
+
+    // run a connection of another browser window that renders the panel,
+    // or the whole window when `panel` is None
+    pub(crate) async fn run_pop_out(
+        &self,
+        websocket: WebSocket,
+        panel: Option<String>,
+        config: SessionConfig,
+    ) {
+        if let Some(panel) = &panel {
+            self.inner.window.set_popped_out(panel, true);
+            self.inner.session.request_render();
+        }
+        self.run_connection(
+            websocket,
+            Processor::new(),
+            panel.as_deref(),
+            ConnectionRole::PopOut,
+            config,
+        )
+        .await;
+        if let Some(panel) = &panel {
+            self.inner.window.set_popped_out(panel, false);
+            self.inner.session.request_render();
+        }
     }
 
-    pub(crate) async fn run(&self, mut websocket: WebSocket) {
+    // push frames to the browser every time a render is requested,
+    // no more often than the frame rate and no more frames than the browser can keep up with.
+    // returns the processor that holds the rendered tree.
+    async fn run_connection(
+        &self,
+        mut websocket: WebSocket,
+        mut vnode_processor: Processor,
+        panel: Option<&str>,
+        role: ConnectionRole,
+        config: SessionConfig,
+    ) -> Processor {
+        // each connection has its own rendered vnodes and event handlers
+        let mut render_version = self.inner.session.subscribe_render();
+        let mut rendered_version = 0;
+        let mut closed = self.inner.closed.subscribe();
+        if *closed.borrow() {
+            return vnode_processor;
+        }
+        let mut connection_commands = vec![BrowserCommand::ConfigureConnection {
+            offline_events: config.offline_events,
+        }];
+        let mut superseded = self.inner.primary.subscribe();
+        let command_receiver = role
+            .receives_browser_commands()
+            .then(|| self.inner.session.add_command_receiver());
+
+        let mut dirty = true;
+        let mut sent_frame = 0;
+        let mut acked_frame = 0;
+        // events received from the browser, and those reflected in the rendered view
+        let mut received_events = 0;
+        let mut handled_events = 0;
+        let pending_event_messages = PendingEventMessages::default();
+        let mut next_frame_at = Instant::now();
+        let mut is_superseded = false;
+
         loop {
-            match websocket.recv().await {
-                Some(msg) => {
+            let can_draw = dirty && sent_frame - acked_frame < MAX_FRAMES_IN_FLIGHT;
+            tokio::select! {
+                msg = websocket.recv() => {
                     match msg {
-                        Ok(WebSocketMessage::Text(text)) => {
-                            if &text == r#""DRAW""# {
-                                if let Some(commands) = self.draw() {
-                                    websocket
-                                        .send(WebSocketMessage::Text(
-                                            serde_json::to_string(&commands).unwrap(),
-                                        ))
-                                        .await
-                                        .unwrap();
-                                }
-                            } else if let Ok(event) =
-                                serde_json::from_str::<crate::vnode::VNodeEvent>(&text)
-                            {
-                                self.handle_event(event);
+                        Some(Ok(WebSocketMessage::Text(text))) => {
+                            if let Ok(event) = serde_json::from_str::<crate::vnode::VNodeEvent>(&text) {
+                                received_events += 1;
+                                EVENT_CONNECTION.sync_scope(pending_event_messages.clone(), || {
+                                    vnode_processor.handle_event(event)
+                                });
+                            } else if let Ok(ack) = serde_json::from_str::<FrameAck>(&text) {
+                                acked_frame = acked_frame.max(ack.frame.min(sent_frame));
+                                // the browser rendered the new tree, so events of the old tree are stale
+                                vnode_processor.forget_stale_events();
+                            } else if let Ok(response) = serde_json::from_str::<ElementQueryResponse>(&text) {
+                                self.inner.session.resolve_element_query(response);
                             }
                         }
-                        Ok(WebSocketMessage::Close(_)) => {
-                            // connection is ended
-                            break;
-                        }
+                        // connection is ended
+                        Some(Ok(WebSocketMessage::Close(_))) | None => break,
                         _ => (),
                     }
                 }
-                None => {
-                    // connection is ended
-                    break;
+                _ = closed.changed() => break,
+                // the session is resumed by a new connection
+                _ = superseded.changed(), if matches!(role, ConnectionRole::Primary(_)) => {
+                    if ConnectionRole::Primary(*superseded.borrow_and_update()) != role {
+                        is_superseded = true;
+                        break;
+                    }
+                }
+                changed = render_version.changed(), if !dirty => {
+                    if changed.is_err() {
+                        break;
+                    }
+                    dirty = true;
+                }
+                _ = tokio::time::sleep_until(next_frame_at), if can_draw => {
+                    dirty = false;
+                    // the view is rendered after the messages of the received events are handled
+                    if pending_event_messages.load(Ordering::SeqCst) == 0 {
+                        handled_events = received_events;
+                    }
+                    let version = *render_version.borrow_and_update();
+                    let commands = self.draw(
+                        &mut vnode_processor,
+                        version,
+                        &mut rendered_version,
+                        panel,
+                        command_receiver,
+                    );
+                    if let Some(mut commands) = commands {
+                        commands
+                            .browser
+                            .splice(0..0, std::mem::take(&mut connection_commands));
+                        sent_frame += 1;
+                        commands.frame = sent_frame;
+                        commands.events = handled_events;
+                        let sent = websocket
+                            .send(WebSocketMessage::Text(
+                                serde_json::to_string(&commands).unwrap(),
+                            ))
+                            .await;
+                        if sent.is_err() {
+                            break;
+                        }
+                        next_frame_at = Instant::now() + config.frame_interval;
+                    }
                 }
             }
         }
-        self.inner.lock().window.disconnected();
+        if let Some(receiver) = command_receiver {
+            self.inner.session.remove_command_receiver(receiver);
+        }
+        if is_superseded {
+            close_websocket(websocket, CLOSE_SUPERSEDED, "superseded").await;
+        }
+        vnode_processor
     }
 
-    fn draw(&self) -> Option<crate::vnode::DifferenceCommands> {
-        if self.is_dirty() {
-            let commands = {
-                let mut inner = self.inner.lock();
-                let vnode = inner.window.view();
-                inner.vnode_processor.next(vnode)
-            };
-            self.reset_dirty();
-            Some(commands)
-        } else {
-            None
+    fn draw(
+        &self,
+        vnode_processor: &mut Processor,
+        version: u64,
+        rendered_version: &mut u64,
+        panel: Option<&str>,
+        command_receiver: Option<u64>,
+    ) -> Option<crate::vnode::DifferenceCommands> {
+        let (browser, dom) = match command_receiver {
+            Some(receiver) => self.inner.session.take_commands(receiver),
+            None => (vec![], vec![]),
+        };
+
+        if version == *rendered_version && browser.is_empty() && dom.is_empty() {
+            return None;
         }
-    }
 
-    fn handle_event(&self, event: crate::vnode::VNodeEvent) {
-        self.inner.lock().vnode_processor.handle_event(event);
+        let mut commands = if version != *rendered_version {
+            let vnode = match panel {
+                None => self.inner.window.view(),
+                Some(panel) => self.inner.window.view_panel(panel)?,
+            };
+            // the first frame of a connection renders the whole tree
+            let reset = *rendered_version == 0;
+            *rendered_version = version;
+            let mut commands = vnode_processor.next(vnode);
+            commands.reset = reset;
+            commands
+        } else {
+            Default::default()
+        };
+        commands.dom = dom;
+        commands.browser = browser;
+        Some(commands)
     }
 }
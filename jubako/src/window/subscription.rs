@@ -0,0 +1,172 @@
+//! Subscriptions that windows declare to receive messages from timers and streams.
+
+use futures_util::{Stream, StreamExt};
+use parking_lot::Mutex;
+use std::{collections::HashMap, fmt::Debug, future::Future, pin::Pin, sync::Arc, time::Duration};
+use tokio::{sync::mpsc::UnboundedSender, task::JoinHandle, time::Instant};
+
+use crate::Message;
+
+type Emit<T> = Arc<dyn Fn(T) + Send + Sync>;
+type SubscriptionStart<T> =
+    Box<dyn FnOnce(Emit<T>) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send>;
+
+/// Source of messages that runs while the window returns it from `subscriptions`.
+///
+/// Subscriptions are identified by their keys. A subscription is started when its key appears
+/// in `subscriptions`, and stopped when the key disappears or the window is disconnected.
+/// A subscription that has finished, such as a timeout that has fired or a stream that has
+/// ended, is started again if its key is still returned.
+pub struct Subscription<T: Message> {
+    key: String,
+    start: SubscriptionStart<T>,
+}
+impl<T: Message> Subscription<T> {
+    /// Dispatch the message created by `message` every `period`.
+    pub fn interval(
+        key: impl Into<String>,
+        period: Duration,
+        message: impl Fn() -> T + Send + 'static,
+    ) -> Self {
+        Self {
+            key: key.into(),
+            start: Box::new(move |emit| {
+                Box::pin(async move {
+                    let mut interval = tokio::time::interval_at(Instant::now() + period, period);
+                    loop {
+                        interval.tick().await;
+                        emit(message());
+                    }
+                })
+            }),
+        }
+    }
+
+    /// Dispatch the message once after `delay`.
+    pub fn timeout(key: impl Into<String>, delay: Duration, message: T) -> Self {
+        Self {
+            key: key.into(),
+            start: Box::new(move |emit| {
+                Box::pin(async move {
+                    tokio::time::sleep(delay).await;
+                    emit(message);
+                })
+            }),
+        }
+    }
+
+    /// Dispatch the items of the stream created by `create`.
+    pub fn stream<S>(key: impl Into<String>, create: impl FnOnce() -> S + Send + 'static) -> Self
+    where
+        S: Stream<Item = T> + Send + 'static,
+    {
+        Self {
+            key: key.into(),
+            start: Box::new(move |emit| {
+                Box::pin(async move {
+                    let mut stream = Box::pin(create());
+                    while let Some(message) = stream.next().await {
+                        emit(message);
+                    }
+                })
+            }),
+        }
+    }
+
+    /// Convert the messages that the subscription dispatches.
+    pub fn map<U: Message>(self, f: impl Fn(T) -> U + Send + Sync + 'static) -> Subscription<U> {
+        let start = self.start;
+        Subscription {
+            key: self.key,
+            start: Box::new(move |emit: Emit<U>| start(Arc::new(move |message| emit(f(message))))),
+        }
+    }
+
+    /// Key that identifies the subscription.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+}
+impl<T: Message> Debug for Subscription<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Subscription")
+            .field("key", &self.key)
+            .finish()
+    }
+}
+
+struct SubscriptionRunnerInner<T: Message> {
+    sender: UnboundedSender<T>,
+    running: HashMap<String, JoinHandle<()>>,
+    cancelled: bool,
+}
+
+// starts and stops the subscriptions of a window
+pub(crate) struct SubscriptionRunner<T: Message> {
+    inner: Arc<Mutex<SubscriptionRunnerInner<T>>>,
+}
+impl<T: Message> SubscriptionRunner<T> {
+    pub(crate) fn new(sender: UnboundedSender<T>) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(SubscriptionRunnerInner {
+                sender,
+                running: HashMap::new(),
+                cancelled: false,
+            })),
+        }
+    }
+
+    // start the new subscriptions and stop the subscriptions that are no longer returned
+    pub(crate) fn update(&self, subscriptions: Vec<Subscription<T>>) {
+        let mut inner = self.inner.lock();
+        if inner.cancelled {
+            return;
+        }
+
+        let mut running = HashMap::new();
+        for subscription in subscriptions {
+            if running.contains_key(&subscription.key) {
+                continue;
+            }
+            // a finished task is started again, e.g. a timeout that is still returned after firing
+            let task = match inner.running.remove(&subscription.key) {
+                Some(task) if !task.is_finished() => task,
+                _ => {
+                    let sender = inner.sender.clone();
+                    let emit: Emit<T> = Arc::new(move |message| {
+                        let _ = sender.send(message);
+                    });
+                    tokio::spawn((subscription.start)(emit))
+                }
+            };
+            running.insert(subscription.key, task);
+        }
+
+        for (_, task) in std::mem::replace(&mut inner.running, running) {
+            task.abort();
+        }
+    }
+
+    // stop all subscriptions, and ignore the subscriptions updated after this
+    pub(crate) fn cancel_all(&self) {
+        let mut inner = self.inner.lock();
+        inner.cancelled = true;
+        for (_, task) in inner.running.drain() {
+            task.abort();
+        }
+    }
+}
+impl<T: Message> Clone for SubscriptionRunner<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+impl<T: Message> Debug for SubscriptionRunner<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SubscriptionRunner")
+            .field("running", &self.inner.lock().running.keys())
+            .finish()
+    }
+}